@@ -0,0 +1,430 @@
+use crate::buffers::{BufferViewTarget, GltfBase64Buffer, GltfBufferView};
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::{GltfObject, GltrError, GltrResult};
+
+impl GltfAccessor {
+	/// number of components in a single element, eg 3 for VEC3 and 16 for MAT4. 0 for an unrecognised type
+	pub fn component_count(&self) -> usize {
+		type_component_count(&self.r#type)
+	}
+
+	/// size in bytes of a single element, including the column padding required for some matrix types
+	pub fn element_size(&self) -> usize {
+		let (columns, _, column_stride) = element_layout(&self.r#type, self.component_type);
+		columns * column_stride
+	}
+}
+
+pub(crate) fn type_component_count(accessor_type: &str) -> usize {
+	match accessor_type {
+		"SCALAR" => 1,
+		"VEC2" => 2,
+		"VEC3" => 3,
+		"VEC4" | "MAT2" => 4,
+		"MAT3" => 9,
+		"MAT4" => 16,
+		_ => 0,
+	}
+}
+
+/// (columns, rows, column stride in bytes) of an element.
+///
+/// see 3.6.2.4 Data Alignment, matrix columns start on 4-byte boundaries
+fn element_layout(accessor_type: &str, component_type: ComponentType) -> (usize, usize, usize) {
+	let size = component_type.byte_size();
+	match accessor_type {
+		"MAT2" => (2, 2, (2 * size).next_multiple_of(4)),
+		"MAT3" => (3, 3, (3 * size).next_multiple_of(4)),
+		"MAT4" => (4, 4, 4 * size),
+		other => {
+			let n = type_component_count(other);
+			(1, n, n * size)
+		}
+	}
+}
+
+/// reads a single component, converting normalized integers to their float value when `normalize` is set.
+///
+/// see 3.11 Animations for the normalization equations
+fn read_component(bytes: &[u8], component_type: ComponentType, normalize: bool) -> f32 {
+	match component_type {
+		ComponentType::BYTE => {
+			let v = bytes[0] as i8 as f32;
+			if normalize { (v / 127f32).max(-1f32) } else { v }
+		}
+		ComponentType::UNSIGNED_BYTE => {
+			let v = bytes[0] as f32;
+			if normalize { v / 255f32 } else { v }
+		}
+		ComponentType::SHORT => {
+			let v = i16::from_le_bytes([bytes[0], bytes[1]]) as f32;
+			if normalize { (v / 32767f32).max(-1f32) } else { v }
+		}
+		ComponentType::UNSIGNED_SHORT => {
+			let v = u16::from_le_bytes([bytes[0], bytes[1]]) as f32;
+			if normalize { v / 65535f32 } else { v }
+		}
+		ComponentType::UNSIGNED_INT => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32,
+		_ => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+	}
+}
+
+fn read_component_u32(bytes: &[u8], component_type: ComponentType) -> u32 {
+	match component_type {
+		ComponentType::BYTE => bytes[0] as i8 as u32,
+		ComponentType::UNSIGNED_BYTE => bytes[0] as u32,
+		ComponentType::SHORT => i16::from_le_bytes([bytes[0], bytes[1]]) as u32,
+		ComponentType::UNSIGNED_SHORT => u16::from_le_bytes([bytes[0], bytes[1]]) as u32,
+		ComponentType::UNSIGNED_INT => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+		_ => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as u32,
+	}
+}
+
+/// encodes a single component, converting from a float in the normalized range when `normalize` is set
+fn write_component(out: &mut Vec<u8>, value: f32, component_type: ComponentType, normalize: bool) {
+	match component_type {
+		ComponentType::BYTE => {
+			let v = if normalize { (value * 127f32).round() } else { value.round() };
+			out.push(v.clamp(-128f32, 127f32) as i8 as u8)
+		}
+		ComponentType::UNSIGNED_BYTE => {
+			let v = if normalize { (value * 255f32).round() } else { value.round() };
+			out.push(v.clamp(0f32, 255f32) as u8)
+		}
+		ComponentType::SHORT => {
+			let v = if normalize { (value * 32767f32).round() } else { value.round() };
+			out.extend_from_slice(&(v.clamp(-32768f32, 32767f32) as i16).to_le_bytes())
+		}
+		ComponentType::UNSIGNED_SHORT => {
+			let v = if normalize { (value * 65535f32).round() } else { value.round() };
+			out.extend_from_slice(&(v.clamp(0f32, 65535f32) as u16).to_le_bytes())
+		}
+		ComponentType::UNSIGNED_INT => out.extend_from_slice(&(value.max(0f32).round() as u32).to_le_bytes()),
+		_ => out.extend_from_slice(&value.to_le_bytes()),
+	}
+}
+
+impl GltfObject {
+	/// the bytes of a buffer view, decoded from its buffer
+	pub fn buffer_view_bytes(&self, view_idx: Index<GltfBufferView>) -> GltrResult<Vec<u8>> {
+		let view = self.try_get(view_idx)?;
+
+		let buffer = match self.get(view.buffer) {
+			None => return Err(GltrError::invalid_index(format!("/bufferViews/{view_idx}/buffer"), view.buffer.value())),
+			Some(buffer) => buffer,
+		};
+
+		let bytes = buffer.bytes()?;
+
+		let end = view.byte_offset + view.byte_length;
+		if bytes.len() < end {
+			return Err(GltrError::OutOfRange {
+				path: format!("/bufferViews/{view_idx}"),
+				offset: view.byte_offset,
+				length: view.byte_length,
+				available: bytes.len(),
+			});
+		}
+
+		Ok(bytes[view.byte_offset..end].to_vec())
+	}
+
+	/// reads every component of an accessor as f32, flattened in element order.
+	///
+	/// normalized integer accessors are converted to their float values, sparse substitutions are applied and
+	/// accessors without a buffer view read as zeros
+	pub fn read_accessor_f32(&self, idx: Index<GltfAccessor>) -> GltrResult<Vec<f32>> {
+		let normalized = self.try_get(idx)?.normalized;
+		self.read_accessor_with(idx, |bytes, ct| read_component(bytes, ct, normalized))
+	}
+
+	/// reads every component of an accessor as stored in the buffer, ignoring `normalized`.
+	/// this is the domain used by accessor min/max
+	pub fn read_accessor_raw(&self, idx: Index<GltfAccessor>) -> GltrResult<Vec<f32>> {
+		self.read_accessor_with(idx, |bytes, ct| read_component(bytes, ct, false))
+	}
+
+	/// reads an integer accessor, eg primitive indices, without going through f32
+	pub fn read_accessor_u32(&self, idx: Index<GltfAccessor>) -> GltrResult<Vec<u32>> {
+		self.read_accessor_with(idx, read_component_u32)
+	}
+
+	/// reads an accessor as fixed size elements, eg `read_accessor_array::<3>` for a VEC3 accessor
+	pub fn read_accessor_array<const N: usize>(&self, idx: Index<GltfAccessor>) -> GltrResult<Vec<[f32; N]>> {
+		let accessor = self.try_get(idx)?;
+		if accessor.component_count() != N {
+			return Err(GltrError::ConstraintViolation(format!(
+				"accessor {idx} has type {}, expected {N} components",
+				accessor.r#type
+			)));
+		}
+
+		let values = self.read_accessor_f32(idx)?;
+
+		Ok(values
+			.chunks_exact(N)
+			.map(|x| {
+				let mut element = [0f32; N];
+				element.copy_from_slice(x);
+				element
+			})
+			.collect())
+	}
+
+	fn read_accessor_with<T: Copy + Default>(&self, idx: Index<GltfAccessor>, convert: impl Fn(&[u8], ComponentType) -> T) -> GltrResult<Vec<T>> {
+		let accessor = self.try_get(idx)?;
+
+		let n = accessor.component_count();
+		if n == 0 || !accessor.component_type.is_valid() {
+			return Err(GltrError::ConstraintViolation(format!(
+				"accessor {idx} has an invalid type or component type"
+			)));
+		}
+
+		let (columns, rows, column_stride) = element_layout(&accessor.r#type, accessor.component_type);
+		let component_size = accessor.component_type.byte_size();
+		let element_size = columns * column_stride;
+
+		let mut values = vec![T::default(); accessor.count * n];
+
+		if let Some(view_idx) = accessor.buffer_view {
+			let bytes = self.buffer_view_bytes(view_idx)?;
+			let stride = self.buffer_views[view_idx.value()].byte_stride.unwrap_or(element_size);
+			let offset = accessor.byte_offset.unwrap_or(0);
+
+			if accessor.count > 0 && offset + stride * (accessor.count - 1) + element_size > bytes.len() {
+				return Err(GltrError::OutOfRange {
+					path: format!("/accessors/{idx}"),
+					offset,
+					length: stride * (accessor.count - 1) + element_size,
+					available: bytes.len(),
+				});
+			}
+
+			for i in 0..accessor.count {
+				let base = offset + i * stride;
+				for c in 0..columns {
+					for r in 0..rows {
+						let at = base + c * column_stride + r * component_size;
+						values[i * n + c * rows + r] = convert(&bytes[at..at + component_size], accessor.component_type);
+					}
+				}
+			}
+		}
+
+		if let Some(sparse) = &accessor.sparse {
+			let index_bytes = self.buffer_view_bytes(sparse.indices.buffer_view)?;
+			let index_offset = sparse.indices.byte_offset.unwrap_or(0);
+			let index_size = sparse.indices.component_type.byte_size();
+
+			let value_bytes = match sparse.values.buffer_view {
+				None => return Err(GltrError::ConstraintViolation(format!("sparse values of accessor {idx} have no buffer_view"))),
+				Some(v) => self.buffer_view_bytes(v)?,
+			};
+			let value_offset = sparse.values.byte_offset.unwrap_or(0);
+
+			if index_offset + sparse.count * index_size > index_bytes.len() {
+				return Err(GltrError::OutOfRange {
+					path: format!("/accessors/{idx}/sparse/indices"),
+					offset: index_offset,
+					length: sparse.count * index_size,
+					available: index_bytes.len(),
+				});
+			}
+
+			if value_offset + sparse.count * element_size > value_bytes.len() {
+				return Err(GltrError::OutOfRange {
+					path: format!("/accessors/{idx}/sparse/values"),
+					offset: value_offset,
+					length: sparse.count * element_size,
+					available: value_bytes.len(),
+				});
+			}
+
+			for s in 0..sparse.count {
+				let at = index_offset + s * index_size;
+				let target = read_component_u32(&index_bytes[at..at + index_size], sparse.indices.component_type) as usize;
+
+				if target >= accessor.count {
+					return Err(GltrError::ConstraintViolation(format!("sparse index {target} of accessor {idx} is out of range")));
+				}
+
+				let base = value_offset + s * element_size;
+				for c in 0..columns {
+					for r in 0..rows {
+						let at = base + c * column_stride + r * component_size;
+						values[target * n + c * rows + r] = convert(&value_bytes[at..at + component_size], accessor.component_type);
+					}
+				}
+			}
+		}
+
+		Ok(values)
+	}
+
+	/// appends `bytes` to the first buffer (creating one if there are none) and adds a buffer view over them,
+	/// returning the index of the new buffer view. the data is aligned to 4 bytes within the buffer
+	pub fn push_buffer_view(&mut self, bytes: &[u8], target: Option<BufferViewTarget>) -> GltrResult<Index<GltfBufferView>> {
+		if self.buffers.0.is_empty() {
+			self.buffers.0.push(GltfBase64Buffer::from_bytes(&[]));
+		}
+
+		let byte_offset = self.buffers.0[0].append_bytes(bytes, 4)?;
+
+		Ok(self.push(GltfBufferView::new(Index::new(0), byte_offset, bytes.len(), target)))
+	}
+
+	/// encodes `values` (flattened, in element order) as a new accessor backed by its own buffer view,
+	/// returning the index of the new accessor.
+	///
+	/// when `normalized` is set for an integer component type, values are expected in the normalized float range.
+	/// min and max are computed from the stored values
+	pub fn push_accessor_f32(
+		&mut self,
+		values: &[f32],
+		accessor_type: &str,
+		component_type: ComponentType,
+		normalized: bool,
+		target: Option<BufferViewTarget>,
+	) -> GltrResult<Index<GltfAccessor>> {
+		let (bytes, mut accessor) = encode_accessor_f32(values, accessor_type, component_type, normalized)?;
+
+		accessor.buffer_view = Some(self.push_buffer_view(&bytes, target)?);
+		Ok(self.push(accessor))
+	}
+
+	/// writes vertex indices as an UNSIGNED_SHORT accessor, or UNSIGNED_INT when they don't fit,
+	/// returning the index of the new accessor
+	pub fn push_accessor_indices(&mut self, indices: &[u32]) -> GltrResult<Index<GltfAccessor>> {
+		self.push_accessor_indices_from(indices, ComponentType::UNSIGNED_SHORT)
+	}
+
+	/// writes vertex indices with the smallest of UNSIGNED_BYTE, UNSIGNED_SHORT and UNSIGNED_INT that fits them,
+	/// returning the index of the new accessor. byte indices are slow on some hardware, see
+	/// [GltfObject::push_accessor_indices]
+	pub fn push_accessor_indices_compact(&mut self, indices: &[u32]) -> GltrResult<Index<GltfAccessor>> {
+		self.push_accessor_indices_from(indices, ComponentType::UNSIGNED_BYTE)
+	}
+
+	fn push_accessor_indices_from(&mut self, indices: &[u32], smallest: ComponentType) -> GltrResult<Index<GltfAccessor>> {
+		let (bytes, mut accessor) = encode_accessor_indices(indices, smallest);
+
+		accessor.buffer_view = Some(self.push_buffer_view(&bytes, Some(BufferViewTarget::ELEMENT_ARRAY_BUFFER))?);
+		Ok(self.push(accessor))
+	}
+
+	/// writes fixed size elements as a FLOAT accessor of the matching VECn/SCALAR type
+	pub fn push_accessor_array<const N: usize>(&mut self, values: &[[f32; N]], target: Option<BufferViewTarget>) -> GltrResult<Index<GltfAccessor>> {
+		let flat: Vec<f32> = values.iter().flatten().copied().collect();
+		self.push_accessor_f32(&flat, array_accessor_type::<N>()?, ComponentType::FLOAT, false, target)
+	}
+}
+
+/// the accessor type holding `N` components per element
+pub(crate) fn array_accessor_type<const N: usize>() -> GltrResult<&'static str> {
+	match N {
+		1 => Ok("SCALAR"),
+		2 => Ok("VEC2"),
+		3 => Ok("VEC3"),
+		4 => Ok("VEC4"),
+		16 => Ok("MAT4"),
+		_ => Err(GltrError::ConstraintViolation(format!("no accessor type has {N} components"))),
+	}
+}
+
+fn new_accessor(component_type: ComponentType, normalized: bool, count: usize, accessor_type: &str) -> GltfAccessor {
+	GltfAccessor {
+		buffer_view: None,
+		byte_offset: None,
+		component_type,
+		normalized,
+		count,
+		r#type: accessor_type.to_string(),
+		max: vec![],
+		min: vec![],
+		sparse: None,
+		name: None,
+		extensions: None,
+		extras: None,
+		unknown_fields: UnknownFields::new(),
+	}
+}
+
+/// the tightly packed bytes of an accessor holding `values`, and the accessor describing them with min/max
+/// computed from the stored values. the accessor has no buffer view yet
+pub(crate) fn encode_accessor_f32(
+	values: &[f32],
+	accessor_type: &str,
+	component_type: ComponentType,
+	normalized: bool,
+) -> GltrResult<(Vec<u8>, GltfAccessor)> {
+	let n = type_component_count(accessor_type);
+	if n == 0 || !component_type.is_valid() || !values.len().is_multiple_of(n) {
+		return Err(GltrError::ConstraintViolation(format!(
+			"cannot write {} values as a {accessor_type} accessor",
+			values.len()
+		)));
+	}
+
+	let (columns, rows, column_stride) = element_layout(accessor_type, component_type);
+	let count = values.len() / n;
+
+	let mut bytes = Vec::with_capacity(count * columns * column_stride);
+	for element in values.chunks_exact(n) {
+		for c in 0..columns {
+			let column_start = bytes.len();
+			for r in 0..rows {
+				write_component(&mut bytes, element[c * rows + r], component_type, normalized);
+			}
+			bytes.resize(column_start + column_stride, 0);
+		}
+	}
+
+	let mut min = vec![f32::MAX; if count > 0 { n } else { 0 }];
+	let mut max = vec![f32::MIN; if count > 0 { n } else { 0 }];
+	let element_size = columns * column_stride;
+	let component_size = component_type.byte_size();
+	for i in 0..count {
+		for c in 0..columns {
+			for r in 0..rows {
+				let at = i * element_size + c * column_stride + r * component_size;
+				let v = read_component(&bytes[at..at + component_size], component_type, false);
+				min[c * rows + r] = min[c * rows + r].min(v);
+				max[c * rows + r] = max[c * rows + r].max(v);
+			}
+		}
+	}
+
+	let mut accessor = new_accessor(component_type, normalized, count, accessor_type);
+	accessor.min = min;
+	accessor.max = max;
+
+	Ok((bytes, accessor))
+}
+
+/// the bytes of an index accessor, using the smallest component type, from `smallest` up to UNSIGNED_INT, that
+/// holds every index. the largest value of a component type is the primitive restart value, which indices can't use
+pub(crate) fn encode_accessor_indices(indices: &[u32], smallest: ComponentType) -> (Vec<u8>, GltfAccessor) {
+	let min = indices.iter().copied().min();
+	let max = indices.iter().copied().max();
+
+	let mut bytes = Vec::new();
+	let component_type = if smallest == ComponentType::UNSIGNED_BYTE && max.unwrap_or(0) < u8::MAX as u32 {
+		bytes.extend(indices.iter().map(|i| *i as u8));
+		ComponentType::UNSIGNED_BYTE
+	} else if max.unwrap_or(0) < u16::MAX as u32 {
+		bytes.extend(indices.iter().flat_map(|i| (*i as u16).to_le_bytes()));
+		ComponentType::UNSIGNED_SHORT
+	} else {
+		bytes.extend(indices.iter().flat_map(|i| i.to_le_bytes()));
+		ComponentType::UNSIGNED_INT
+	};
+
+	let mut accessor = new_accessor(component_type, false, indices.len(), "SCALAR");
+	if let (Some(min), Some(max)) = (min, max) {
+		accessor.min = vec![min as f32];
+		accessor.max = vec![max as f32];
+	}
+
+	(bytes, accessor)
+}
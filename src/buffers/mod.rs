@@ -1,51 +1,117 @@
+mod accessor_data;
+
+pub(crate) use accessor_data::{array_accessor_type, encode_accessor_f32, encode_accessor_indices};
+
 use base64::prelude::BASE64_STANDARD;
-use base64::{DecodeError, Engine};
+use base64::Engine;
+use crate::defaults::default_0;
+use crate::error::{GltrError, GltrResult};
+use crate::gltf_object::prelude::{Extensions, Extras, Index, UnknownFields};
 use serde_derive::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GltfBase64Buffer {
 	#[serde(rename = "byteLength")]
 	pub byte_length: usize,
-	pub uri: String,
+
+	/// undefined for the buffer stored in the binary chunk of a GLB file
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub uri: Option<String>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub name: Option<String>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extensions: Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras: Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
 
 	#[serde(skip)]
-	pub(crate) original_index: Option<usize>,
+	pub(crate) original_index: Option<Index<GltfBase64Buffer>>,
 }
 
 
 impl GltfBase64Buffer {
-	pub fn bytes(&self) -> Result<Vec<u8>, &'static str> {
-		let content = &self.uri;
+	const DATA_URI_PREFIX: &'static str = "data:application/octet-stream;base64,";
+
+	pub fn from_bytes(bytes: &[u8]) -> Self {
+		GltfBase64Buffer {
+			byte_length: bytes.len(),
+			uri: Some(format!("{}{}", Self::DATA_URI_PREFIX, BASE64_STANDARD.encode(bytes))),
+			name: None,
+			extensions: None,
+			extras: None,
+			unknown_fields: UnknownFields::new(),
+			original_index: None,
+		}
+	}
 
-		if !content.starts_with("data:application/octet-stream;base64,") {
-			return Err("invalid buffer view encoding: expected RFC2397 encoded application/octet-stream;base64");
-		};
+	/// replaces the content of the buffer, re-encoding it as a data URI
+	pub fn set_bytes(&mut self, bytes: &[u8]) {
+		self.byte_length = bytes.len();
+		self.uri = Some(format!("{}{}", Self::DATA_URI_PREFIX, BASE64_STANDARD.encode(bytes)));
+	}
 
-		let buf = content.replace("data:application/octet-stream;base64,", "");
+	pub fn bytes(&self) -> GltrResult<Vec<u8>> {
+		Ok(BASE64_STANDARD.decode(self.payload()?)?)
+	}
 
-		let bytes = BASE64_STANDARD.decode(buf);
+	/// appends `bytes` after zero padding the buffer to a multiple of `alignment`, returning the offset they start at.
+	///
+	/// only the trailing partial base64 group of the data URI is decoded and encoded again, so appending many small
+	/// pieces costs the size of the pieces rather than the size of the buffer each time
+	pub(crate) fn append_bytes(&mut self, bytes: &[u8], alignment: usize) -> GltrResult<usize> {
+		let payload_length = self.payload()?.len();
+
+		// byteLength doesn't describe the data URI, so the groups can't be located without decoding all of it
+		if payload_length != self.byte_length.div_ceil(3) * 4 {
+			let mut content = self.bytes()?;
+			content.resize(content.len().next_multiple_of(alignment), 0);
+			let byte_offset = content.len();
+			content.extend_from_slice(bytes);
+			self.set_bytes(&content);
+			return Ok(byte_offset);
+		}
 
-		match bytes {
-			Ok(b) => {
-				Ok(b)
-			}
-			Err(e) => {
-				let err_str = match e {
-					DecodeError::InvalidByte(_, _) => {
-						"base64 decoding error: DecodeError::InvalidByte"
-					}
-					DecodeError::InvalidLength(_) => {
-						"base64 decoding error: DecodeError::InvalidLength"
-					}
-					DecodeError::InvalidLastSymbol(_, _) => {
-						"base64 decoding error: DecodeError::InvalidLastSymbol"
-					}
-					DecodeError::InvalidPadding => {
-						"base64 decoding error: DecodeError::InvalidPadding"
-					}
-				};
-				Err(err_str)
+		let byte_offset = self.byte_length.next_multiple_of(alignment);
+		let whole_groups = self.byte_length / 3;
+
+		let uri = self.uri.as_mut().expect("payload() checked the uri");
+		let kept = uri.len() - payload_length + whole_groups * 4;
+
+		let mut tail = BASE64_STANDARD.decode(&uri[kept..])?;
+		tail.resize(byte_offset - whole_groups * 3, 0);
+		tail.extend_from_slice(bytes);
+
+		uri.truncate(kept);
+		BASE64_STANDARD.encode_string(&tail, uri);
+		self.byte_length = byte_offset + bytes.len();
+
+		Ok(byte_offset)
+	}
+
+	/// the base64 part of the data URI
+	fn payload(&self) -> GltrResult<&str> {
+		let content = match &self.uri {
+			None => {
+				return Err(GltrError::ConstraintViolation(
+					"buffer has no uri, its data is the binary chunk of a GLB file".to_string(),
+				))
 			}
+			Some(uri) => uri,
+		};
+
+		let payload = ["data:application/octet-stream;base64,", "data:application/gltf-buffer;base64,"]
+			.iter()
+			.find_map(|prefix| content.strip_prefix(prefix));
+
+		match payload {
+			None => Err(GltrError::InvalidDataUri(content.clone())),
+			Some(p) => Ok(p),
 		}
 	}
 }
@@ -60,23 +126,53 @@ pub struct GltfBinaryBuffer {
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GltfBufferView {
-	pub buffer: usize,
+	pub buffer: Index<GltfBase64Buffer>,
 	#[serde(rename = "byteLength")]
 	pub byte_length: usize,
-	#[serde(rename = "byteOffset")]
+	#[serde(rename = "byteOffset", default = "default_0")]
 	pub byte_offset: usize,
 
+	#[serde(rename = "byteStride", skip_serializing_if = "Option::is_none")]
+	pub byte_stride: Option<usize>,
+
 	//https://registry.khronos.org/glTF/specs/2.0/glTF-2.0.pdf see 5.11.5 bufferView.target
 	//enum
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub target: Option<BufferViewTarget>,
 
-	#[serde(skip)]
-	pub(crate) original_index: Option<usize>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub name: Option<String>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extensions: Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras: Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
 
 }
 
 
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+impl GltfBufferView {
+	pub fn new(buffer: Index<GltfBase64Buffer>, byte_offset: usize, byte_length: usize, target: Option<BufferViewTarget>) -> Self {
+		GltfBufferView {
+			buffer,
+			byte_length,
+			byte_offset,
+			byte_stride: None,
+			target,
+			name: None,
+			extensions: None,
+			extras: None,
+			unknown_fields: UnknownFields::new(),
+		}
+	}
+}
+
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct BufferViewTarget(usize);
 impl BufferViewTarget {
 	pub const ARRAY_BUFFER: BufferViewTarget = BufferViewTarget(34962);
@@ -92,7 +188,7 @@ impl BufferViewTarget {
 pub struct GltfBinaryBuffers(pub Vec<GltfBinaryBuffer>);
 
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GltfBuffers(pub Vec<GltfBase64Buffer>);
 
 impl GltfBuffers {
@@ -107,7 +203,7 @@ impl Default for GltfBuffers {
 }
 
 impl TryFrom<GltfBuffers> for GltfBinaryBuffers {
-	type Error = &'static str;
+	type Error = GltrError;
 
 
 	fn try_from(value: GltfBuffers) -> Result<Self, Self::Error> {
@@ -127,20 +223,23 @@ impl GltfBuffers {
 	pub fn empty() -> Self {
 		GltfBuffers(vec![])
 	}
-	pub fn to_binary(self) -> Result<GltfBinaryBuffers, &'static str> {
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+	pub fn to_binary(self) -> GltrResult<GltfBinaryBuffers> {
 		GltfBinaryBuffers::try_from(self)
 	}
 }
 
 impl GltfBinaryBuffers {
-	pub fn get_view(&self, view: &GltfBufferView) -> Result<Vec<u8>, &'static str> {
+	pub fn get_view(&self, view: &GltfBufferView) -> GltrResult<Vec<u8>> {
 		let target_buffer = view.buffer;
 
-		let buffer = self.0.get(target_buffer);
+		let buffer = self.0.get(target_buffer.value());
 
 		match buffer {
 			None => {
-				Err("No such buffer")
+				Err(GltrError::invalid_index("/buffers", target_buffer.value()))
 			}
 			Some(b) => {
 				let length = view.byte_length;
@@ -149,7 +248,12 @@ impl GltfBinaryBuffers {
 				let end_len = offset + length;
 
 				if b.bytes.len() < end_len {
-					return Err("not enough bytes");
+					return Err(GltrError::OutOfRange {
+						path: format!("/buffers/{target_buffer}"),
+						offset,
+						length,
+						available: b.bytes.len(),
+					});
 				};
 
 				let view_bytes = &b.bytes[offset..end_len];
@@ -161,12 +265,17 @@ impl GltfBinaryBuffers {
 }
 
 impl TryFrom<GltfBase64Buffer> for GltfBinaryBuffer {
-	type Error = &'static str;
+	type Error = GltrError;
 
 	fn try_from(value: GltfBase64Buffer) -> Result<Self, Self::Error> {
 		let bytes = value.bytes()?;
 
-		assert_eq!(bytes.len(), value.byte_length);
+		if bytes.len() != value.byte_length {
+			return Err(GltrError::BufferLength {
+				expected: value.byte_length,
+				actual: bytes.len(),
+			});
+		}
 
 		Ok(Self {
 			byte_length: value.byte_length,
@@ -176,13 +285,13 @@ impl TryFrom<GltfBase64Buffer> for GltfBinaryBuffer {
 }
 
 impl GltfBinaryBuffer {
-	pub fn from_base64(b64_encoded: GltfBase64Buffer) -> Result<GltfBinaryBuffer, &'static str> {
+	pub fn from_base64(b64_encoded: GltfBase64Buffer) -> GltrResult<GltfBinaryBuffer> {
 		GltfBinaryBuffer::try_from(b64_encoded)
 	}
 }
 
 impl GltfBase64Buffer {
-	pub fn to_binary(self) -> Result<GltfBinaryBuffer, &'static str> {
+	pub fn to_binary(self) -> GltrResult<GltfBinaryBuffer> {
 		GltfBinaryBuffer::try_from(self)
 	}
 }
@@ -0,0 +1,403 @@
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::{GltfObject, GltrResult};
+use serde_derive::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+
+/// numbers closer than this are considered equal, so re-exporting does not report rounding noise as changes
+const EPSILON: f64 = 1e-6;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+	Added,
+	Removed,
+	Modified,
+}
+
+/// a property that differs between the two versions of an entity. None when the property only exists on one side
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct PropertyChange {
+	/// path of the property within the entity, eg `translation` or `pbrMetallicRoughness/baseColorFactor`
+	pub property: String,
+
+	pub old: Option<Value>,
+
+	pub new: Option<Value>,
+}
+
+/// a node, mesh or material that was added, removed or modified
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct EntityDiff {
+	pub kind: ChangeKind,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub name: Option<String>,
+
+	#[serde(rename = "oldIndex", skip_serializing_if = "Option::is_none")]
+	pub old_index: Option<usize>,
+
+	#[serde(rename = "newIndex", skip_serializing_if = "Option::is_none")]
+	pub new_index: Option<usize>,
+
+	/// empty unless the entity was modified
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub changes: Vec<PropertyChange>,
+}
+
+/// The differences between two documents, see [diff]
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct GltfDiff {
+	pub nodes: Vec<EntityDiff>,
+	pub meshes: Vec<EntityDiff>,
+	pub materials: Vec<EntityDiff>,
+}
+
+impl GltfDiff {
+	pub fn is_empty(&self) -> bool {
+		self.nodes.is_empty() && self.meshes.is_empty() && self.materials.is_empty()
+	}
+}
+
+impl Display for GltfDiff {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		let kinds = [("node", &self.nodes), ("mesh", &self.meshes), ("material", &self.materials)];
+
+		for (kind, entities) in kinds {
+			for entity in entities {
+				let symbol = match entity.kind {
+					ChangeKind::Added => '+',
+					ChangeKind::Removed => '-',
+					ChangeKind::Modified => '~',
+				};
+				let name = match (&entity.name, entity.new_index.or(entity.old_index)) {
+					(Some(name), _) => format!("\"{name}\""),
+					(None, Some(idx)) => format!("#{idx}"),
+					(None, None) => String::new(),
+				};
+
+				writeln!(f, "{symbol} {kind} {name}")?;
+				for change in &entity.changes {
+					let show = |v: &Option<Value>| v.as_ref().map_or("(none)".to_string(), |v| v.to_string());
+					writeln!(f, "\t{}: {} -> {}", change.property, show(&change.old), show(&change.new))?;
+				}
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// Compares two versions of a document, eg before and after an artist re-exported it.
+///
+/// nodes, meshes and materials are matched by name, and unnamed ones by a hash of their content. references
+/// between them are compared by the name (or content hash) of what they refer to rather than by index, so
+/// reordering the arrays of a document is not a change. meshes are compared by their vertex counts, bounds and a
+/// hash of their geometry, and materials by all of their parameters
+pub fn diff(a: &GltfObject, b: &GltfObject) -> GltrResult<GltfDiff> {
+	let a = Comparable::new(a)?;
+	let b = Comparable::new(b)?;
+
+	Ok(GltfDiff {
+		nodes: diff_entities(&a.nodes, &b.nodes),
+		meshes: diff_entities(&a.meshes, &b.meshes),
+		materials: diff_entities(&a.materials, &b.materials),
+	})
+}
+
+/// an entity reduced to the values that are compared, with references replaced by keys
+struct Entity {
+	name: Option<String>,
+	/// the name, or a hash of the content for unnamed entities
+	key: String,
+	hash: u64,
+	value: Value,
+}
+
+impl Entity {
+	fn new(name: Option<String>, value: Value) -> Self {
+		let hash = hash_value(&value);
+		let key = name.clone().unwrap_or_else(|| format!("#{hash:016x}"));
+
+		Entity { name, key, hash, value }
+	}
+}
+
+fn hash_value(value: &Value) -> u64 {
+	let mut hasher = DefaultHasher::new();
+	value.to_string().hash(&mut hasher);
+	hasher.finish()
+}
+
+struct Comparable {
+	nodes: Vec<Entity>,
+	meshes: Vec<Entity>,
+	materials: Vec<Entity>,
+}
+
+impl Comparable {
+	fn new(object: &GltfObject) -> GltrResult<Self> {
+		let mut texture_keys = Vec::with_capacity(object.textures.len());
+		for texture in &object.textures {
+			let image = texture.source_image_index.and_then(|i| object.get(i));
+			let key = match image {
+				None => Value::Null,
+				Some(GltfImage { name: Some(name), .. }) => json!(name),
+				Some(GltfImage { uri: Some(uri), .. }) if !uri.starts_with("data:") => json!(uri),
+				Some(image) => {
+					let bytes = match image.buffer_view {
+						None => image.uri.clone().unwrap_or_default().into_bytes(),
+						Some(view) => object.buffer_view_bytes(view)?,
+					};
+
+					let mut hasher = DefaultHasher::new();
+					bytes.hash(&mut hasher);
+					json!(format!("#{:016x}", hasher.finish()))
+				}
+			};
+			texture_keys.push(key);
+		}
+
+		let mut materials = Vec::with_capacity(object.materials.len());
+		for material in &object.materials {
+			let mut value = serde_json::to_value(material)?;
+			value.as_object_mut().map(|m| m.remove("name"));
+
+			for slot in material.texture_slots() {
+				let pointer = format!("/{}/index", slot.pointer);
+				if let Some(index) = value.pointer_mut(&pointer) {
+					*index = texture_keys.get(slot.texture_index.value()).cloned().unwrap_or(Value::Null);
+				}
+			}
+
+			materials.push(Entity::new(material.name.clone(), value));
+		}
+
+		let mut meshes = Vec::with_capacity(object.meshes.len());
+		for mesh in &object.meshes {
+			let mut primitives = Vec::with_capacity(mesh.primitives.len());
+
+			for primitive in &mesh.primitives {
+				let mut value = Map::new();
+				value.insert("mode".to_string(), json!(primitive.mode()));
+				value.insert("attributes".to_string(), json!(primitive.attributes.keys().collect::<Vec<_>>()));
+				value.insert("material".to_string(), key_of(&materials, primitive.material.map(Index::value)));
+
+				let mut hasher = DefaultHasher::new();
+
+				if let Some(position) = primitive.attributes.get("POSITION") {
+					let positions = object.read_accessor_array::<3>(*position)?;
+
+					let mut min = [f32::INFINITY; 3];
+					let mut max = [f32::NEG_INFINITY; 3];
+					for p in &positions {
+						for c in 0..3 {
+							min[c] = min[c].min(p[c]);
+							max[c] = max[c].max(p[c]);
+							p[c].to_bits().hash(&mut hasher);
+						}
+					}
+
+					value.insert("vertices".to_string(), json!(positions.len()));
+					if !positions.is_empty() {
+						value.insert("bounds".to_string(), json!({ "min": min, "max": max }));
+					}
+				}
+
+				if let Some(indices) = primitive.accessor {
+					let indices = object.read_accessor_u32(indices)?;
+					indices.hash(&mut hasher);
+					value.insert("indices".to_string(), json!(indices.len()));
+				}
+
+				value.insert("geometryHash".to_string(), json!(format!("{:016x}", hasher.finish())));
+				primitives.push(Value::Object(value));
+			}
+
+			meshes.push(Entity::new(mesh.name.clone(), json!({ "primitives": primitives })));
+		}
+
+		let mut nodes = Vec::with_capacity(object.nodes.len());
+		for node in &object.nodes {
+			let (translation, rotation, scale) = node.local_trs();
+
+			let value = json!({
+				"translation": translation,
+				"rotation": rotation,
+				"scale": scale,
+				"mesh": key_of(&meshes, node.mesh.map(Index::value)),
+				"camera": node.camera.is_some(),
+				"skin": node.skin.is_some(),
+			});
+
+			nodes.push(Entity::new(node.name.clone(), value));
+		}
+
+		// children are referenced by key, which is only known once every node has one
+		let children: Vec<Value> = object
+			.nodes
+			.iter()
+			.map(|n| json!(n.child_indices().iter().map(|c| key_of(&nodes, Some(c.value()))).collect::<Vec<_>>()))
+			.collect();
+
+		for (node, children) in nodes.iter_mut().zip(children) {
+			node.value["children"] = children;
+			node.hash = hash_value(&node.value);
+		}
+
+		Ok(Comparable { nodes, meshes, materials })
+	}
+}
+
+fn key_of(entities: &[Entity], idx: Option<usize>) -> Value {
+	match idx.and_then(|i| entities.get(i)) {
+		None => Value::Null,
+		Some(e) => json!(e.key),
+	}
+}
+
+/// pairs the entities of both sides, by name first and then by content hash
+fn match_entities(a: &[Entity], b: &[Entity]) -> (Vec<(usize, usize)>, Vec<usize>, Vec<usize>) {
+	let mut pairs = Vec::new();
+	let mut matched_b = vec![false; b.len()];
+	let mut unmatched_a = Vec::new();
+
+	// entities with the same name are paired in the order they appear
+	let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+	for (i, e) in b.iter().enumerate().rev() {
+		if let Some(name) = &e.name {
+			by_name.entry(name).or_default().push(i);
+		}
+	}
+
+	let mut by_hash: HashMap<u64, Vec<usize>> = HashMap::new();
+	for (i, e) in b.iter().enumerate().rev() {
+		if e.name.is_none() {
+			by_hash.entry(e.hash).or_default().push(i);
+		}
+	}
+
+	for (i, e) in a.iter().enumerate() {
+		let candidate = match &e.name {
+			Some(name) => by_name.get_mut(name.as_str()).and_then(|c| c.pop()),
+			None => by_hash.get_mut(&e.hash).and_then(|c| c.pop()),
+		};
+
+		match candidate {
+			None => unmatched_a.push(i),
+			Some(j) => {
+				matched_b[j] = true;
+				pairs.push((i, j));
+			}
+		}
+	}
+
+	let unmatched_b = (0..b.len()).filter(|j| !matched_b[*j]).collect();
+	(pairs, unmatched_a, unmatched_b)
+}
+
+fn diff_entities(a: &[Entity], b: &[Entity]) -> Vec<EntityDiff> {
+	let (pairs, removed, added) = match_entities(a, b);
+	let mut out = Vec::new();
+
+	for i in removed {
+		out.push(EntityDiff {
+			kind: ChangeKind::Removed,
+			name: a[i].name.clone(),
+			old_index: Some(i),
+			new_index: None,
+			changes: Vec::new(),
+		});
+	}
+
+	for (i, j) in pairs {
+		let changes = diff_values(&a[i].value, &b[j].value);
+		if !changes.is_empty() {
+			out.push(EntityDiff {
+				kind: ChangeKind::Modified,
+				name: a[i].name.clone(),
+				old_index: Some(i),
+				new_index: Some(j),
+				changes,
+			});
+		}
+	}
+
+	for j in added {
+		out.push(EntityDiff {
+			kind: ChangeKind::Added,
+			name: b[j].name.clone(),
+			old_index: None,
+			new_index: Some(j),
+			changes: Vec::new(),
+		});
+	}
+
+	out
+}
+
+/// the leaves of a value by path. arrays of numbers (vectors, colors) are a single leaf
+fn flatten(path: String, value: &Value, out: &mut BTreeMap<String, Value>) {
+	match value {
+		Value::Object(map) => {
+			for (key, v) in map {
+				let path = if path.is_empty() { key.clone() } else { format!("{path}/{key}") };
+				flatten(path, v, out);
+			}
+		}
+		Value::Array(items) if !items.iter().all(Value::is_number) => {
+			for (i, v) in items.iter().enumerate() {
+				flatten(format!("{path}/{i}"), v, out);
+			}
+		}
+		_ => {
+			out.insert(path, value.clone());
+		}
+	}
+}
+
+fn values_equal(a: &Value, b: &Value) -> bool {
+	match (a, b) {
+		(Value::Number(a), Value::Number(b)) => match (a.as_f64(), b.as_f64()) {
+			(Some(a), Some(b)) => (a - b).abs() <= EPSILON * a.abs().max(b.abs()).max(1.0),
+			_ => a == b,
+		},
+		(Value::Array(a), Value::Array(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| values_equal(a, b)),
+		_ => a == b,
+	}
+}
+
+fn diff_values(a: &Value, b: &Value) -> Vec<PropertyChange> {
+	let mut old = BTreeMap::new();
+	let mut new = BTreeMap::new();
+	flatten(String::new(), a, &mut old);
+	flatten(String::new(), b, &mut new);
+
+	let mut changes = Vec::new();
+
+	for (property, old_value) in &old {
+		let new_value = new.get(property);
+		if new_value.is_none_or(|n| !values_equal(old_value, n)) {
+			changes.push(PropertyChange {
+				property: property.clone(),
+				old: Some(old_value.clone()),
+				new: new_value.cloned(),
+			});
+		}
+	}
+
+	for (property, new_value) in &new {
+		if !old.contains_key(property) {
+			changes.push(PropertyChange {
+				property: property.clone(),
+				old: None,
+				new: Some(new_value.clone()),
+			});
+		}
+	}
+
+	changes
+}
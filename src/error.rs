@@ -0,0 +1,126 @@
+use serde_json::error::Category;
+use std::fmt::{Display, Formatter};
+
+/// Errors returned by the fallible APIs of this crate
+#[derive(Debug)]
+pub enum GltrError {
+	/// the document is not valid JSON
+	InvalidJson {
+		line: usize,
+		column: usize,
+		source: serde_json::Error,
+	},
+
+	/// JSON that does not match the structure of a glTF asset, or a value serde could not convert. the message of
+	/// the source holds the line and column when the JSON was parsed from text
+	JsonData(serde_json::Error),
+
+	/// a buffer uri that is not an RFC2397 application/octet-stream (or gltf-buffer) base64 data URI
+	InvalidDataUri(String),
+
+	/// the base64 payload of a data URI could not be decoded. position is the byte offset in the payload, when known
+	Base64 {
+		position: Option<usize>,
+		source: base64::DecodeError,
+	},
+
+	/// a buffer whose decoded content does not match its byteLength
+	BufferLength { expected: usize, actual: usize },
+
+	/// a bufferView (or accessor data within one) extends past the end of the data it views
+	OutOfRange {
+		path: String,
+		offset: usize,
+		length: usize,
+		available: usize,
+	},
+
+	/// an index that does not refer to an existing object. path is the JSON pointer of the property holding the
+	/// index, eg `/meshes/0/primitives/1/material`, or of the array being indexed for indices passed as arguments
+	InvalidIndex { path: String, index: usize },
+
+	/// the document is structurally valid but breaks a rule of the specification, or an operation cannot be applied to it
+	ConstraintViolation(String),
+
+	Io(std::io::Error),
+}
+
+pub type GltrResult<T> = Result<T, GltrError>;
+
+impl GltrError {
+	pub fn invalid_index(path: impl Into<String>, index: usize) -> Self {
+		GltrError::InvalidIndex { path: path.into(), index }
+	}
+}
+
+impl Display for GltrError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			GltrError::InvalidJson { line, column, source } => {
+				write!(f, "invalid JSON at line {line}, column {column}: {source}")
+			}
+			GltrError::JsonData(source) => write!(f, "unexpected JSON data: {source}"),
+			GltrError::InvalidDataUri(uri) => {
+				let shown: String = uri.chars().take(48).collect();
+				write!(f, "invalid buffer encoding: expected a base64 data URI, found \"{shown}\"")
+			}
+			GltrError::Base64 { position: Some(position), source } => {
+				write!(f, "base64 decoding error at byte {position}: {source}")
+			}
+			GltrError::Base64 { position: None, source } => write!(f, "base64 decoding error: {source}"),
+			GltrError::BufferLength { expected, actual } => {
+				write!(f, "buffer byteLength is {expected} but its data is {actual} bytes")
+			}
+			GltrError::OutOfRange { path, offset, length, available } => write!(
+				f,
+				"{path} reads {length} bytes at offset {offset}, but only {available} bytes are available"
+			),
+			GltrError::InvalidIndex { path, index } => write!(f, "{path}: index {index} does not exist"),
+			GltrError::ConstraintViolation(message) => write!(f, "{message}"),
+			GltrError::Io(e) => write!(f, "I/O error: {e}"),
+		}
+	}
+}
+
+impl std::error::Error for GltrError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			GltrError::InvalidJson { source, .. } => Some(source),
+			GltrError::JsonData(source) => Some(source),
+			GltrError::Base64 { source, .. } => Some(source),
+			GltrError::Io(e) => Some(e),
+			_ => None,
+		}
+	}
+}
+
+impl From<serde_json::Error> for GltrError {
+	fn from(value: serde_json::Error) -> Self {
+		match value.classify() {
+			Category::Syntax | Category::Eof => GltrError::InvalidJson {
+				line: value.line(),
+				column: value.column(),
+				source: value,
+			},
+			Category::Data => GltrError::JsonData(value),
+			Category::Io => GltrError::Io(value.into()),
+		}
+	}
+}
+
+impl From<base64::DecodeError> for GltrError {
+	fn from(value: base64::DecodeError) -> Self {
+		let position = match value {
+			base64::DecodeError::InvalidByte(p, _) | base64::DecodeError::InvalidLastSymbol(p, _) => Some(p),
+			base64::DecodeError::InvalidLength(_) | base64::DecodeError::InvalidPadding => None,
+		};
+
+		GltrError::Base64 { position, source: value }
+	}
+}
+
+impl From<std::io::Error> for GltrError {
+	fn from(value: std::io::Error) -> Self {
+		GltrError::Io(value)
+	}
+}
@@ -0,0 +1,209 @@
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::{GltfObject, GltrResult};
+use crate::math::*;
+use serde_derive::Serialize;
+
+/// An axis aligned bounding box
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+	pub min: [f32; 3],
+	pub max: [f32; 3],
+}
+
+/// A sphere containing every point it was built from, see [GltfObject::node_bounding_sphere]
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct BoundingSphere {
+	pub center: [f32; 3],
+	pub radius: f32,
+}
+
+impl Aabb {
+	/// the smallest box containing every point, None without points
+	pub fn from_points(points: &[[f32; 3]]) -> Option<Aabb> {
+		let (first, rest) = points.split_first()?;
+		Some(rest.iter().fold(Aabb { min: *first, max: *first }, |b, p| Aabb { min: min(b.min, *p), max: max(b.max, *p) }))
+	}
+
+	pub fn union(&self, other: &Aabb) -> Aabb {
+		Aabb {
+			min: min(self.min, other.min),
+			max: max(self.max, other.max),
+		}
+	}
+
+	pub fn center(&self) -> [f32; 3] {
+		scale(add(self.min, self.max), 0.5f32)
+	}
+
+	pub fn size(&self) -> [f32; 3] {
+		sub(self.max, self.min)
+	}
+
+	/// the eight corners, x changing fastest
+	pub fn corners(&self) -> [[f32; 3]; 8] {
+		let (a, b) = (self.min, self.max);
+		[0, 1, 2, 3, 4, 5, 6, 7].map(|i| {
+			[
+				if i & 1 == 0 { a[0] } else { b[0] },
+				if i & 2 == 0 { a[1] } else { b[1] },
+				if i & 4 == 0 { a[2] } else { b[2] },
+			]
+		})
+	}
+
+	/// the box containing the eight transformed corners, larger than the transformed contents under rotation
+	pub fn transformed(&self, matrix: &[f32; 16]) -> Aabb {
+		let corners = self.corners().map(|c| transform_point(matrix, c));
+		Aabb::from_points(&corners).unwrap_or(*self)
+	}
+
+	/// the sphere through the corners
+	pub fn bounding_sphere(&self) -> BoundingSphere {
+		BoundingSphere {
+			center: self.center(),
+			radius: length(self.size()) / 2f32,
+		}
+	}
+}
+
+impl BoundingSphere {
+	/// Ritter's approximation of the smallest sphere containing every point, usually within a few percent of it
+	pub fn from_points(points: &[[f32; 3]]) -> Option<BoundingSphere> {
+		let first = *points.first()?;
+
+		// the diameter starts between the point farthest from any point and the point farthest from that one
+		let farthest = |from: [f32; 3]| {
+			points.iter().copied().fold(from, |f, p| if length(sub(p, from)) > length(sub(f, from)) { p } else { f })
+		};
+		let a = farthest(first);
+		let b = farthest(a);
+
+		let mut sphere = BoundingSphere {
+			center: scale(add(a, b), 0.5f32),
+			radius: length(sub(b, a)) / 2f32,
+		};
+
+		// points outside grow the sphere just enough to reach them, keeping the opposite side in place
+		for p in points {
+			let distance = length(sub(*p, sphere.center));
+			if distance > sphere.radius {
+				let radius = (sphere.radius + distance) / 2f32;
+				sphere.center = add(sphere.center, scale(sub(*p, sphere.center), (radius - sphere.radius) / distance));
+				sphere.radius = radius;
+			}
+		}
+
+		Some(sphere)
+	}
+}
+
+impl GltfObject {
+	/// the box of the POSITION of every primitive of a mesh, in the space of the mesh. the declared min/max of
+	/// POSITION are used when present, the data is read when they are missing or normalized. morph targets are
+	/// not applied
+	pub fn mesh_bounds(&self, mesh: Index<GltfMesh>) -> GltrResult<Option<Aabb>> {
+		let mut bounds: Option<Aabb> = None;
+
+		for primitive in &self.try_get(mesh)?.primitives {
+			let position = match primitive.attributes.get("POSITION") {
+				None => continue,
+				Some(position) => *position,
+			};
+
+			let accessor = self.try_get(position)?;
+			let aabb = match (accessor.min.as_slice(), accessor.max.as_slice()) {
+				(&[a, b, c], &[x, y, z]) if !accessor.normalized => Some(Aabb { min: [a, b, c], max: [x, y, z] }),
+				_ => Aabb::from_points(&self.read_accessor_array::<3>(position)?),
+			};
+
+			bounds = match (bounds, aabb) {
+				(Some(b), Some(a)) => Some(b.union(&a)),
+				(b, a) => b.or(a),
+			};
+		}
+
+		Ok(bounds)
+	}
+
+	/// the world space points bounding the meshes of `nodes`: the corners of every mesh box, see
+	/// [GltfObject::mesh_bounds], or with `exact` every vertex. instanced nodes contribute every instance
+	fn world_points(&self, nodes: &[Index<GltfNode>], exact: bool) -> GltrResult<Vec<[f32; 3]>> {
+		let parents = self.node_parents();
+		let mut points = vec![];
+
+		for idx in nodes {
+			let mesh = match self.try_get(*idx)?.mesh {
+				None => continue,
+				Some(mesh) => mesh,
+			};
+
+			let local = match exact {
+				true => {
+					let mut vertices = vec![];
+					for primitive in &self.try_get(mesh)?.primitives {
+						if let Some(position) = primitive.attributes.get("POSITION") {
+							vertices.extend(self.read_accessor_array::<3>(*position)?);
+						}
+					}
+					vertices
+				}
+				false => match self.mesh_bounds(mesh)? {
+					None => continue,
+					Some(aabb) => aabb.corners().to_vec(),
+				},
+			};
+
+			let world = self.node_world_matrix_with(*idx, &parents)?;
+			let matrices = match self.nodes[idx.value()].has_extension(ExtMeshGpuInstancing::NAME) {
+				false => vec![world],
+				true => self.node_instance_matrices(*idx)?.iter().map(|instance| mat4_mul(&world, instance)).collect(),
+			};
+
+			for matrix in &matrices {
+				points.extend(local.iter().map(|p| transform_point(matrix, *p)));
+			}
+		}
+
+		Ok(points)
+	}
+
+	/// the nodes whose meshes are bounded: the node, and with `recursive` all of its descendants
+	fn bounded_nodes(&self, idx: Index<GltfNode>, recursive: bool) -> GltrResult<Vec<Index<GltfNode>>> {
+		match recursive {
+			true => self.node_subtree(idx),
+			false => self.try_get(idx).map(|_| vec![idx]),
+		}
+	}
+
+	/// every node of a scene, with an [GltrError::InvalidIndex] if it does not exist
+	pub(crate) fn bounded_scene_nodes(&self, scene: Index<GltfScene>) -> GltrResult<Vec<Index<GltfNode>>> {
+		let mut nodes = vec![];
+		for root in &self.try_get(scene)?.nodes {
+			nodes.extend(self.node_subtree(*root)?);
+		}
+
+		Ok(nodes)
+	}
+
+	/// the world space box of the mesh of a node, and with `recursive` of the meshes of its descendants. the
+	/// corners of every mesh box are transformed unless `exact`, which transforms every vertex for a tighter box
+	/// under rotation. None when there is no mesh to bound. skins and morph targets are not applied
+	pub fn node_bounds(&self, idx: Index<GltfNode>, recursive: bool, exact: bool) -> GltrResult<Option<Aabb>> {
+		Ok(Aabb::from_points(&self.world_points(&self.bounded_nodes(idx, recursive)?, exact)?))
+	}
+
+	/// the world space sphere around the same points as [GltfObject::node_bounds]
+	pub fn node_bounding_sphere(&self, idx: Index<GltfNode>, recursive: bool, exact: bool) -> GltrResult<Option<BoundingSphere>> {
+		Ok(BoundingSphere::from_points(&self.world_points(&self.bounded_nodes(idx, recursive)?, exact)?))
+	}
+
+	/// the world space box of every mesh drawn by a scene, see [GltfObject::node_bounds]
+	pub fn scene_bounds(&self, scene: Index<GltfScene>, exact: bool) -> GltrResult<Option<Aabb>> {
+		Ok(Aabb::from_points(&self.world_points(&self.bounded_scene_nodes(scene)?, exact)?))
+	}
+
+	/// the world space sphere around every mesh drawn by a scene, see [GltfObject::node_bounding_sphere]
+	pub fn scene_bounding_sphere(&self, scene: Index<GltfScene>, exact: bool) -> GltrResult<Option<BoundingSphere>> {
+		Ok(BoundingSphere::from_points(&self.world_points(&self.bounded_scene_nodes(scene)?, exact)?))
+	}
+}
@@ -0,0 +1,470 @@
+use crate::buffers::BufferViewTarget;
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::{GltfObject, GltrError, GltrResult};
+use crate::math::*;
+use std::collections::HashMap;
+
+/// One of the six directions along the axes of a coordinate system
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+	X,
+	NegativeX,
+	Y,
+	NegativeY,
+	Z,
+	NegativeZ,
+}
+
+impl Axis {
+	fn index(self) -> usize {
+		match self {
+			Axis::X | Axis::NegativeX => 0,
+			Axis::Y | Axis::NegativeY => 1,
+			Axis::Z | Axis::NegativeZ => 2,
+		}
+	}
+
+	fn sign(self) -> f32 {
+		match self {
+			Axis::X | Axis::Y | Axis::Z => 1f32,
+			_ => -1f32,
+		}
+	}
+}
+
+/// The axes pointing up, forward and right in a coordinate system, forward being the direction the front of an
+/// asset faces, see [GltfObject::convert_axes]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoordinateSystem {
+	pub up: Axis,
+	pub forward: Axis,
+	pub right: Axis,
+}
+
+impl CoordinateSystem {
+	/// +Y up, +Z forward and -X right
+	///
+	/// https://registry.khronos.org/glTF/specs/2.0/glTF-2.0.html#coordinate-system-and-units
+	pub const GLTF: CoordinateSystem = CoordinateSystem {
+		up: Axis::Y,
+		forward: Axis::Z,
+		right: Axis::NegativeX,
+	};
+
+	/// +Z up, -Y forward and -X right, the right handed Z up system of Blender and 3ds Max
+	pub const Z_UP: CoordinateSystem = CoordinateSystem {
+		up: Axis::Z,
+		forward: Axis::NegativeY,
+		right: Axis::NegativeX,
+	};
+
+	fn axes(&self) -> [Axis; 3] {
+		[self.up, self.forward, self.right]
+	}
+}
+
+/// a change of basis by a signed permutation of the axes, followed by a uniform scale
+struct Conversion {
+	/// the permutation times the scale
+	matrix: Mat4,
+	inverse: Mat4,
+	permutation: Mat4,
+	factor: f32,
+	mirrored: bool,
+}
+
+impl Conversion {
+	fn new(permutation: Mat4, factor: f32) -> Conversion {
+		let mut matrix = permutation;
+		let mut inverse = IDENTITY;
+		for c in 0..3 {
+			for r in 0..3 {
+				matrix[c * 4 + r] *= factor;
+				// the inverse of a signed permutation is its transpose
+				inverse[c * 4 + r] = permutation[r * 4 + c] / factor;
+			}
+		}
+
+		Conversion {
+			matrix,
+			inverse,
+			permutation,
+			factor,
+			mirrored: mat4_determinant3(&permutation) < 0f32,
+		}
+	}
+
+	fn point(&self, p: Vec3) -> Vec3 {
+		transform_point(&self.matrix, p)
+	}
+
+	fn direction(&self, v: Vec3) -> Vec3 {
+		transform_vector(&self.permutation, v)
+	}
+
+	/// the same transform expressed in the converted space, C * m * C^-1
+	fn conjugate(&self, m: &Mat4) -> Mat4 {
+		mat4_mul(&mat4_mul(&self.matrix, m), &self.inverse)
+	}
+
+	/// the rotation expressed in the converted space. a mirror turns the axis over but keeps the rotation proper
+	fn conjugate_rotation(&self, q: Quat) -> Quat {
+		let [x, y, z] = scale(self.direction([q[0], q[1], q[2]]), if self.mirrored { -1f32 } else { 1f32 });
+		[x, y, z, q[3]]
+	}
+
+	/// the scale along the permuted axes
+	fn conjugate_scale(&self, s: Vec3) -> Vec3 {
+		transform_vector(&self.permutation.map(f32::abs), s)
+	}
+}
+
+impl GltfObject {
+	/// converts the document from the `from` coordinate system to `to`, see [GltfObject::scale_units] for what
+	/// `bake` does. a conversion between systems of different handedness mirrors the document: without `bake` the
+	/// root nodes get a matrix with a negative determinant, which can't be animated. with `bake` cameras and lights
+	/// move to a new child of their node that keeps them looking the same way
+	pub fn convert_axes(&mut self, from: CoordinateSystem, to: CoordinateSystem, bake: bool) -> GltrResult<()> {
+		for system in [from, to] {
+			let [up, forward, right] = system.axes().map(Axis::index);
+			if up == forward || up == right || forward == right {
+				return Err(GltrError::ConstraintViolation(format!("{system:?} uses an axis twice")));
+			}
+		}
+
+		let mut permutation = [0f32; 16];
+		permutation[15] = 1f32;
+		for (a, b) in from.axes().into_iter().zip(to.axes()) {
+			permutation[a.index() * 4 + b.index()] = a.sign() * b.sign();
+		}
+
+		self.convert(Conversion::new(permutation, 1f32), bake)
+	}
+
+	/// scales the document by `factor`, eg 100 from glTF metres to centimetres.
+	///
+	/// without `bake` only the transforms of the root nodes and their animations change. with `bake` the
+	/// conversion is applied to every vertex, node, animation key, instance and inverse bind matrix instead, so
+	/// the document reads as authored in the new units. baked POSITION and animated translations and scales are written as FLOAT,
+	/// the replaced accessors stay in the document until it is pruned. either way camera clip planes and
+	/// magnifications and light ranges are scaled, as node scale doesn't apply to them
+	pub fn scale_units(&mut self, factor: f32, bake: bool) -> GltrResult<()> {
+		if !factor.is_finite() || factor <= 0f32 {
+			return Err(GltrError::ConstraintViolation(format!("unit scale {factor} must be positive")));
+		}
+
+		self.convert(Conversion::new(IDENTITY, factor), bake)
+	}
+
+	fn convert(&mut self, conversion: Conversion, bake: bool) -> GltrResult<()> {
+		match bake {
+			true => self.bake_conversion(&conversion)?,
+			false => self.convert_roots(&conversion)?,
+		}
+
+		let f = conversion.factor;
+		for camera in &mut self.cameras {
+			if let Some(p) = &mut camera.perspective {
+				p.z_near *= f;
+				p.z_far = p.z_far.map(|z| z * f);
+			}
+			if let Some(o) = &mut camera.orthographic {
+				o.x_magnitude *= f;
+				o.y_magnitude *= f;
+				o.z_near *= f;
+				o.z_far *= f;
+			}
+		}
+
+		if let Some(lights) = self.extension_mut::<KhrLightsPunctual>() {
+			for light in &mut lights.lights {
+				light.range = light.range.map(|r| r * f);
+			}
+		}
+
+		Ok(())
+	}
+
+	/// the animation channels targeting every node, as (animation, channel)
+	fn node_channels(&self) -> Vec<Vec<(usize, usize)>> {
+		let mut channels = vec![vec![]; self.nodes.len()];
+		for (a, animation) in self.animations.iter().enumerate() {
+			for (c, channel) in animation.channels.iter().enumerate() {
+				if let Some(n) = channels.get_mut(channel.target.node.map_or(usize::MAX, |n| n.value())) {
+					n.push((a, c));
+				}
+			}
+		}
+		channels
+	}
+
+	/// reads an accessor, applies `f` to every element and pushes the result, keeping the type, and the component
+	/// type unless `float`
+	fn map_accessor(
+		&mut self,
+		idx: Index<GltfAccessor>,
+		float: bool,
+		target: Option<BufferViewTarget>,
+		f: impl Fn(&mut [f32]),
+	) -> GltrResult<Index<GltfAccessor>> {
+		let accessor = self.try_get(idx)?;
+		let (accessor_type, n) = (accessor.r#type.clone(), accessor.component_count());
+		let (component_type, normalized) = match float {
+			true => (ComponentType::FLOAT, false),
+			false => (accessor.component_type, accessor.normalized),
+		};
+
+		let mut values = self.read_accessor_f32(idx)?;
+		values.chunks_exact_mut(n).for_each(f);
+		self.push_accessor_f32(&values, &accessor_type, component_type, normalized, target)
+	}
+
+	/// converts the output of an animation sampler for a channel of `path`, once for every accessor and path
+	fn convert_channel(
+		&mut self,
+		cache: &mut HashMap<(usize, String), Index<GltfAccessor>>,
+		animation: usize,
+		channel: usize,
+		f: &dyn Fn(&str, &mut [f32]),
+	) -> GltrResult<()> {
+		let path = self.animations[animation].channels[channel].target.path.clone();
+		if !matches!(path.as_str(), "translation" | "rotation" | "scale") {
+			return Ok(());
+		}
+
+		let sampler = self.animations[animation].channels[channel].sampler.value();
+		let output = match self.animations[animation].samplers.get(sampler) {
+			None => return Err(GltrError::invalid_index(format!("/animations/{animation}/samplers"), sampler)),
+			Some(s) => s.output,
+		};
+
+		let converted = match cache.get(&(output.value(), path.clone())) {
+			Some(converted) => *converted,
+			None => {
+				let converted = self.map_accessor(output, path != "rotation", None, |v| f(&path, v))?;
+				cache.insert((output.value(), path), converted);
+				converted
+			}
+		};
+
+		// samplers shared with channels of other paths get a copy of their own
+		let shared = self.animations[animation].channels.iter().filter(|c| c.sampler.value() == sampler).count() > 1;
+		match shared {
+			false => self.animations[animation].samplers[sampler].output = converted,
+			true => {
+				let mut copy = self.animations[animation].samplers[sampler].clone();
+				copy.output = converted;
+				let samplers = &mut self.animations[animation].samplers;
+				samplers.push(copy);
+				self.animations[animation].channels[channel].sampler = Index::new(samplers.len() - 1);
+			}
+		}
+
+		Ok(())
+	}
+
+	/// puts the conversion on the root nodes, and on the level of detail nodes of roots
+	fn convert_roots(&mut self, c: &Conversion) -> GltrResult<()> {
+		let parents = self.node_parents();
+
+		let mut lod_base = vec![None; self.nodes.len()];
+		for (n, node) in self.nodes.iter().enumerate() {
+			for id in node.extension::<MsftLod>().into_iter().flat_map(|l| &l.ids) {
+				if let Some(base) = lod_base.get_mut(id.value()) {
+					*base = Some(n);
+				}
+			}
+		}
+
+		let roots: Vec<usize> = (0..self.nodes.len())
+			.filter(|n| parents[*n].is_none() && lod_base[*n].is_none_or(|base| parents[base].is_none()))
+			.collect();
+
+		let channels = self.node_channels();
+		let rotation = mat4_decompose(&c.permutation).1;
+		let mut cache = HashMap::new();
+
+		let animated = |idx: usize| channels[idx].iter().any(|(a, ch)| self.animations[*a].channels[*ch].target.path != "weights");
+
+		// checked for every root before any is changed
+		for idx in &roots {
+			if (c.mirrored || self.nodes[*idx].matrix.is_some()) && animated(*idx) {
+				return Err(GltrError::ConstraintViolation(format!(
+					"root node {idx} is animated and can't take a mirroring conversion, it has to be baked"
+				)));
+			}
+		}
+
+		for idx in roots {
+			let node = &mut self.nodes[idx];
+			if c.mirrored || node.matrix.is_some() {
+				node.matrix = Some(mat4_mul(&c.matrix, &node.local_matrix()));
+				node.translation = None;
+				node.rotation = None;
+				node.scale = None;
+				continue;
+			}
+
+			let (t, r, s) = node.local_trs();
+			node.set_local_trs(c.point(t), quat_mul(rotation, r), scale(s, c.factor));
+
+			let f = |path: &str, v: &mut [f32]| match path {
+				"translation" => v.copy_from_slice(&c.point([v[0], v[1], v[2]])),
+				"rotation" => v.copy_from_slice(&quat_mul(rotation, [v[0], v[1], v[2], v[3]])),
+				_ => v.iter_mut().for_each(|x| *x *= c.factor),
+			};
+			for (a, ch) in &channels[idx] {
+				self.convert_channel(&mut cache, *a, *ch, &f)?;
+			}
+		}
+
+		Ok(())
+	}
+
+	/// expresses every vertex, node, animation key, instance and inverse bind matrix in the converted space
+	fn bake_conversion(&mut self, c: &Conversion) -> GltrResult<()> {
+		for node in &mut self.nodes {
+			match node.matrix {
+				Some(m) => node.matrix = Some(c.conjugate(&m)),
+				None => {
+					let (t, r, s) = node.local_trs();
+					node.set_local_trs(c.point(t), c.conjugate_rotation(r), c.conjugate_scale(s));
+				}
+			}
+		}
+
+		// cameras and lights look down their local -Z, which the conjugation turns with the axes. they move to a
+		// child turned back by the rotation of the permutation, a proper rotation that keeps -Z and +Y of a mirror
+		let rotation = mat4_decompose(&c.permutation).1;
+		if rotation != QUAT_IDENTITY {
+			for n in 0..self.nodes.len() {
+				let node = &mut self.nodes[n];
+				let camera = node.camera.take();
+				let light = match node.remove_extension(KhrLightsPunctualNode::NAME) {
+					Some(GltfExtension::KhrLightsPunctualNode(l)) => Some(l),
+					_ => None,
+				};
+				if camera.is_none() && light.is_none() {
+					continue;
+				}
+
+				let mut child = GltfNode {
+					name: node.name.clone(),
+					camera,
+					rotation: Some(rotation),
+					..GltfNode::default()
+				};
+				if let Some(light) = light {
+					child.set_extension(light);
+				}
+
+				let child = self.push(child);
+				self.nodes[n].children.get_or_insert_with(Vec::new).push(child);
+			}
+		}
+
+		// translation, rotation and scale values, of animation keys and instances alike
+		let trs = |path: &str, v: &mut [f32]| match path {
+			"translation" => v.copy_from_slice(&c.point([v[0], v[1], v[2]])),
+			"rotation" => v.copy_from_slice(&c.conjugate_rotation([v[0], v[1], v[2], v[3]])),
+			_ => v.copy_from_slice(&c.conjugate_scale([v[0], v[1], v[2]])),
+		};
+
+		let mut cache = HashMap::new();
+		for a in 0..self.animations.len() {
+			for ch in 0..self.animations[a].channels.len() {
+				self.convert_channel(&mut cache, a, ch, &trs)?;
+			}
+		}
+
+		for n in 0..self.nodes.len() {
+			let mut instancing = match self.nodes[n].extension::<ExtMeshGpuInstancing>() {
+				None => continue,
+				Some(e) => e.clone(),
+			};
+
+			for (name, accessor) in instancing.attributes.iter_mut() {
+				let path = name.to_lowercase();
+				if matches!(path.as_str(), "translation" | "rotation" | "scale") {
+					*accessor = self.map_accessor(*accessor, path != "rotation", None, |v| trs(&path, v))?;
+				}
+			}
+
+			self.nodes[n].set_extension(instancing);
+		}
+
+		for s in 0..self.skins.len() {
+			if let Some(matrices) = self.skins[s].inverse_bind_matrices {
+				let converted = self.map_accessor(matrices, true, None, |m| {
+					let mut matrix = IDENTITY;
+					matrix.copy_from_slice(m);
+					m.copy_from_slice(&c.conjugate(&matrix));
+				})?;
+				self.skins[s].inverse_bind_matrices = Some(converted);
+			}
+		}
+
+		let mut attributes: HashMap<(usize, bool), Index<GltfAccessor>> = HashMap::new();
+		let handedness = if c.mirrored { -1f32 } else { 1f32 };
+
+		for mesh in self.indices::<GltfMesh>().collect::<Vec<_>>() {
+			for primitive in 0..self.meshes[mesh.value()].primitives.len() {
+				let p = &self.meshes[mesh.value()].primitives[primitive];
+				let semantics = p.attributes.iter().map(|(s, a)| (s.clone(), *a, false));
+				let targets = p.targets.iter().flatten().flatten().map(|(s, a)| (s.clone(), *a, true));
+				let semantics: Vec<(String, Index<GltfAccessor>, bool)> = semantics.chain(targets).collect();
+
+				let mut converted = vec![];
+				for (semantic, accessor, target) in semantics {
+					if !matches!(semantic.as_str(), "POSITION" | "NORMAL" | "TANGENT") {
+						continue;
+					}
+
+					let key = (accessor.value(), target);
+					let new = match attributes.get(&key) {
+						Some(new) => *new,
+						None => {
+							let position = semantic == "POSITION";
+							let new = self.map_accessor(accessor, position, Some(BufferViewTarget::ARRAY_BUFFER), |v| {
+								let xyz = [v[0], v[1], v[2]];
+								v[..3].copy_from_slice(&if position { c.point(xyz) } else { c.direction(xyz) });
+								// the bitangent is the cross product of normal and tangent, which a mirror turns over
+								if v.len() == 4 {
+									v[3] *= handedness;
+								}
+							})?;
+							attributes.insert(key, new);
+							new
+						}
+					};
+					converted.push((semantic, accessor, target, new));
+				}
+
+				let p = &mut self.meshes[mesh.value()].primitives[primitive];
+				for (semantic, _, target, new) in &converted {
+					if !target {
+						p.attributes.insert(semantic.clone(), *new);
+					}
+				}
+				for morph in p.targets.iter_mut().flatten() {
+					for (semantic, accessor) in morph.iter_mut() {
+						if let Some((_, _, _, new)) = converted.iter().find(|(s, a, t, _)| *t && s == semantic && a == accessor) {
+							*accessor = *new;
+						}
+					}
+				}
+
+				// a mirror turns the triangles over, swapping two corners turns them back
+				if c.mirrored && p.mode().is_triangles() {
+					let indices: Vec<u32> = self.primitive_triangles(mesh, primitive)?.flat_map(|[a, b, c]| [a, c, b]).collect();
+					let indices = self.push_accessor_indices(&indices)?;
+					let p = self.primitive_mut(mesh, primitive)?;
+					p.accessor = Some(indices);
+					p.mode = None;
+				}
+			}
+		}
+
+		Ok(())
+	}
+}
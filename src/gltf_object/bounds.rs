@@ -0,0 +1,107 @@
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::{GltfObject, GltrResult};
+use serde_derive::Serialize;
+
+/// An accessor whose declared min/max differ from its data, found by [GltfObject::check_bounds]
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct BoundsMismatch {
+	pub accessor: Index<GltfAccessor>,
+
+	/// as found in the document, empty when missing
+	pub min: Vec<f32>,
+	pub max: Vec<f32>,
+
+	/// computed from the data
+	#[serde(rename = "expectedMin")]
+	pub expected_min: Vec<f32>,
+
+	#[serde(rename = "expectedMax")]
+	pub expected_max: Vec<f32>,
+}
+
+impl GltfObject {
+	/// the exact per component min and max of an accessor, in the stored domain used by accessor bounds: normalized
+	/// integers are not converted, sparse substitutions are applied. empty for an accessor without elements
+	pub fn accessor_bounds(&self, idx: Index<GltfAccessor>) -> GltrResult<(Vec<f32>, Vec<f32>)> {
+		let n = self.try_get(idx)?.component_count();
+		let values = self.read_accessor_raw(idx)?;
+
+		if values.len() < n || n == 0 {
+			return Ok((vec![], vec![]));
+		}
+
+		let mut min = vec![f32::MAX; n];
+		let mut max = vec![f32::MIN; n];
+		for element in values.chunks_exact(n) {
+			for (c, v) in element.iter().enumerate() {
+				min[c] = min[c].min(*v);
+				max[c] = max[c].max(*v);
+			}
+		}
+
+		Ok((min, max))
+	}
+
+	/// the accessors that declare bounds, and the ones that must: POSITION attributes and morph targets, and
+	/// animation sampler inputs
+	fn bounded_accessors(&self) -> Vec<bool> {
+		let mut bounded: Vec<bool> = self.accessors.iter().map(|a| !a.min.is_empty() || !a.max.is_empty()).collect();
+
+		let positions = self
+			.meshes
+			.iter()
+			.flat_map(|m| &m.primitives)
+			.flat_map(|p| std::iter::once(&p.attributes).chain(p.targets.iter().flatten()))
+			.filter_map(|attributes| attributes.get("POSITION"));
+		let inputs = self.animations.iter().flat_map(|a| &a.samplers).map(|s| &s.input);
+
+		for accessor in positions.chain(inputs) {
+			if let Some(b) = bounded.get_mut(accessor.value()) {
+				*b = true;
+			}
+		}
+
+		bounded
+	}
+
+	/// compares the min/max of every accessor that declares them, or must, with its data, without modifying the
+	/// document. returns the accessors that differ, see [GltfObject::recompute_bounds] to repair them
+	pub fn check_bounds(&self) -> GltrResult<Vec<BoundsMismatch>> {
+		let mut mismatches = vec![];
+
+		for (i, bounded) in self.bounded_accessors().into_iter().enumerate() {
+			if !bounded {
+				continue;
+			}
+
+			let idx = Index::new(i);
+			let (expected_min, expected_max) = self.accessor_bounds(idx)?;
+			let accessor = &self.accessors[i];
+			if accessor.min != expected_min || accessor.max != expected_max {
+				mismatches.push(BoundsMismatch {
+					accessor: idx,
+					min: accessor.min.clone(),
+					max: accessor.max.clone(),
+					expected_min,
+					expected_max,
+				});
+			}
+		}
+
+		Ok(mismatches)
+	}
+
+	/// writes the exact min/max of every accessor that declares them, or must, see [GltfObject::check_bounds].
+	/// returns the number of accessors whose bounds changed
+	pub fn recompute_bounds(&mut self) -> GltrResult<usize> {
+		let mismatches = self.check_bounds()?;
+
+		for mismatch in &mismatches {
+			let accessor = &mut self.accessors[mismatch.accessor.value()];
+			accessor.min = mismatch.expected_min.clone();
+			accessor.max = mismatch.expected_max.clone();
+		}
+
+		Ok(mismatches.len())
+	}
+}
@@ -0,0 +1,270 @@
+use crate::buffers::{array_accessor_type, encode_accessor_f32, encode_accessor_indices};
+use crate::buffers::{BufferViewTarget, GltfBase64Buffer, GltfBufferView};
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::{GltfObject, GltrError, GltrResult};
+use crate::math::QUAT_IDENTITY;
+use std::collections::BTreeMap;
+
+pub type BufferViewId = Index<GltfBufferView>;
+pub type AccessorId = Index<GltfAccessor>;
+pub type ImageId = Index<GltfImage>;
+pub type TextureId = Index<GltfTexture>;
+pub type MaterialId = Index<GltfMaterial>;
+pub type MeshId = Index<GltfMesh>;
+pub type NodeId = Index<GltfNode>;
+pub type SceneId = Index<GltfScene>;
+
+/// translation, rotation and scale of a node relative to its parent
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NodeTransform {
+	pub translation: [f32; 3],
+	/// unit quaternion, [x, y, z, w]
+	pub rotation: [f32; 4],
+	pub scale: [f32; 3],
+}
+
+impl Default for NodeTransform {
+	fn default() -> Self {
+		NodeTransform {
+			translation: [0f32; 3],
+			rotation: QUAT_IDENTITY,
+			scale: [1f32; 3],
+		}
+	}
+}
+
+impl NodeTransform {
+	pub fn from_translation(translation: [f32; 3]) -> Self {
+		NodeTransform { translation, ..NodeTransform::default() }
+	}
+}
+
+/// vertex data of a single triangle list primitive, see [GltfBuilder::add_mesh].
+/// every attribute that is not empty must have one element per position
+#[derive(Debug, Clone, Default)]
+pub struct PrimitiveData {
+	pub positions: Vec<[f32; 3]>,
+	pub normals: Vec<[f32; 3]>,
+	pub tangents: Vec<[f32; 4]>,
+	pub tex_coords: Vec<[f32; 2]>,
+	pub colors: Vec<[f32; 4]>,
+
+	/// empty for non-indexed geometry
+	pub indices: Vec<u32>,
+
+	pub material: Option<MaterialId>,
+}
+
+/// Builds a [GltfObject] from code.
+///
+/// objects are added one at a time and referenced by the handle returned when adding them. all binary data is
+/// collected into a single buffer that is encoded once, by [GltfBuilder::build]
+pub struct GltfBuilder {
+	object: GltfObject,
+	data: Vec<u8>,
+}
+
+impl Default for GltfBuilder {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl GltfBuilder {
+	pub fn new() -> Self {
+		GltfBuilder {
+			object: GltfObject::new(),
+			data: Vec::new(),
+		}
+	}
+
+	/// adds a buffer view over a copy of `bytes`, aligned to 4 bytes within the buffer
+	pub fn add_buffer_data(&mut self, bytes: &[u8], target: Option<BufferViewTarget>) -> BufferViewId {
+		self.data.resize(self.data.len().next_multiple_of(4), 0);
+		let byte_offset = self.data.len();
+		self.data.extend_from_slice(bytes);
+
+		self.object.push(GltfBufferView::new(Index::new(0), byte_offset, bytes.len(), target))
+	}
+
+	/// adds a FLOAT accessor of the VECn/SCALAR/MAT4 type matching `N`, with min and max
+	pub fn add_accessor<const N: usize>(&mut self, values: &[[f32; N]], target: Option<BufferViewTarget>) -> GltrResult<AccessorId> {
+		let flat: Vec<f32> = values.iter().flatten().copied().collect();
+		let (bytes, mut accessor) = encode_accessor_f32(&flat, array_accessor_type::<N>()?, ComponentType::FLOAT, false)?;
+
+		accessor.buffer_view = Some(self.add_buffer_data(&bytes, target));
+		Ok(self.object.push(accessor))
+	}
+
+	/// adds an index accessor, UNSIGNED_SHORT when every index fits and UNSIGNED_INT otherwise
+	pub fn add_indices(&mut self, indices: &[u32]) -> AccessorId {
+		let (bytes, mut accessor) = encode_accessor_indices(indices, ComponentType::UNSIGNED_SHORT);
+
+		accessor.buffer_view = Some(self.add_buffer_data(&bytes, Some(BufferViewTarget::ELEMENT_ARRAY_BUFFER)));
+		self.object.push(accessor)
+	}
+
+	/// adds a mesh with one primitive per entry of `primitives`, writing their vertex data to the buffer
+	pub fn add_mesh(&mut self, primitives: Vec<PrimitiveData>) -> GltrResult<MeshId> {
+		let mut mesh = GltfMesh::default();
+
+		for (primitive_idx, data) in primitives.into_iter().enumerate() {
+			let count = data.positions.len();
+			let lengths = [
+				("NORMAL", data.normals.len()),
+				("TANGENT", data.tangents.len()),
+				("TEXCOORD_0", data.tex_coords.len()),
+				("COLOR_0", data.colors.len()),
+			];
+
+			for (semantic, len) in lengths {
+				if len != 0 && len != count {
+					return Err(GltrError::ConstraintViolation(format!(
+						"primitive {primitive_idx} has {count} positions but {len} {semantic} values"
+					)));
+				}
+			}
+
+			if let Some(i) = data.indices.iter().find(|i| **i as usize >= count) {
+				return Err(GltrError::ConstraintViolation(format!(
+					"primitive {primitive_idx} has index {i} but only {count} vertices"
+				)));
+			}
+
+			if let Some(material) = data.material {
+				self.object.try_get(material)?;
+			}
+
+			let target = Some(BufferViewTarget::ARRAY_BUFFER);
+			let mut attributes = BTreeMap::new();
+			attributes.insert("POSITION".to_string(), self.add_accessor(&data.positions, target)?);
+
+			if !data.normals.is_empty() {
+				attributes.insert("NORMAL".to_string(), self.add_accessor(&data.normals, target)?);
+			}
+			if !data.tangents.is_empty() {
+				attributes.insert("TANGENT".to_string(), self.add_accessor(&data.tangents, target)?);
+			}
+			if !data.tex_coords.is_empty() {
+				attributes.insert("TEXCOORD_0".to_string(), self.add_accessor(&data.tex_coords, target)?);
+			}
+			if !data.colors.is_empty() {
+				attributes.insert("COLOR_0".to_string(), self.add_accessor(&data.colors, target)?);
+			}
+
+			let indices = if data.indices.is_empty() { None } else { Some(self.add_indices(&data.indices)) };
+
+			mesh.primitives.push(GltfMeshPrimitive {
+				attributes,
+				accessor: indices,
+				material: data.material,
+				mode: None,
+				targets: None,
+				extensions: None,
+				extras: None,
+				unknown_fields: UnknownFields::new(),
+				original_index: None,
+			});
+		}
+
+		Ok(self.object.push(mesh))
+	}
+
+	/// adds a metallic-roughness material, everything else on the material keeps its default
+	pub fn add_material(&mut self, pbr: GltfMetallicRoughness) -> MaterialId {
+		self.object.push(GltfMaterial {
+			pbr_metallic_roughness: Some(pbr),
+			..GltfMaterial::default()
+		})
+	}
+
+	/// embeds a PNG file in the buffer and adds a texture sampling it with the default sampler
+	pub fn add_texture_from_png(&mut self, bytes: &[u8]) -> GltrResult<TextureId> {
+		const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+		if !bytes.starts_with(&PNG_SIGNATURE) {
+			return Err(GltrError::ConstraintViolation("image data is not a PNG file".to_string()));
+		}
+
+		let image = self.add_image(bytes, "image/png");
+		Ok(self.object.push(GltfTexture {
+			source_image_index: Some(image),
+			..GltfTexture::default()
+		}))
+	}
+
+	fn add_image(&mut self, bytes: &[u8], mime_type: &str) -> ImageId {
+		let buffer_view = self.add_buffer_data(bytes, None);
+
+		self.object.push(GltfImage {
+			mime_type: Some(mime_type.to_string()),
+			buffer_view: Some(buffer_view),
+			..GltfImage::default()
+		})
+	}
+
+	/// adds a node, as a child of `parent` or as a root node when there is none
+	pub fn add_node(&mut self, parent: Option<NodeId>, transform: NodeTransform) -> GltrResult<NodeId> {
+		let idx = NodeId::new(self.object.nodes.len());
+
+		if let Some(parent) = parent {
+			match self.object.get_mut(parent) {
+				None => return Err(GltrError::invalid_index("/nodes", parent.value())),
+				Some(p) => p.children.get_or_insert_with(Vec::new).push(idx),
+			}
+		}
+
+		let mut node = GltfNode::default();
+		node.set_local_trs(transform.translation, transform.rotation, transform.scale);
+
+		Ok(self.object.push(node))
+	}
+
+	/// adds a node drawing `mesh`, see [GltfBuilder::add_node]
+	pub fn add_mesh_node(&mut self, parent: Option<NodeId>, transform: NodeTransform, mesh: MeshId) -> GltrResult<NodeId> {
+		self.object.try_get(mesh)?;
+
+		let node = self.add_node(parent, transform)?;
+		self.node_mut(node).mesh = Some(mesh);
+
+		Ok(node)
+	}
+
+	/// adds a scene with the given root nodes. the first scene added becomes the default scene
+	pub fn add_scene(&mut self, name: Option<&str>, roots: &[NodeId]) -> GltrResult<SceneId> {
+		for root in roots {
+			self.object.try_get(*root)?;
+		}
+
+		let idx = self.object.push(GltfScene {
+			name: name.map(|n| n.to_string()),
+			nodes: roots.to_vec(),
+			..GltfScene::default()
+		});
+		self.object.scene.get_or_insert(idx);
+
+		Ok(idx)
+	}
+
+	pub fn node_mut(&mut self, node: NodeId) -> &mut GltfNode {
+		&mut self.object.nodes[node.value()]
+	}
+
+	pub fn mesh_mut(&mut self, mesh: MeshId) -> &mut GltfMesh {
+		&mut self.object.meshes[mesh.value()]
+	}
+
+	pub fn material_mut(&mut self, material: MaterialId) -> &mut GltfMaterial {
+		&mut self.object.materials[material.value()]
+	}
+
+	/// the document built so far, with its binary data encoded into its only buffer
+	pub fn build(mut self) -> GltfObject {
+		if !self.data.is_empty() {
+			self.object.buffers.0 = vec![GltfBase64Buffer::from_bytes(&self.data)];
+		}
+
+		self.object.sync_extensions();
+		self.object
+	}
+}
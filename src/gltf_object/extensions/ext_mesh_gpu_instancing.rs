@@ -0,0 +1,216 @@
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::{GltfObject, GltrResult};
+use crate::math::*;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// EXT_mesh_gpu_instancing, found in the extensions of a node with a mesh
+///
+/// https://github.com/KhronosGroup/glTF/tree/main/extensions/2.0/Vendor/EXT_mesh_gpu_instancing
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct ExtMeshGpuInstancing {
+	/// attribute name (TRANSLATION, ROTATION, SCALE or a custom attribute) to accessor index
+	pub attributes: BTreeMap<String, Index<GltfAccessor>>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extensions: Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras: Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
+}
+
+impl GltfObject {
+	/// collapses sibling nodes that draw the same mesh into a single node using EXT_mesh_gpu_instancing.
+	///
+	/// only nodes with a mesh and nothing else (no children, camera, skin, morph weights, extensions or animation)
+	/// that aren't skin joints are considered. each group of two or more siblings sharing a mesh is replaced by one
+	/// node with an identity transform whose TRANSLATION/ROTATION/SCALE accessors hold the local transforms of the
+	/// original nodes. returns the indices of the instanced nodes
+	pub fn instance_repeated_meshes(&mut self) -> GltrResult<Vec<Index<GltfNode>>> {
+		let mut animated = vec![false; self.nodes.len()];
+		for animation in &self.animations {
+			for channel in &animation.channels {
+				if let Some(n) = channel.target.node {
+					if let Some(a) = animated.get_mut(n.value()) {
+						*a = true;
+					}
+				}
+			}
+		}
+
+		// joints can't be removed, and a skinned node ignores its transform
+		let joints = self.skin_joints();
+
+		let eligible = |idx: usize, node: &GltfNode| {
+			node.mesh.is_some()
+				&& node.child_indices().is_empty()
+				&& node.camera.is_none()
+				&& node.skin.is_none()
+				&& !joints[idx]
+				&& node.weights.is_none()
+				&& node.extensions.is_none()
+				&& !animated[idx]
+		};
+
+		// every list of siblings: children of each node, plus the root nodes of each scene
+		let mut sibling_lists: Vec<Vec<Index<GltfNode>>> = self.nodes.iter().map(|n| n.child_indices().to_vec()).collect();
+		sibling_lists.extend(self.scenes.iter().map(|s| s.nodes.clone()));
+
+		let mut groups: Vec<Vec<Index<GltfNode>>> = Vec::new();
+		let mut grouped = vec![false; self.nodes.len()];
+
+		for siblings in &sibling_lists {
+			let mut by_mesh: BTreeMap<Index<GltfMesh>, Vec<Index<GltfNode>>> = BTreeMap::new();
+
+			for idx in siblings {
+				let node = self.try_get(*idx)?;
+
+				// a node listed under two parents (or two scenes) can't be folded into either
+				if grouped[idx.value()] || !eligible(idx.value(), node) {
+					continue;
+				}
+
+				by_mesh.entry(node.mesh.unwrap()).or_default().push(*idx);
+			}
+
+			for (_, group) in by_mesh {
+				if group.len() > 1 {
+					for idx in &group {
+						grouped[idx.value()] = true;
+					}
+					groups.push(group);
+				}
+			}
+		}
+
+		let mut to_remove = Vec::new();
+		let mut instanced = Vec::new();
+
+		for group in groups {
+			let transforms: Vec<_> = group.iter().map(|x| self.nodes[x.value()].local_trs()).collect();
+
+			let translations: Vec<[f32; 3]> = transforms.iter().map(|x| x.0).collect();
+			let rotations: Vec<[f32; 4]> = transforms.iter().map(|x| x.1).collect();
+			let scales: Vec<[f32; 3]> = transforms.iter().map(|x| x.2).collect();
+
+			let mut extension = ExtMeshGpuInstancing::default();
+			extension.attributes.insert("TRANSLATION".to_string(), self.push_accessor_array(&translations, None)?);
+
+			if rotations.iter().any(|r| *r != QUAT_IDENTITY) {
+				extension.attributes.insert("ROTATION".to_string(), self.push_accessor_array(&rotations, None)?);
+			}
+
+			if scales.iter().any(|s| *s != [1f32; 3]) {
+				extension.attributes.insert("SCALE".to_string(), self.push_accessor_array(&scales, None)?);
+			}
+
+			let keep = group[0];
+			let node = &mut self.nodes[keep.value()];
+			node.set_local_trs([0f32; 3], QUAT_IDENTITY, [1f32; 3]);
+			node.set_extension(extension);
+
+			instanced.push(keep);
+			to_remove.extend_from_slice(&group[1..]);
+		}
+
+		self.remove_nodes(&to_remove)?;
+		self.sync_extensions();
+
+		// indices of the kept nodes shift down by the number of removed nodes before them
+		to_remove.sort();
+		Ok(instanced
+			.into_iter()
+			.map(|idx| Index::new(idx.value() - to_remove.partition_point(|r| *r < idx)))
+			.collect())
+	}
+
+	/// replaces EXT_mesh_gpu_instancing on every node with ordinary nodes, one child per instance.
+	///
+	/// the instanced node keeps its own transform and children, its mesh moves to the new children which carry the
+	/// per-instance TRANSLATION/ROTATION/SCALE. custom instance attributes are dropped
+	pub fn expand_instancing(&mut self) -> GltrResult<()> {
+		for idx in self.indices::<GltfNode>().collect::<Vec<_>>() {
+			if !self.nodes[idx.value()].has_extension(ExtMeshGpuInstancing::NAME) {
+				continue;
+			}
+
+			let transforms = self.node_instance_transforms(idx)?;
+
+			let node = &mut self.nodes[idx.value()];
+			node.remove_extension(ExtMeshGpuInstancing::NAME);
+
+			let mesh = node.mesh.take();
+			let name = node.name.clone();
+
+			let first_child = self.nodes.len();
+			for (i, (t, r, s)) in transforms.iter().enumerate() {
+				let mut child = GltfNode {
+					mesh,
+					name: name.as_ref().map(|n| format!("{n}_{i}")),
+					..GltfNode::default()
+				};
+				child.set_local_trs(*t, *r, *s);
+
+				self.nodes.push(child);
+			}
+
+			self.nodes[idx.value()]
+				.children
+				.get_or_insert_with(Vec::new)
+				.extend((first_child..first_child + transforms.len()).map(Index::new));
+		}
+
+		self.sync_extensions();
+
+		Ok(())
+	}
+
+	/// the number of instances drawn for a node, 1 for nodes without EXT_mesh_gpu_instancing
+	pub fn node_instance_count(&self, node: Index<GltfNode>) -> GltrResult<usize> {
+		match self.try_get(node)?.extension::<ExtMeshGpuInstancing>() {
+			None => Ok(1),
+			Some(e) => Ok(e.attributes.values().filter_map(|a| self.get(*a)).map(|a| a.count).min().unwrap_or(0)),
+		}
+	}
+
+	/// per-instance (translation, rotation, scale) of an instanced node, relative to the node.
+	/// empty for nodes without EXT_mesh_gpu_instancing
+	#[allow(clippy::type_complexity)]
+	pub fn node_instance_transforms(&self, node: Index<GltfNode>) -> GltrResult<Vec<([f32; 3], [f32; 4], [f32; 3])>> {
+		let extension = match self.try_get(node)?.extension::<ExtMeshGpuInstancing>() {
+			None => return Ok(vec![]),
+			Some(e) => e,
+		};
+
+		let read = |name: &str| -> GltrResult<Option<Vec<f32>>> {
+			match extension.attributes.get(name) {
+				None => Ok(None),
+				Some(accessor) => Ok(Some(self.read_accessor_f32(*accessor)?)),
+			}
+		};
+
+		let (translations, rotations, scales) = (read("TRANSLATION")?, read("ROTATION")?, read("SCALE")?);
+
+		let count = self.node_instance_count(node)?;
+		Ok((0..count)
+			.map(|i| {
+				let t = translations.as_ref().map_or([0f32; 3], |x| [x[i * 3], x[i * 3 + 1], x[i * 3 + 2]]);
+				let r = rotations.as_ref().map_or(QUAT_IDENTITY, |x| [x[i * 4], x[i * 4 + 1], x[i * 4 + 2], x[i * 4 + 3]]);
+				let s = scales.as_ref().map_or([1f32; 3], |x| [x[i * 3], x[i * 3 + 1], x[i * 3 + 2]]);
+				(t, r, s)
+			})
+			.collect())
+	}
+
+	/// per-instance local transforms of an instanced node as column-major matrices, see [GltfObject::node_instance_transforms]
+	pub fn node_instance_matrices(&self, node: Index<GltfNode>) -> GltrResult<Vec<[f32; 16]>> {
+		Ok(self
+			.node_instance_transforms(node)?
+			.into_iter()
+			.map(|(t, r, s)| mat4_from_trs(t, r, s))
+			.collect())
+	}
+}
@@ -0,0 +1,171 @@
+use crate::defaults::*;
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::{GltfObject, GltrError, GltrResult};
+use serde_derive::{Deserialize, Serialize};
+
+/// KHR_lights_punctual as found in the root glTF extensions, defining the lights in the asset
+///
+/// https://github.com/KhronosGroup/glTF/tree/main/extensions/2.0/Khronos/KHR_lights_punctual
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct KhrLightsPunctual {
+	pub lights: Vec<KhrLight>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extensions: Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras: Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
+}
+
+/// KHR_lights_punctual as found in a node's extensions, instancing one of the root lights
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct KhrLightsPunctualNode {
+	pub light: Index<KhrLight>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extensions: Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras: Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct KhrLight {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub name: Option<String>,
+
+	#[serde(default = "default_f32_vec_1")]
+	pub color: [f32; 3],
+
+	/// candela for point and spot lights, lux for directional lights
+	#[serde(default = "default_1f")]
+	pub intensity: f32,
+
+	#[serde(rename = "type")]
+	pub light_type: KhrLightType,
+
+	/// distance cutoff, infinite when undefined. not allowed on directional lights
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub range: Option<f32>,
+
+	/// required when light_type is spot
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub spot: Option<KhrLightSpot>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extensions: Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras: Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KhrLightType {
+	Directional,
+	Point,
+	Spot,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct KhrLightSpot {
+	#[serde(default = "default_0f")]
+	pub inner_cone_angle: f32,
+
+	#[serde(default = "light_spot_default_outer_cone_angle")]
+	pub outer_cone_angle: f32,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extensions: Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras: Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
+}
+
+impl KhrLightsPunctualNode {
+	pub fn new(light: Index<KhrLight>) -> Self {
+		KhrLightsPunctualNode {
+			light,
+			extensions: None,
+			extras: None,
+			unknown_fields: UnknownFields::new(),
+		}
+	}
+}
+
+impl KhrLight {
+	/// a white light of intensity 1 without range, spot lights get the default cone
+	pub fn new(light_type: KhrLightType) -> Self {
+		KhrLight {
+			name: None,
+			color: default_f32_vec_1(),
+			intensity: default_1f(),
+			light_type,
+			range: None,
+			spot: (light_type == KhrLightType::Spot).then(KhrLightSpot::default),
+			extensions: None,
+			extras: None,
+			unknown_fields: UnknownFields::new(),
+		}
+	}
+}
+
+impl Default for KhrLightSpot {
+	fn default() -> Self {
+		KhrLightSpot {
+			inner_cone_angle: default_0f(),
+			outer_cone_angle: light_spot_default_outer_cone_angle(),
+			extensions: None,
+			extras: None,
+			unknown_fields: UnknownFields::new(),
+		}
+	}
+}
+
+impl GltfObject {
+	/// the lights defined in the root KHR_lights_punctual extension
+	pub fn lights(&self) -> &[KhrLight] {
+		match self.extension::<KhrLightsPunctual>() {
+			None => &[],
+			Some(l) => &l.lights,
+		}
+	}
+
+	/// adds a light to the root KHR_lights_punctual extension, returning its index
+	pub fn add_light(&mut self, light: KhrLight) -> Index<KhrLight> {
+		if self.extension::<KhrLightsPunctual>().is_none() {
+			self.set_extension(KhrLightsPunctual::default());
+		}
+
+		let lights = &mut self.extension_mut::<KhrLightsPunctual>().unwrap().lights;
+		lights.push(light);
+		Index::new(lights.len() - 1)
+	}
+
+	/// the light instanced by a node, if it has one
+	pub fn node_light(&self, node: Index<GltfNode>) -> GltrResult<Option<&KhrLight>> {
+		match self.try_get(node)?.extension::<KhrLightsPunctualNode>() {
+			None => Ok(None),
+			Some(l) => match self.lights().get(l.light.value()) {
+				None => Err(GltrError::invalid_index(
+					format!("/nodes/{node}/extensions/KHR_lights_punctual/light"),
+					l.light.value(),
+				)),
+				Some(light) => Ok(Some(light)),
+			},
+		}
+	}
+}
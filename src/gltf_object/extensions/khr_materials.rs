@@ -0,0 +1,121 @@
+use crate::defaults::*;
+use crate::gltf_object::prelude::*;
+use serde_derive::{Deserialize, Serialize};
+
+/// KHR_materials_emissive_strength
+///
+/// https://github.com/KhronosGroup/glTF/tree/main/extensions/2.0/Khronos/KHR_materials_emissive_strength
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct KhrMaterialsEmissiveStrength {
+	#[serde(default = "default_1f")]
+	pub emissive_strength: f32,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extensions: Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras: Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
+}
+
+/// KHR_materials_clearcoat
+///
+/// https://github.com/KhronosGroup/glTF/tree/main/extensions/2.0/Khronos/KHR_materials_clearcoat
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct KhrMaterialsClearcoat {
+	#[serde(default = "default_0f")]
+	pub clearcoat_factor: f32,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub clearcoat_texture: Option<GltfTextureInfo>,
+
+	#[serde(default = "default_0f")]
+	pub clearcoat_roughness_factor: f32,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub clearcoat_roughness_texture: Option<GltfTextureInfo>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub clearcoat_normal_texture: Option<GltfNormalTexture>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extensions: Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras: Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
+}
+
+/// KHR_materials_transmission
+///
+/// https://github.com/KhronosGroup/glTF/tree/main/extensions/2.0/Khronos/KHR_materials_transmission
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct KhrMaterialsTransmission {
+	#[serde(default = "default_0f")]
+	pub transmission_factor: f32,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub transmission_texture: Option<GltfTextureInfo>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extensions: Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras: Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
+}
+
+/// KHR_materials_ior
+///
+/// https://github.com/KhronosGroup/glTF/tree/main/extensions/2.0/Khronos/KHR_materials_ior
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct KhrMaterialsIor {
+	#[serde(default = "material_default_ior")]
+	pub ior: f32,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extensions: Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras: Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
+}
+
+/// KHR_materials_specular
+///
+/// https://github.com/KhronosGroup/glTF/tree/main/extensions/2.0/Khronos/KHR_materials_specular
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct KhrMaterialsSpecular {
+	#[serde(default = "default_1f")]
+	pub specular_factor: f32,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub specular_texture: Option<GltfTextureInfo>,
+
+	#[serde(default = "default_f32_vec_1")]
+	pub specular_color_factor: [f32; 3],
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub specular_color_texture: Option<GltfTextureInfo>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extensions: Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras: Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
+}
@@ -0,0 +1,458 @@
+use crate::buffers::BufferViewTarget;
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::references::untyped_tex_coords;
+use crate::gltf_object::{GltfObject, GltrError, GltrResult};
+use crate::math::*;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// KHR_mesh_quantization
+///
+/// this extension has no properties of its own, it only widens the accessor component types allowed for
+/// vertex attributes. it is only ever listed in extensionsUsed/extensionsRequired, the struct exists so it can be
+/// named in the registry like any other extension
+///
+/// https://github.com/KhronosGroup/glTF/tree/main/extensions/2.0/Khronos/KHR_mesh_quantization
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct KhrMeshQuantization {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extensions: Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras: Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
+}
+
+/// component types used by [GltfObject::quantize_meshes]
+#[derive(Debug, Clone, Copy)]
+pub struct QuantizeOptions {
+	/// BYTE or SHORT
+	pub position_type: ComponentType,
+
+	/// whether positions are stored normalized, the dequantization scale folded into the node transform differs
+	pub position_normalized: bool,
+
+	/// BYTE or SHORT, always normalized. also used for TANGENT
+	pub normal_type: ComponentType,
+
+	/// UNSIGNED_BYTE or UNSIGNED_SHORT, always normalized
+	pub texcoord_type: ComponentType,
+}
+
+impl Default for QuantizeOptions {
+	fn default() -> Self {
+		QuantizeOptions {
+			position_type: ComponentType::SHORT,
+			position_normalized: false,
+			normal_type: ComponentType::BYTE,
+			texcoord_type: ComponentType::UNSIGNED_SHORT,
+		}
+	}
+}
+
+fn attribute_accessor(primitive: &GltfMeshPrimitive, name: &str) -> Option<Index<GltfAccessor>> {
+	primitive.attributes.get(name).copied()
+}
+
+fn max_integer(component_type: ComponentType) -> f32 {
+	match component_type {
+		ComponentType::BYTE => 127f32,
+		ComponentType::UNSIGNED_BYTE => 255f32,
+		ComponentType::SHORT => 32767f32,
+		_ => 65535f32,
+	}
+}
+
+impl GltfObject {
+	/// quantizes vertex attributes using KHR_mesh_quantization.
+	///
+	/// POSITION is stored relative to the bounds of the mesh, the dequantization offset and scale are folded into
+	/// every node using the mesh (a node with children, an animated transform, a camera or a light gets a new child
+	/// node holding the mesh instead, EXT_mesh_gpu_instancing nodes get it in every instance). meshes that are unused,
+	/// skinned or have morph targets keep float positions.
+	/// NORMAL and TANGENT become normalized integers. TEXCOORD sets sampled by a material's textures are stored relative
+	/// to their bounds with the dequantization folded into each texture's KHR_texture_transform, other sets (and sets
+	/// also read by a texture of a material extension without a typed struct) are only quantized when they already
+	/// lie in [0, 1].
+	///
+	/// the replaced float accessors stay in the document until it is pruned
+	pub fn quantize_meshes(&mut self, options: QuantizeOptions) -> GltrResult<()> {
+		let valid = matches!(options.position_type, ComponentType::BYTE | ComponentType::SHORT)
+			&& matches!(options.normal_type, ComponentType::BYTE | ComponentType::SHORT)
+			&& matches!(options.texcoord_type, ComponentType::UNSIGNED_BYTE | ComponentType::UNSIGNED_SHORT);
+
+		if !valid {
+			return Err(GltrError::ConstraintViolation("unsupported component type in quantize options".to_string()));
+		}
+
+		// the instance transforms are rewritten, which needs them typed
+		for (node_idx, node) in self.nodes.iter().enumerate() {
+			if node.has_extension(ExtMeshGpuInstancing::NAME) && node.extension::<ExtMeshGpuInstancing>().is_none() {
+				return Err(GltrError::ConstraintViolation(format!(
+					"node {node_idx} has an EXT_mesh_gpu_instancing that does not match its schema"
+				)));
+			}
+		}
+
+		let mut uses_texture_transform = false;
+
+		for mesh_idx in 0..self.meshes.len() {
+			self.quantize_mesh_positions(mesh_idx, options)?;
+		}
+
+		// normals and tangents, shared accessors only need converting once
+		let mut converted: HashMap<Index<GltfAccessor>, Index<GltfAccessor>> = HashMap::new();
+		for mesh_idx in 0..self.meshes.len() {
+			for primitive_idx in 0..self.meshes[mesh_idx].primitives.len() {
+				for semantic in ["NORMAL", "TANGENT"] {
+					let primitive = &self.meshes[mesh_idx].primitives[primitive_idx];
+					let accessor = match attribute_accessor(primitive, semantic) {
+						None => continue,
+						Some(a) => a,
+					};
+
+					if self.get(accessor).is_none_or(|a| a.component_type != ComponentType::FLOAT) {
+						continue;
+					}
+
+					let new_idx = match converted.get(&accessor) {
+						Some(n) => *n,
+						None => {
+							let values = self.read_accessor_f32(accessor)?;
+							let accessor_type = self.accessors[accessor.value()].r#type.clone();
+							let n = self.push_accessor_f32(&values, &accessor_type, options.normal_type, true, Some(BufferViewTarget::ARRAY_BUFFER))?;
+							converted.insert(accessor, n);
+							n
+						}
+					};
+
+					self.meshes[mesh_idx].primitives[primitive_idx].attributes.insert(semantic.to_string(), new_idx);
+				}
+			}
+		}
+
+		for material_idx in 0..self.materials.len() {
+			uses_texture_transform |= self.quantize_material_texcoords(Some(material_idx), options)?;
+		}
+		self.quantize_material_texcoords(None, options)?;
+
+		let mut required = vec![KhrMeshQuantization::NAME];
+		if uses_texture_transform {
+			required.push(KhrTextureTransform::NAME);
+		}
+
+		for name in required {
+			if !self.extensions_required.iter().any(|x| x == name) {
+				self.extensions_required.push(name.to_string());
+			}
+		}
+
+		self.sync_extensions();
+
+		Ok(())
+	}
+
+	fn quantize_mesh_positions(&mut self, mesh_idx: usize, options: QuantizeOptions) -> GltrResult<()> {
+		let users: Vec<usize> = (0..self.nodes.len()).filter(|n| self.nodes[*n].mesh == Some(Index::new(mesh_idx))).collect();
+
+		// the transform of a skinned node is ignored, there is nowhere to fold the dequantization into
+		let mesh = &self.meshes[mesh_idx];
+		let skip = users.is_empty()
+			|| users.iter().any(|n| self.nodes[*n].skin.is_some())
+			|| mesh.primitives.iter().any(|p| p.targets.is_some());
+
+		if skip {
+			return Ok(());
+		}
+
+		let mut positions = Vec::new();
+		for (primitive_idx, primitive) in mesh.primitives.iter().enumerate() {
+			let accessor = match attribute_accessor(primitive, "POSITION") {
+				None => continue,
+				Some(a) => a,
+			};
+
+			match self.get(accessor) {
+				None => {
+					let path = format!("/meshes/{mesh_idx}/primitives/{primitive_idx}/attributes/POSITION");
+					return Err(GltrError::invalid_index(path, accessor.value()));
+				}
+				// already quantized
+				Some(a) if a.component_type != ComponentType::FLOAT => return Ok(()),
+				Some(_) => positions.push(accessor),
+			}
+		}
+
+		if positions.is_empty() {
+			return Ok(());
+		}
+
+		let mut min = [f32::MAX; 3];
+		let mut max = [f32::MIN; 3];
+		let mut values = HashMap::new();
+		for accessor in &positions {
+			if values.contains_key(accessor) {
+				continue;
+			}
+
+			let read = self.read_accessor_array::<3>(*accessor)?;
+			for p in &read {
+				min = crate::math::min(min, *p);
+				max = crate::math::max(max, *p);
+			}
+			values.insert(*accessor, read);
+		}
+
+		let center = scale(add(min, max), 0.5f32);
+		let half_extent = (0..3).map(|i| (max[i] - min[i]) * 0.5f32).fold(0f32, f32::max);
+		let half_extent = if half_extent > 0f32 { half_extent } else { 1f32 };
+
+		let max_int = max_integer(options.position_type);
+
+		// dequantized position = stored * node_scale + center
+		let node_scale = if options.position_normalized { half_extent } else { half_extent / max_int };
+
+		let mut quantized = HashMap::new();
+		for (accessor, read) in values {
+			let stored: Vec<f32> = read
+				.iter()
+				.flat_map(|p| {
+					let q = scale(sub(*p, center), 1f32 / half_extent);
+					if options.position_normalized { q } else { scale(q, max_int) }
+				})
+				.collect();
+
+			let new_idx = self.push_accessor_f32(&stored, "VEC3", options.position_type, options.position_normalized, Some(BufferViewTarget::ARRAY_BUFFER))?;
+			quantized.insert(accessor, new_idx);
+		}
+
+		for primitive in &mut self.meshes[mesh_idx].primitives {
+			if let Some(accessor) = attribute_accessor(primitive, "POSITION") {
+				primitive.attributes.insert("POSITION".to_string(), quantized[&accessor]);
+			}
+		}
+
+		let dequantize = mat4_from_trs(center, QUAT_IDENTITY, [node_scale; 3]);
+
+		let mut animated = vec![false; self.nodes.len()];
+		for channel in self.animations.iter().flat_map(|a| &a.channels) {
+			if let Some(a) = channel.target.node.and_then(|n| animated.get_mut(n.value())) {
+				*a |= channel.target.path != "weights";
+			}
+		}
+
+		for node_idx in users {
+			if let Some(instancing) = self.nodes[node_idx].extension::<ExtMeshGpuInstancing>() {
+				// the dequantization applies before the instance transform, so it is folded into every instance
+				let mut instancing = instancing.clone();
+				let (translations, scales): (Vec<[f32; 3]>, Vec<[f32; 3]>) = self
+					.node_instance_transforms(Index::new(node_idx))?
+					.into_iter()
+					.map(|(t, r, s)| (add(t, quat_rotate(r, [s[0] * center[0], s[1] * center[1], s[2] * center[2]])), scale(s, node_scale)))
+					.unzip();
+
+				instancing.attributes.insert("TRANSLATION".to_string(), self.push_accessor_array(&translations, None)?);
+				instancing.attributes.insert("SCALE".to_string(), self.push_accessor_array(&scales, None)?);
+				self.nodes[node_idx].set_extension(instancing);
+				continue;
+			}
+
+			let node = &mut self.nodes[node_idx];
+
+			let has_light = node.has_extension(KhrLightsPunctualNode::NAME);
+			if !node.child_indices().is_empty() || animated[node_idx] || node.camera.is_some() || has_light {
+				// the dequantization must not affect the children, cameras or lights, nor be overwritten by
+				// animations, so the mesh moves to a new child
+				let mesh_node = GltfNode {
+					mesh: node.mesh.take(),
+					name: node.name.clone(),
+					translation: Some(center),
+					scale: Some([node_scale; 3]),
+					..GltfNode::default()
+				};
+
+				self.nodes.push(mesh_node);
+				let new_idx = Index::new(self.nodes.len() - 1);
+				self.nodes[node_idx].children.get_or_insert_with(Vec::new).push(new_idx);
+			} else if let Some(matrix) = &node.matrix {
+				node.matrix = Some(mat4_mul(matrix, &dequantize));
+			} else {
+				let (t, r, s) = node.local_trs();
+				let translation = add(t, quat_rotate(r, [s[0] * center[0], s[1] * center[1], s[2] * center[2]]));
+				node.set_local_trs(translation, r, scale(s, node_scale));
+			}
+		}
+
+		Ok(())
+	}
+
+	/// quantizes the TEXCOORD sets of primitives using the material (or using no material when None).
+	/// returns true if a KHR_texture_transform was added or modified
+	fn quantize_material_texcoords(&mut self, material_idx: Option<usize>, options: QuantizeOptions) -> GltrResult<bool> {
+		// sets sampled by the material's textures, these can be remapped through a texture transform
+		let mut sampled: BTreeSet<usize> = BTreeSet::new();
+		// sets also read by textures of untyped material extensions, which would be left without the remapping
+		let mut untyped: BTreeSet<usize> = BTreeSet::new();
+		if let Some(idx) = material_idx {
+			for slot in self.materials[idx].texture_slots() {
+				let set = slot.extension::<KhrTextureTransform>().and_then(|t| t.tex_coord).unwrap_or(slot.tex_coord);
+				sampled.insert(set);
+			}
+			untyped = untyped_tex_coords(&self.materials[idx]);
+		}
+
+		let primitives: Vec<(usize, usize)> = self
+			.meshes
+			.iter()
+			.enumerate()
+			.flat_map(|(m, mesh)| {
+				mesh.primitives
+					.iter()
+					.enumerate()
+					.filter(|(_, p)| p.material == material_idx.map(Index::new))
+					.map(move |(p, _)| (m, p))
+			})
+			.collect();
+
+		// every TEXCOORD set present on those primitives
+		let mut sets: BTreeMap<usize, Vec<Index<GltfAccessor>>> = BTreeMap::new();
+		for (m, p) in &primitives {
+			for (name, accessor) in &self.meshes[*m].primitives[*p].attributes {
+				if let Some(set) = name.strip_prefix("TEXCOORD_").and_then(|s| s.parse::<usize>().ok()) {
+					sets.entry(set).or_default().push(*accessor);
+				}
+			}
+		}
+
+		let mut changed_transform = false;
+
+		for (set, accessors) in sets {
+			let float_accessors: Vec<Index<GltfAccessor>> = accessors
+				.iter()
+				.copied()
+				.filter(|a| self.get(*a).is_some_and(|x| x.component_type == ComponentType::FLOAT))
+				.collect();
+
+			// a set that is partially quantized already would need two different mappings
+			if float_accessors.len() != accessors.len() || float_accessors.is_empty() {
+				continue;
+			}
+
+			let mut min = [f32::MAX; 2];
+			let mut max = [f32::MIN; 2];
+			let mut values = HashMap::new();
+			for accessor in &float_accessors {
+				if values.contains_key(accessor) {
+					continue;
+				}
+				let read = self.read_accessor_array::<2>(*accessor)?;
+				for uv in &read {
+					for i in 0..2 {
+						min[i] = min[i].min(uv[i]);
+						max[i] = max[i].max(uv[i]);
+					}
+				}
+				values.insert(*accessor, read);
+			}
+
+			let in_unit_range = min[0] >= 0f32 && min[1] >= 0f32 && max[0] <= 1f32 && max[1] <= 1f32;
+
+			let (offset, extent) = if in_unit_range {
+				([0f32, 0f32], [1f32, 1f32])
+			} else if sampled.contains(&set) && !untyped.contains(&set) {
+				let extent = [(max[0] - min[0]).max(f32::EPSILON), (max[1] - min[1]).max(f32::EPSILON)];
+				(min, extent)
+			} else {
+				continue;
+			};
+
+			let mut quantized = HashMap::new();
+			for (accessor, read) in values {
+				let stored: Vec<f32> = read
+					.iter()
+					.flat_map(|uv| [(uv[0] - offset[0]) / extent[0], (uv[1] - offset[1]) / extent[1]])
+					.collect();
+
+				let new_idx = self.push_accessor_f32(&stored, "VEC2", options.texcoord_type, true, Some(BufferViewTarget::ARRAY_BUFFER))?;
+				quantized.insert(accessor, new_idx);
+			}
+
+			let attribute = format!("TEXCOORD_{set}");
+			for (m, p) in &primitives {
+				let primitive = &mut self.meshes[*m].primitives[*p];
+				if let Some(accessor) = attribute_accessor(primitive, &attribute) {
+					primitive.attributes.insert(attribute.clone(), quantized[&accessor]);
+				}
+			}
+
+			if in_unit_range {
+				continue;
+			}
+
+			if let Some(idx) = material_idx {
+				for mut slot in self.materials[idx].texture_slots_mut() {
+					let existing = slot.extension::<KhrTextureTransform>().cloned();
+					let slot_set = existing.as_ref().and_then(|t| t.tex_coord).unwrap_or(*slot.tex_coord);
+					if slot_set != set {
+						continue;
+					}
+
+					let transform = existing.unwrap_or_default().pre_transform(extent, offset);
+					slot.set_extension(transform);
+					changed_transform = true;
+				}
+			}
+		}
+
+		Ok(changed_transform)
+	}
+
+	/// converts quantized POSITION, NORMAL, TANGENT and TEXCOORD attributes back to FLOAT.
+	///
+	/// the dequantization transforms folded into nodes and KHR_texture_transform by [GltfObject::quantize_meshes] are
+	/// kept, they remain correct for the float data
+	pub fn dequantize_meshes(&mut self) -> GltrResult<()> {
+		let mut converted: HashMap<Index<GltfAccessor>, Index<GltfAccessor>> = HashMap::new();
+
+		for mesh_idx in 0..self.meshes.len() {
+			for primitive_idx in 0..self.meshes[mesh_idx].primitives.len() {
+				let attributes: Vec<(String, Index<GltfAccessor>)> = self.meshes[mesh_idx].primitives[primitive_idx]
+					.attributes
+					.iter()
+					.filter(|(k, _)| matches!(k.as_str(), "POSITION" | "NORMAL" | "TANGENT") || k.starts_with("TEXCOORD_"))
+					.map(|(k, v)| (k.clone(), *v))
+					.collect();
+
+				for (semantic, accessor) in attributes {
+					match self.get(accessor) {
+						None => {
+							let path = format!("/meshes/{mesh_idx}/primitives/{primitive_idx}/attributes/{semantic}");
+							return Err(GltrError::invalid_index(path, accessor.value()));
+						}
+						Some(a) if a.component_type == ComponentType::FLOAT => continue,
+						Some(_) => {}
+					}
+
+					let new_idx = match converted.get(&accessor) {
+						Some(n) => *n,
+						None => {
+							let values = self.read_accessor_f32(accessor)?;
+							let accessor_type = self.accessors[accessor.value()].r#type.clone();
+							let n = self.push_accessor_f32(&values, &accessor_type, ComponentType::FLOAT, false, Some(BufferViewTarget::ARRAY_BUFFER))?;
+							converted.insert(accessor, n);
+							n
+						}
+					};
+
+					self.meshes[mesh_idx].primitives[primitive_idx].attributes.insert(semantic.to_string(), new_idx);
+				}
+			}
+		}
+
+		self.sync_extensions();
+
+		Ok(())
+	}
+}
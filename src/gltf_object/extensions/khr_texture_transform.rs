@@ -0,0 +1,213 @@
+use crate::buffers::BufferViewTarget;
+use crate::defaults::*;
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::references::untyped_tex_coords;
+use crate::gltf_object::{GltfObject, GltrError, GltrResult};
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use serde_derive::{Deserialize, Serialize};
+
+/// KHR_texture_transform, found in the extensions of a textureInfo
+///
+/// https://github.com/KhronosGroup/glTF/tree/main/extensions/2.0/Khronos/KHR_texture_transform
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct KhrTextureTransform {
+	#[serde(default = "default_f32_vec2_0")]
+	pub offset: [f32; 2],
+
+	/// rotation in radians, counter-clockwise in UV space
+	#[serde(default = "default_0f")]
+	pub rotation: f32,
+
+	#[serde(default = "default_f32_vec2_1")]
+	pub scale: [f32; 2],
+
+	/// overrides the textureInfo's texCoord when present
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub tex_coord: Option<usize>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extensions: Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras: Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
+}
+
+impl Default for KhrTextureTransform {
+	fn default() -> Self {
+		KhrTextureTransform {
+			offset: default_f32_vec2_0(),
+			rotation: 0f32,
+			scale: default_f32_vec2_1(),
+			tex_coord: None,
+			extensions: None,
+			extras: None,
+			unknown_fields: UnknownFields::new(),
+		}
+	}
+}
+
+impl KhrTextureTransform {
+	/// the row-major 3x3 matrix applied to UVs, `translation * rotation * scale`
+	///
+	/// a positive rotation turns UVs counter-clockwise as seen in image space (where v points down)
+	pub fn matrix(&self) -> [[f32; 3]; 3] {
+		let (s, c) = self.rotation.sin_cos();
+		let [sx, sy] = self.scale;
+		let [ox, oy] = self.offset;
+
+		[
+			[c * sx, s * sy, ox],
+			[-s * sx, c * sy, oy],
+			[0f32, 0f32, 1f32],
+		]
+	}
+
+	pub fn transform_uv(&self, uv: [f32; 2]) -> [f32; 2] {
+		let m = self.matrix();
+		[
+			m[0][0] * uv[0] + m[0][1] * uv[1] + m[0][2],
+			m[1][0] * uv[0] + m[1][1] * uv[1] + m[1][2],
+		]
+	}
+
+	/// true when the transform does not change UVs (texCoord overrides are not considered)
+	pub fn is_identity(&self) -> bool {
+		self.offset == [0f32, 0f32] && self.rotation == 0f32 && self.scale == [1f32, 1f32]
+	}
+
+	/// the transform equivalent to first mapping UVs with `uv * scale + offset`, then applying self.
+	/// used to fold a dequantization into an existing transform
+	pub fn pre_transform(&self, scale: [f32; 2], offset: [f32; 2]) -> KhrTextureTransform {
+		KhrTextureTransform {
+			offset: self.transform_uv(offset),
+			rotation: self.rotation,
+			scale: [self.scale[0] * scale[0], self.scale[1] * scale[1]],
+			..self.clone()
+		}
+	}
+
+	fn same_transform(&self, other: &KhrTextureTransform) -> bool {
+		let (a, b) = (self.matrix(), other.matrix());
+		a.iter().flatten().zip(b.iter().flatten()).all(|(x, y)| (x - y).abs() <= 1e-6)
+	}
+}
+
+impl GltfObject {
+	/// bakes the KHR_texture_transform of every material into texture coordinates, see [GltfObject::bake_texture_transform]
+	pub fn bake_texture_transforms(&mut self) -> GltrResult<()> {
+		for material in self.indices::<GltfMaterial>().collect::<Vec<_>>() {
+			self.bake_texture_transform(material)?;
+		}
+		Ok(())
+	}
+
+	/// applies the KHR_texture_transform of the material's textures to the TEXCOORD accessors of every primitive
+	/// using the material, then removes the extension from the material.
+	///
+	/// the transformed coordinates are written to new accessors, so accessors shared with primitives that use other
+	/// materials are left unchanged. fails without modifying anything if two textures read the same texCoord set with
+	/// different transforms, as a single accessor cannot hold both, if a transform does not match its schema, or if
+	/// a baked set is also read by a texture of a material extension without a typed struct, eg KHR_materials_sheen
+	pub fn bake_texture_transform(&mut self, material: Index<GltfMaterial>) -> GltrResult<()> {
+		let slots = self.try_get(material)?.texture_slots();
+
+		// texCoord set -> transform to bake into it
+		let mut transforms: BTreeMap<usize, KhrTextureTransform> = BTreeMap::new();
+		let mut has_transform = false;
+
+		for slot in slots {
+			if slot.has_extension(KhrTextureTransform::NAME) && slot.extension::<KhrTextureTransform>().is_none() {
+				return Err(GltrError::ConstraintViolation(format!(
+					"material {material} has a KHR_texture_transform that does not match its schema"
+				)));
+			}
+
+			let transform = slot.extension::<KhrTextureTransform>().cloned().unwrap_or_default();
+			has_transform |= slot.has_extension(KhrTextureTransform::NAME);
+
+			let set = transform.tex_coord.unwrap_or(slot.tex_coord);
+
+			match transforms.get(&set) {
+				Some(existing) if !existing.same_transform(&transform) => {
+					return Err(GltrError::ConstraintViolation(format!(
+						"material {material} uses TEXCOORD_{set} with different texture transforms"
+					)));
+				}
+				Some(_) => {}
+				None => {
+					transforms.insert(set, transform);
+				}
+			}
+		}
+
+		if !has_transform {
+			return Ok(());
+		}
+
+		transforms.retain(|_, t| !t.is_identity());
+
+		// the textures of the untyped material extensions have no transform to remove, baking would move them too
+		if let Some(set) = untyped_tex_coords(&self.materials[material.value()]).into_iter().find(|s| transforms.contains_key(s)) {
+			return Err(GltrError::ConstraintViolation(format!(
+				"material {material} has an extension texture reading TEXCOORD_{set}, which would be baked with the transform"
+			)));
+		}
+
+		// (texCoord set, original accessor) -> transformed coordinates, read before anything is modified
+		let mut baked: BTreeMap<(usize, Index<GltfAccessor>), Vec<[f32; 2]>> = BTreeMap::new();
+		// (mesh, primitive, texCoord set, original accessor) for every attribute to point at a transformed accessor
+		let mut rewrites = vec![];
+
+		for mesh_idx in 0..self.meshes.len() {
+			for primitive_idx in 0..self.meshes[mesh_idx].primitives.len() {
+				let primitive = &self.meshes[mesh_idx].primitives[primitive_idx];
+				if primitive.material != Some(material) {
+					continue;
+				}
+
+				for (set, transform) in &transforms {
+					let accessor_idx = match primitive.attributes.get(&format!("TEXCOORD_{set}")) {
+						None => continue,
+						Some(v) => *v,
+					};
+
+					if let Entry::Vacant(entry) = baked.entry((*set, accessor_idx)) {
+						let uvs = self.read_accessor_array::<2>(accessor_idx)?;
+						entry.insert(uvs.into_iter().map(|uv| transform.transform_uv(uv)).collect());
+					}
+
+					rewrites.push((mesh_idx, primitive_idx, *set, accessor_idx));
+				}
+			}
+		}
+
+		// only the first push can fail (when buffer 0 isn't a data URI), so nothing is left half rewritten
+		let mut pushed: HashMap<(usize, Index<GltfAccessor>), Index<GltfAccessor>> = HashMap::new();
+		for (key, uvs) in baked {
+			pushed.insert(key, self.push_accessor_array(&uvs, Some(BufferViewTarget::ARRAY_BUFFER))?);
+		}
+
+		for (mesh_idx, primitive_idx, set, accessor_idx) in rewrites {
+			self.meshes[mesh_idx].primitives[primitive_idx]
+				.attributes
+				.insert(format!("TEXCOORD_{set}"), pushed[&(set, accessor_idx)]);
+		}
+
+		for mut slot in self.materials[material.value()].texture_slots_mut() {
+			if let Some(GltfExtension::KhrTextureTransform(t)) = slot.remove_extension(KhrTextureTransform::NAME) {
+				if let Some(set) = t.tex_coord {
+					*slot.tex_coord = set;
+				}
+			}
+		}
+
+		self.sync_extensions();
+
+		Ok(())
+	}
+}
@@ -0,0 +1,265 @@
+mod ext_mesh_gpu_instancing;
+mod khr_lights_punctual;
+mod khr_materials;
+mod khr_mesh_quantization;
+mod khr_texture_transform;
+mod msft_lod;
+mod usage;
+
+pub use ext_mesh_gpu_instancing::*;
+pub use khr_lights_punctual::*;
+pub use khr_materials::*;
+pub use khr_mesh_quantization::*;
+pub use khr_texture_transform::*;
+pub use msft_lod::*;
+
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::GltfObject;
+use serde::de::DeserializeOwned;
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet};
+use usage::{collect_extension_names, ExtensionNames};
+
+/// A typed extension struct that can be stored in a [GltfExtensions] map.
+///
+/// implemented for every extension known to the registry in this module; anything else is kept as
+/// [GltfExtension::Unknown] and can be read back with [GltfExtensions::get_raw]
+pub trait GltfExtensionType: Serialize + DeserializeOwned + Sized {
+	/// the key used for this extension in an `extensions` object, eg "KHR_materials_ior"
+	const NAME: &'static str;
+
+	fn from_extension(extension: &GltfExtension) -> Option<&Self>;
+
+	fn from_extension_mut(extension: &mut GltfExtension) -> Option<&mut Self>;
+
+	fn into_extension(self) -> GltfExtension;
+}
+
+macro_rules! known_extensions {
+	($($variant:ident => $name:literal),* $(,)?) => {
+		/// The value stored against a single key of an `extensions` object
+		///
+		/// known extensions are deserialized into their typed struct, any unrecognised extension (or a known
+		/// extension whose content does not match its schema) is kept as raw JSON. properties a typed struct does
+		/// not model are kept in its `unknown_fields`
+		#[derive(Debug, Clone, PartialEq)]
+		pub enum GltfExtension {
+			$($variant($variant),)*
+			Unknown(serde_json::Value),
+		}
+
+		$(
+			impl GltfExtensionType for $variant {
+				const NAME: &'static str = $name;
+
+				fn from_extension(extension: &GltfExtension) -> Option<&Self> {
+					match extension {
+						GltfExtension::$variant(v) => Some(v),
+						_ => None,
+					}
+				}
+
+				fn from_extension_mut(extension: &mut GltfExtension) -> Option<&mut Self> {
+					match extension {
+						GltfExtension::$variant(v) => Some(v),
+						_ => None,
+					}
+				}
+
+				fn into_extension(self) -> GltfExtension {
+					GltfExtension::$variant(self)
+				}
+			}
+		)*
+
+		impl GltfExtension {
+			/// parses `value` as the typed struct registered for `name`.
+			///
+			/// several structs may share a name (eg the root and node forms of KHR_lights_punctual), they are
+			/// tried in registration order and the first that deserializes is used
+			pub fn parse(name: &str, value: serde_json::Value) -> Self {
+				$(
+					if name == $name {
+						if let Ok(v) = serde_json::from_value::<$variant>(value.clone()) {
+							return GltfExtension::$variant(v);
+						}
+					}
+				)*
+				GltfExtension::Unknown(value)
+			}
+
+			pub fn is_known(&self) -> bool {
+				!matches!(self, GltfExtension::Unknown(_))
+			}
+
+			pub fn to_value(&self) -> serde_json::Value {
+				match self {
+					$(GltfExtension::$variant(v) => serde_json::to_value(v).unwrap_or(serde_json::Value::Null),)*
+					GltfExtension::Unknown(v) => v.clone(),
+				}
+			}
+		}
+
+		impl ExtensionNames for GltfExtension {
+			fn add_extension_names(&self, names: &mut BTreeSet<String>) {
+				match self {
+					$(GltfExtension::$variant(v) => v.add_extension_names(names),)*
+					GltfExtension::Unknown(v) => collect_extension_names(v, names),
+				}
+			}
+		}
+
+		impl Serialize for GltfExtension {
+			fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+				match self {
+					$(GltfExtension::$variant(v) => v.serialize(serializer),)*
+					GltfExtension::Unknown(v) => v.serialize(serializer),
+				}
+			}
+		}
+	};
+}
+
+known_extensions! {
+	KhrMaterialsEmissiveStrength => "KHR_materials_emissive_strength",
+	KhrMaterialsClearcoat => "KHR_materials_clearcoat",
+	KhrMaterialsTransmission => "KHR_materials_transmission",
+	KhrMaterialsIor => "KHR_materials_ior",
+	KhrMaterialsSpecular => "KHR_materials_specular",
+	KhrTextureTransform => "KHR_texture_transform",
+	KhrLightsPunctual => "KHR_lights_punctual",
+	KhrLightsPunctualNode => "KHR_lights_punctual",
+	KhrMeshQuantization => "KHR_mesh_quantization",
+	ExtMeshGpuInstancing => "EXT_mesh_gpu_instancing",
+	MsftLod => "MSFT_lod",
+}
+
+/// The content of an `extensions` property, keyed by extension name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GltfExtensions(pub BTreeMap<String, GltfExtension>);
+
+impl GltfExtensions {
+	pub fn new() -> Self {
+		GltfExtensions(BTreeMap::new())
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+
+	pub fn names(&self) -> impl Iterator<Item = &str> {
+		self.0.keys().map(|x| x.as_str())
+	}
+
+	pub fn contains(&self, name: &str) -> bool {
+		self.0.contains_key(name)
+	}
+
+	pub fn get<T: GltfExtensionType>(&self) -> Option<&T> {
+		T::from_extension(self.0.get(T::NAME)?)
+	}
+
+	pub fn get_mut<T: GltfExtensionType>(&mut self) -> Option<&mut T> {
+		T::from_extension_mut(self.0.get_mut(T::NAME)?)
+	}
+
+	pub fn get_raw(&self, name: &str) -> Option<&GltfExtension> {
+		self.0.get(name)
+	}
+
+	/// inserts a typed extension, returning whatever was previously stored under its name
+	pub fn insert<T: GltfExtensionType>(&mut self, value: T) -> Option<GltfExtension> {
+		self.0.insert(T::NAME.to_string(), value.into_extension())
+	}
+
+	/// inserts raw JSON under `name`. if `name` is a known extension, the value is parsed into its typed form
+	pub fn insert_raw(&mut self, name: &str, value: serde_json::Value) -> Option<GltfExtension> {
+		self.0.insert(name.to_string(), GltfExtension::parse(name, value))
+	}
+
+	pub fn remove(&mut self, name: &str) -> Option<GltfExtension> {
+		self.0.remove(name)
+	}
+}
+
+impl Serialize for GltfExtensions {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let mut map = serializer.serialize_map(Some(self.0.len()))?;
+		for (k, v) in &self.0 {
+			map.serialize_entry(k, v)?;
+		}
+		map.end()
+	}
+}
+
+impl<'de> Deserialize<'de> for GltfExtensions {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let raw = BTreeMap::<String, serde_json::Value>::deserialize(deserializer)?;
+
+		let map = raw
+			.into_iter()
+			.map(|(k, v)| {
+				let parsed = GltfExtension::parse(&k, v);
+				(k, parsed)
+			})
+			.collect();
+
+		Ok(GltfExtensions(map))
+	}
+}
+
+/// Typed access to the `extensions` property of a glTF object
+pub trait Extensible {
+	fn extensions(&self) -> &Extensions;
+
+	fn extensions_mut(&mut self) -> &mut Extensions;
+
+	fn extension<T: GltfExtensionType>(&self) -> Option<&T> {
+		self.extensions().as_ref()?.get::<T>()
+	}
+
+	fn extension_mut<T: GltfExtensionType>(&mut self) -> Option<&mut T> {
+		self.extensions_mut().as_mut()?.get_mut::<T>()
+	}
+
+	fn has_extension(&self, name: &str) -> bool {
+		self.extensions().as_ref().is_some_and(|x| x.contains(name))
+	}
+
+	fn set_extension<T: GltfExtensionType>(&mut self, value: T) -> Option<GltfExtension> {
+		self.extensions_mut()
+			.get_or_insert_with(GltfExtensions::new)
+			.insert(value)
+	}
+
+	/// removes the extension with the given name. the `extensions` property is reset to None when it becomes empty
+	fn remove_extension(&mut self, name: &str) -> Option<GltfExtension> {
+		let extensions = self.extensions_mut();
+		let removed = extensions.as_mut()?.remove(name);
+
+		if extensions.as_ref().is_some_and(|x| x.is_empty()) {
+			*extensions = None;
+		}
+
+		removed
+	}
+}
+
+macro_rules! impl_extensible {
+	($($t:ty),* $(,)?) => {
+		$(
+			impl Extensible for $t {
+				fn extensions(&self) -> &Extensions {
+					&self.extensions
+				}
+
+				fn extensions_mut(&mut self) -> &mut Extensions {
+					&mut self.extensions
+				}
+			}
+		)*
+	};
+}
+
+impl_extensible!(GltfObject, GltfMaterial, GltfTextureInfo, GltfNormalTexture, GltfNode);
@@ -0,0 +1,107 @@
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::{GltfObject, GltrError, GltrResult};
+use serde_derive::{Deserialize, Serialize};
+
+/// the key of the node extras holding the screen coverage of every level of detail
+pub const MSFT_SCREEN_COVERAGE: &str = "MSFT_screencoverage";
+
+/// MSFT_lod, found in the extensions of a node with a mesh. the nodes in `ids`, from most to least detailed,
+/// replace the node as it covers less of the screen, they are not part of the node hierarchy themselves
+///
+/// https://github.com/KhronosGroup/glTF/tree/main/extensions/2.0/Vendor/MSFT_lod
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct MsftLod {
+	pub ids: Vec<Index<GltfNode>>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extensions: Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras: Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
+}
+
+/// One level of detail generated by [GltfObject::generate_lods]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LodLevel {
+	pub simplify: SimplifyOptions,
+
+	/// the smallest fraction of the screen height the node covers for this level to be drawn, below it the next
+	/// level is, or nothing for the last one
+	pub screen_coverage: f32,
+}
+
+/// Where [GltfObject::generate_lods] puts the simplified meshes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LodOutput {
+	/// a node per level listed in the MSFT_lod extension of the node, with the screen coverage of the node and of
+	/// every level in its extras
+	MsftLod {
+		/// the smallest screen coverage for which the node itself is drawn
+		screen_coverage: f32,
+	},
+
+	/// only the meshes, for the caller to place
+	Meshes,
+}
+
+impl GltfObject {
+	/// simplifies the mesh of `node` once per level, each level starting again from the full mesh, see
+	/// [GltfObject::simplify_mesh]. returns the new meshes, from most to least detailed.
+	///
+	/// with [LodOutput::MsftLod] the level nodes get the transform and skin of `node`, any MSFT_lod the node already
+	/// had is replaced
+	pub fn generate_lods(&mut self, node: Index<GltfNode>, levels: &[LodLevel], output: LodOutput) -> GltrResult<Vec<Index<GltfMesh>>> {
+		let mesh = match self.try_get(node)?.mesh {
+			None => {
+				return Err(GltrError::ConstraintViolation(format!("node {node} has no mesh to generate levels of detail for")));
+			}
+			Some(mesh) => mesh,
+		};
+		let name = self.try_get(mesh)?.name.clone();
+
+		let mut meshes = Vec::with_capacity(levels.len());
+		for (i, level) in levels.iter().enumerate() {
+			let (lod, _) = self.simplify_mesh(mesh, level.simplify)?;
+			self.meshes[lod.value()].name = name.as_ref().map(|n| format!("{n}_LOD{}", i + 1));
+			meshes.push(lod);
+		}
+
+		let screen_coverage = match output {
+			LodOutput::Meshes => return Ok(meshes),
+			LodOutput::MsftLod { screen_coverage } => screen_coverage,
+		};
+
+		let base = self.try_get(node)?.clone();
+		let mut ids = Vec::with_capacity(meshes.len());
+		for (i, lod) in meshes.iter().enumerate() {
+			ids.push(self.push(GltfNode {
+				name: base.name.as_ref().map(|n| format!("{n}_LOD{}", i + 1)),
+				mesh: Some(*lod),
+				skin: base.skin,
+				translation: base.translation,
+				rotation: base.rotation,
+				scale: base.scale,
+				matrix: base.matrix,
+				..GltfNode::default()
+			}));
+		}
+
+		let coverage: Vec<f32> = std::iter::once(screen_coverage).chain(levels.iter().map(|l| l.screen_coverage)).collect();
+
+		let n = &mut self.nodes[node.value()];
+		n.set_extension(MsftLod { ids, ..MsftLod::default() });
+		match &mut n.extras {
+			Some(serde_json::Value::Object(extras)) => {
+				extras.insert(MSFT_SCREEN_COVERAGE.to_string(), coverage.into());
+			}
+			extras => *extras = Some(serde_json::json!({ MSFT_SCREEN_COVERAGE: coverage })),
+		}
+
+		self.sync_extensions();
+
+		Ok(meshes)
+	}
+}
@@ -0,0 +1,222 @@
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::{GltfObject, GltfSkin};
+use std::collections::BTreeSet;
+
+impl GltfObject {
+	/// names of every extension that appears in an `extensions` object anywhere in the document,
+	/// plus extensions that are only detectable by how the asset uses core properties (KHR_mesh_quantization)
+	pub fn collect_extension_names(&self) -> BTreeSet<String> {
+		let mut names = BTreeSet::new();
+		self.add_extension_names(&mut names);
+
+		if self.uses_mesh_quantization() {
+			names.insert(KhrMeshQuantization::NAME.to_string());
+		}
+
+		names
+	}
+
+	/// serializes the object to a JSON value, without modifying self.
+	///
+	/// like every serialization of GltfObject, extensionsUsed and extensionsRequired are written as
+	/// [GltfObject::sync_extensions] would leave them
+	pub fn to_json_value(&self) -> serde_json::Result<serde_json::Value> {
+		serde_json::to_value(self)
+	}
+
+	/// extensions_used as [GltfObject::sync_extensions] would leave it, this is what serialization writes
+	pub(crate) fn synced_extensions_used(&self) -> Vec<String> {
+		let mut used = self.extensions_used.clone();
+		sync_extension_lists(&mut used, &mut vec![], &self.collect_extension_names());
+		used
+	}
+
+	/// see [GltfObject::synced_extensions_used]
+	pub(crate) fn synced_extensions_required(&self) -> Vec<String> {
+		let names = self.collect_extension_names();
+		self.extensions_required.iter().filter(|x| names.contains(*x)).cloned().collect()
+	}
+
+	/// recomputes extensions_used from the extensions present in the document.
+	///
+	/// existing entries keep their order and newly found extensions are appended, extensions_required
+	/// only keeps the entries that are still in use
+	pub fn sync_extensions(&mut self) {
+		let names = self.collect_extension_names();
+		sync_extension_lists(&mut self.extensions_used, &mut self.extensions_required, &names);
+	}
+
+	/// true when any vertex attribute uses a component type that core glTF does not allow for it
+	pub fn uses_mesh_quantization(&self) -> bool {
+		for mesh in &self.meshes {
+			for primitive in &mesh.primitives {
+				for (semantic, accessor) in &primitive.attributes {
+					if let Some(accessor) = self.get(*accessor) {
+						if !core_allows_attribute_type(semantic, accessor) {
+							return true;
+						}
+					}
+				}
+			}
+		}
+
+		false
+	}
+}
+
+fn sync_extension_lists(used: &mut Vec<String>, required: &mut Vec<String>, names: &BTreeSet<String>) {
+	used.retain(|x| names.contains(x));
+	for name in names {
+		if !used.contains(name) {
+			used.push(name.clone());
+		}
+	}
+
+	required.retain(|x| names.contains(x));
+}
+
+/// a glTF property that can hold `extensions` objects, in itself or in the properties it contains.
+///
+/// walking the typed properties avoids serializing the document (base64 buffers included) on every sync
+pub(super) trait ExtensionNames {
+	fn add_extension_names(&self, names: &mut BTreeSet<String>);
+}
+
+impl<T: ExtensionNames> ExtensionNames for Option<T> {
+	fn add_extension_names(&self, names: &mut BTreeSet<String>) {
+		if let Some(value) = self {
+			value.add_extension_names(names);
+		}
+	}
+}
+
+impl<T: ExtensionNames> ExtensionNames for Vec<T> {
+	fn add_extension_names(&self, names: &mut BTreeSet<String>) {
+		for value in self {
+			value.add_extension_names(names);
+		}
+	}
+}
+
+impl ExtensionNames for GltfExtensions {
+	fn add_extension_names(&self, names: &mut BTreeSet<String>) {
+		for (name, extension) in &self.0 {
+			names.insert(name.clone());
+			extension.add_extension_names(names);
+		}
+	}
+}
+
+impl ExtensionNames for gltf_buffers::GltfBuffers {
+	fn add_extension_names(&self, names: &mut BTreeSet<String>) {
+		self.0.add_extension_names(names);
+	}
+}
+
+/// implements [ExtensionNames] for properties with `extensions`, `extras` and `unknown_fields`, walking the listed
+/// fields that hold other properties
+macro_rules! extension_names {
+	($($t:ty => [$($field:ident),*]),* $(,)?) => {
+		$(
+			impl ExtensionNames for $t {
+				fn add_extension_names(&self, names: &mut BTreeSet<String>) {
+					self.extensions.add_extension_names(names);
+					$(self.$field.add_extension_names(names);)*
+
+					// unmodelled properties can hold extensions too, eg a texture of an untyped material extension
+					for value in self.unknown_fields.values() {
+						collect_extension_names(value, names);
+					}
+				}
+			}
+		)*
+	};
+}
+
+extension_names! {
+	GltfObject => [accessors, animations, asset, buffers, buffer_views, cameras, images, materials, meshes, nodes, samplers, scenes, skins, textures],
+	GltfAccessor => [sparse],
+	GltfAccessorSparse => [indices, values],
+	GltfAccessorSparseIndices => [],
+	GltfAccessorSparseValues => [],
+	GltfAnimation => [channels, samplers],
+	GltfAnimationChannel => [target],
+	GltfAnimationChannelTarget => [],
+	GltfAnimationSampler => [],
+	GltfAsset => [],
+	gltf_buffers::GltfBase64Buffer => [],
+	gltf_buffers::GltfBufferView => [],
+	GltfCamera => [orthographic, perspective],
+	GltfCameraOrthographic => [],
+	GltfCameraPerspective => [],
+	GltfImage => [],
+	GltfMaterial => [pbr_metallic_roughness, normal_texture, occlusion_texture, emissive_texture],
+	GltfMetallicRoughness => [base_color_texture, metallic_roughness_texture],
+	GltfTextureInfo => [],
+	GltfNormalTexture => [],
+	GltfOcclusionTexture => [],
+	GltfMesh => [primitives],
+	GltfMeshPrimitive => [],
+	GltfNode => [],
+	GltfSampler => [],
+	GltfScene => [],
+	GltfSkin => [],
+	GltfTexture => [],
+	KhrMaterialsEmissiveStrength => [],
+	KhrMaterialsClearcoat => [clearcoat_texture, clearcoat_roughness_texture, clearcoat_normal_texture],
+	KhrMaterialsTransmission => [transmission_texture],
+	KhrMaterialsIor => [],
+	KhrMaterialsSpecular => [specular_texture, specular_color_texture],
+	KhrTextureTransform => [],
+	KhrLightsPunctual => [lights],
+	KhrLightsPunctualNode => [],
+	KhrLight => [spot],
+	KhrLightSpot => [],
+	KhrMeshQuantization => [],
+	ExtMeshGpuInstancing => [],
+	MsftLod => [],
+}
+
+pub(super) fn collect_extension_names(value: &serde_json::Value, names: &mut BTreeSet<String>) {
+	match value {
+		serde_json::Value::Object(map) => {
+			for (k, v) in map {
+				match k.as_str() {
+					// extras are application specific, an "extensions" key in there is not a glTF extension
+					"extras" => {}
+					"extensions" => {
+						if let Some(extensions) = v.as_object() {
+							for (name, content) in extensions {
+								names.insert(name.clone());
+								collect_extension_names(content, names);
+							}
+						}
+					}
+					_ => collect_extension_names(v, names),
+				}
+			}
+		}
+		serde_json::Value::Array(values) => {
+			for v in values {
+				collect_extension_names(v, names);
+			}
+		}
+		_ => {}
+	}
+}
+
+/// see 3.7.2.1 Overview - Meshes, the accessor component types allowed for each attribute semantic
+fn core_allows_attribute_type(semantic: &str, accessor: &GltfAccessor) -> bool {
+	let ct = accessor.component_type;
+	let normalized_small = accessor.normalized && (ct == ComponentType::UNSIGNED_BYTE || ct == ComponentType::UNSIGNED_SHORT);
+
+	match semantic {
+		"POSITION" | "NORMAL" | "TANGENT" => ct == ComponentType::FLOAT,
+		s if s.starts_with("TEXCOORD_") || s.starts_with("COLOR_") || s.starts_with("WEIGHTS_") => {
+			ct == ComponentType::FLOAT || normalized_small
+		}
+		s if s.starts_with("JOINTS_") => ct == ComponentType::UNSIGNED_BYTE || ct == ComponentType::UNSIGNED_SHORT,
+		// custom attributes (prefixed with an underscore) have no restrictions
+		_ => true,
+	}
+}
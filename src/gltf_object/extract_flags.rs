@@ -1,6 +1,7 @@
 use std::ops;
 use std::ops::Deref;
 
+#[derive(Clone, Copy)]
 pub struct GltrExtractFlags(i32);
 
 impl GltrExtractFlags {
@@ -1,4 +1,8 @@
 pub type Extras = Option<serde_json::Value>;
-pub type Extensions = Option<serde_json::Value>;
+pub type Extensions = Option<crate::gltf_object::extensions::GltfExtensions>;
 pub type OptionalObject = Option<serde_json::Value>;
 
+
+/// properties of a JSON object that the struct holding them does not model. they are collected when parsing and
+/// written back unchanged, so that parsing and saving an asset does not lose anything
+pub type UnknownFields = serde_json::Map<String, serde_json::Value>;
@@ -0,0 +1,212 @@
+use crate::buffers::BufferViewTarget;
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::{GltfObject, GltrError, GltrResult};
+use crate::math::*;
+use std::collections::BTreeMap;
+
+/// what primitives need in common to be concatenated: material, mode, and the semantic, type, component type and
+/// normalized flag of every attribute. primitives that can't be concatenated, eg line strips, get a key of their own
+type MergeKey = (Option<Index<GltfMaterial>>, PrimitiveMode, Vec<(String, String, ComponentType, bool)>, Option<usize>);
+
+/// the attributes transformed by node transforms, always written as FLOAT
+const BAKED: [&str; 3] = ["POSITION", "NORMAL", "TANGENT"];
+
+#[derive(Default)]
+struct MergedPrimitive {
+	/// flattened values of every attribute
+	values: BTreeMap<String, Vec<f32>>,
+	indices: Vec<u32>,
+	vertex_count: u32,
+}
+
+impl GltfObject {
+	/// bakes the transforms of the nodes under `root` into their geometry and concatenates the primitives that share
+	/// a material, a mode and the layout of their attributes into one, see [MergeKey]. the nodes under `root` are
+	/// replaced by one child per merged primitive, each with a mesh of its own, and `root` loses its mesh. returns
+	/// the new children.
+	///
+	/// strips and fans become TRIANGLES, every primitive is indexed, with indices rebased on the vertices before
+	/// them and as wide as the merged vertex count needs. the replaced meshes and accessors stay in the document
+	/// until it is pruned. nodes with a skin, camera, morph weights, extensions or animations can't be baked, nor can
+	/// primitives with morph targets. the nodes under `root` are removed, so none of them may be a skin joint
+	pub fn merge_by_material(&mut self, root: Index<GltfNode>) -> GltrResult<Vec<Index<GltfNode>>> {
+		let subtree = self.node_subtree(root)?;
+
+		let mut animated = vec![false; self.nodes.len()];
+		for node in self.animations.iter().flat_map(|a| &a.channels).filter_map(|c| c.target.node) {
+			if let Some(a) = animated.get_mut(node.value()) {
+				*a = true;
+			}
+		}
+
+		let joints = self.skin_joints();
+
+		for idx in &subtree {
+			if *idx != root && joints[idx.value()] {
+				return Err(GltrError::ConstraintViolation(format!("node {idx} is a skin joint and can't be merged away")));
+			}
+
+			let node = &self.nodes[idx.value()];
+			if node.skin.is_some() || node.camera.is_some() || node.weights.is_some() || node.extensions.is_some() || animated[idx.value()] {
+				return Err(GltrError::ConstraintViolation(format!(
+					"node {idx} has a skin, camera, morph weights, extensions or animations and can't be merged"
+				)));
+			}
+		}
+
+		// the transform of every node relative to the root, parents come before their children in the subtree
+		let mut relative: BTreeMap<Index<GltfNode>, Mat4> = BTreeMap::new();
+		relative.insert(root, IDENTITY);
+		for idx in &subtree {
+			let transform = relative[idx];
+			for child in self.nodes[idx.value()].child_indices() {
+				relative.insert(*child, mat4_mul(&transform, &self.nodes[child.value()].local_matrix()));
+			}
+		}
+
+		// in the order the primitives are first met
+		let mut merged: Vec<(MergeKey, MergedPrimitive)> = vec![];
+		let mut unmergeable = 0;
+
+		for idx in &subtree {
+			let mesh = match self.nodes[idx.value()].mesh {
+				None => continue,
+				Some(mesh) => mesh,
+			};
+
+			for primitive in 0..self.try_get(mesh)?.primitives.len() {
+				let p = self.primitive(mesh, primitive)?;
+				if p.targets.as_ref().is_some_and(|t| !t.is_empty()) {
+					return Err(GltrError::ConstraintViolation(format!(
+						"/meshes/{mesh}/primitives/{primitive} has morph targets and can't be merged"
+					)));
+				}
+
+				let vertex_count = self.primitive_vertex_count(mesh, primitive)?;
+				let (mode, indices) = match p.mode() {
+					m if m.is_triangles() => (PrimitiveMode::TRIANGLES, self.primitive_triangles(mesh, primitive)?.flatten().collect()),
+					m => match p.accessor {
+						None => (m, (0..vertex_count as u32).collect::<Vec<u32>>()),
+						Some(accessor) => (m, self.read_accessor_u32(accessor)?),
+					},
+				};
+
+				let mut layout = vec![];
+				for (semantic, accessor) in &p.attributes {
+					let a = self.try_get(*accessor)?;
+					match BAKED.contains(&semantic.as_str()) {
+						true => layout.push((semantic.clone(), a.r#type.clone(), ComponentType::FLOAT, false)),
+						false => layout.push((semantic.clone(), a.r#type.clone(), a.component_type, a.normalized)),
+					}
+				}
+
+				let own = match mode {
+					PrimitiveMode::LINE_STRIP | PrimitiveMode::LINE_LOOP => {
+						unmergeable += 1;
+						Some(unmergeable)
+					}
+					_ => None,
+				};
+
+				let transform = relative[idx];
+				let normals = normal_matrix(&transform);
+				let flipped = mat4_determinant3(&transform) < 0f32;
+
+				let key = (p.material, mode, layout, own);
+				let at = match merged.iter().position(|(k, _)| *k == key) {
+					Some(at) => at,
+					None => {
+						merged.push((key, MergedPrimitive::default()));
+						merged.len() - 1
+					}
+				};
+				let target = &mut merged[at].1;
+				let base = target.vertex_count;
+
+				for (semantic, accessor) in &p.attributes {
+					let mut values = self.read_accessor_f32(*accessor)?;
+					values.truncate(vertex_count * self.try_get(*accessor)?.component_count());
+
+					match semantic.as_str() {
+						"POSITION" => values.chunks_exact_mut(3).for_each(|v| v.copy_from_slice(&transform_point(&transform, [v[0], v[1], v[2]]))),
+						"NORMAL" => values.chunks_exact_mut(3).for_each(|v| v.copy_from_slice(&normalize(transform_vector(&normals, [v[0], v[1], v[2]])))),
+						"TANGENT" => values.chunks_exact_mut(4).for_each(|v| {
+							let tangent = normalize(transform_vector(&transform, [v[0], v[1], v[2]]));
+							v[..3].copy_from_slice(&tangent);
+							if flipped {
+								v[3] = -v[3];
+							}
+						}),
+						_ => {}
+					}
+
+					target.values.entry(semantic.clone()).or_default().extend(values);
+				}
+
+				if let Some(i) = indices.iter().find(|i| **i as usize >= vertex_count) {
+					return Err(GltrError::ConstraintViolation(format!(
+						"/meshes/{mesh}/primitives/{primitive} uses vertex {i}, POSITION has {vertex_count} vertices"
+					)));
+				}
+
+				// a mirroring transform turns the triangles over, swapping two corners turns them back
+				if flipped && mode == PrimitiveMode::TRIANGLES {
+					target.indices.extend(indices.chunks_exact(3).flat_map(|t| [t[0], t[2], t[1]]).map(|i| i + base));
+				} else {
+					target.indices.extend(indices.iter().map(|i| i + base));
+				}
+				target.vertex_count += vertex_count as u32;
+			}
+		}
+
+		let mut meshes = vec![];
+		for ((material, mode, layout, _), primitive) in merged {
+			let mut attributes = BTreeMap::new();
+			for (semantic, accessor_type, component_type, normalized) in layout {
+				let values = &primitive.values[&semantic];
+				let accessor = self.push_accessor_f32(values, &accessor_type, component_type, normalized, Some(BufferViewTarget::ARRAY_BUFFER))?;
+				attributes.insert(semantic, accessor);
+			}
+
+			let indices = self.push_accessor_indices(&primitive.indices)?;
+			let name = material.and_then(|m| self.get(m)).and_then(|m| m.name.clone());
+
+			meshes.push(self.push(GltfMesh {
+				name: name.clone(),
+				primitives: vec![GltfMeshPrimitive {
+					attributes,
+					accessor: Some(indices),
+					material,
+					mode: if mode == PrimitiveMode::TRIANGLES { None } else { Some(mode) },
+					targets: None,
+					extensions: None,
+					extras: None,
+					unknown_fields: UnknownFields::new(),
+					original_index: None,
+				}],
+				..GltfMesh::default()
+			}));
+		}
+
+		// the root moves down by the number of removed nodes before it
+		let descendants: Vec<Index<GltfNode>> = subtree.iter().copied().filter(|n| *n != root).collect();
+		let root = Index::<GltfNode>::new(root.value() - descendants.iter().filter(|n| **n < root).count());
+
+		self.nodes[subtree[0].value()].children = None;
+		self.nodes[subtree[0].value()].mesh = None;
+		self.remove_nodes(&descendants)?;
+
+		let mut children = vec![];
+		for mesh in meshes {
+			children.push(self.push(GltfNode {
+				name: self.meshes[mesh.value()].name.clone(),
+				mesh: Some(mesh),
+				..GltfNode::default()
+			}));
+		}
+
+		self.nodes[root.value()].children = if children.is_empty() { None } else { Some(children.clone()) };
+
+		Ok(children)
+	}
+}
@@ -0,0 +1,93 @@
+mod merge;
+mod normals;
+mod simplify;
+mod tangents;
+mod triangles;
+mod vertex_cache;
+mod weld;
+
+pub use normals::*;
+pub use simplify::*;
+pub use tangents::*;
+pub use triangles::*;
+pub use vertex_cache::*;
+pub use weld::*;
+
+use crate::buffers::BufferViewTarget;
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::{GltfObject, GltrError, GltrResult};
+
+impl GltfObject {
+	/// the primitive at `primitive` of the mesh, with an [GltrError::InvalidIndex] naming whichever does not exist
+	pub fn primitive(&self, mesh: Index<GltfMesh>, primitive: usize) -> GltrResult<&GltfMeshPrimitive> {
+		match self.try_get(mesh)?.primitives.get(primitive) {
+			None => Err(GltrError::invalid_index(format!("/meshes/{mesh}/primitives"), primitive)),
+			Some(p) => Ok(p),
+		}
+	}
+
+	pub fn primitive_mut(&mut self, mesh: Index<GltfMesh>, primitive: usize) -> GltrResult<&mut GltfMeshPrimitive> {
+		match self.get_mut(mesh) {
+			None => Err(GltrError::invalid_index(GltfMesh::PATH, mesh.value())),
+			Some(m) => match m.primitives.get_mut(primitive) {
+				None => Err(GltrError::invalid_index(format!("/meshes/{mesh}/primitives"), primitive)),
+				Some(p) => Ok(p),
+			},
+		}
+	}
+
+	/// the number of vertices of the primitive, the count of its POSITION accessor
+	pub fn primitive_vertex_count(&self, mesh: Index<GltfMesh>, primitive: usize) -> GltrResult<usize> {
+		match self.primitive(mesh, primitive)?.attributes.get("POSITION") {
+			None => Err(GltrError::ConstraintViolation(format!(
+				"/meshes/{mesh}/primitives/{primitive} has no POSITION attribute"
+			))),
+			Some(position) => Ok(self.try_get(*position)?.count),
+		}
+	}
+
+	/// rebuilds the vertices of a primitive, new vertex i being a copy of vertex `sources[i]`, drawn by the index
+	/// accessor `indices`. every attribute and morph target is written to a new accessor with its component type
+	/// kept, the replaced accessors stay in the document until it is pruned
+	pub(crate) fn remap_vertices(&mut self, mesh: Index<GltfMesh>, primitive: usize, sources: &[u32], indices: Index<GltfAccessor>) -> GltrResult<()> {
+		let p = self.primitive(mesh, primitive)?.clone();
+
+		let mut remap = |accessor: Index<GltfAccessor>| -> GltrResult<Index<GltfAccessor>> {
+			let a = self.try_get(accessor)?;
+			let (n, accessor_type, component_type, normalized) = (a.component_count(), a.r#type.clone(), a.component_type, a.normalized);
+
+			let values = self.read_accessor_f32(accessor)?;
+			let mut remapped = Vec::with_capacity(sources.len() * n);
+			for source in sources {
+				match values.get(*source as usize * n..(*source as usize + 1) * n) {
+					None => {
+						return Err(GltrError::ConstraintViolation(format!(
+							"vertex {source} is outside of accessor {accessor} with {} elements",
+							a.count
+						)))
+					}
+					Some(element) => remapped.extend_from_slice(element),
+				}
+			}
+
+			self.push_accessor_f32(&remapped, &accessor_type, component_type, normalized, Some(BufferViewTarget::ARRAY_BUFFER))
+		};
+
+		let mut attributes = p.attributes.clone();
+		for accessor in attributes.values_mut() {
+			*accessor = remap(*accessor)?;
+		}
+
+		let mut targets = p.targets.clone();
+		for accessor in targets.iter_mut().flatten().flat_map(|t| t.values_mut()) {
+			*accessor = remap(*accessor)?;
+		}
+
+		let p = self.primitive_mut(mesh, primitive)?;
+		p.attributes = attributes;
+		p.targets = targets;
+		p.accessor = Some(indices);
+
+		Ok(())
+	}
+}
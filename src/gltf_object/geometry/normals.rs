@@ -0,0 +1,126 @@
+use crate::buffers::BufferViewTarget;
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::{GltfObject, GltrError, GltrResult};
+use crate::math::*;
+use std::collections::HashMap;
+
+/// How [GltfObject::generate_normals] shades a primitive
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NormalMode {
+	/// every triangle gets the normal of its face, vertices shared by triangles facing different ways are split
+	Flat,
+
+	/// vertices get the average of the normals of the faces around them, weighted by the angle of each face at the
+	/// vertex. faces meeting at more than `crease_angle` radians keep separate normals, splitting the vertex
+	Smooth { crease_angle: f32 },
+}
+
+impl Default for NormalMode {
+	fn default() -> Self {
+		NormalMode::Smooth {
+			crease_angle: std::f32::consts::PI,
+		}
+	}
+}
+
+impl GltfObject {
+	/// writes a NORMAL attribute for a triangle primitive, replacing the existing one.
+	///
+	/// faces are found from the POSITION accessor and the indices, and vertices at the same position are treated as
+	/// one so seams of other attributes stay smooth. the primitive is rebuilt as an indexed TRIANGLES primitive
+	/// when vertices have to be split, see [NormalMode]. degenerate triangles don't contribute to any normal
+	pub fn generate_normals(&mut self, mesh: Index<GltfMesh>, primitive: usize, mode: NormalMode) -> GltrResult<()> {
+		let triangles: Vec<[u32; 3]> = self.primitive_triangles(mesh, primitive)?.collect();
+		let position = self.primitive(mesh, primitive)?.attributes["POSITION"];
+		let positions = self.read_accessor_array::<3>(position)?;
+
+		if let Some(corner) = triangles.iter().flatten().find(|v| **v as usize >= positions.len()) {
+			return Err(GltrError::ConstraintViolation(format!(
+				"/meshes/{mesh}/primitives/{primitive} uses vertex {corner}, POSITION has {} vertices",
+				positions.len()
+			)));
+		}
+
+		let faces: Vec<Vec3> = triangles
+			.iter()
+			.map(|t| {
+				let [a, b, c] = t.map(|v| positions[v as usize]);
+				normalize(cross(sub(b, a), sub(c, a)))
+			})
+			.collect();
+
+		// the normal of every corner of every triangle
+		let corners: Vec<Vec3> = match mode {
+			NormalMode::Flat => faces.iter().flat_map(|f| [*f; 3]).collect(),
+			NormalMode::Smooth { crease_angle } => smooth_normals(&triangles, &positions, &faces, crease_angle.cos()),
+		};
+
+		// a vertex is kept once per distinct normal of its corners
+		let mut vertices: HashMap<(u32, [u32; 3]), u32> = HashMap::new();
+		let mut sources = vec![];
+		let mut normals = vec![];
+		let mut indices = Vec::with_capacity(corners.len());
+		for (vertex, normal) in triangles.iter().flatten().zip(&corners) {
+			let key = (*vertex, normal.map(f32::to_bits));
+			let index = *vertices.entry(key).or_insert_with(|| {
+				sources.push(*vertex);
+				normals.push(*normal);
+				sources.len() as u32 - 1
+			});
+			indices.push(index);
+		}
+
+		// when no vertex is split or dropped the normals already are in vertex order
+		let unchanged = sources.len() == positions.len() && sources.iter().enumerate().all(|(i, s)| i as u32 == *s);
+		if !unchanged {
+			let indices = self.push_accessor_indices(&indices)?;
+			self.remap_vertices(mesh, primitive, &sources, indices)?;
+			self.primitive_mut(mesh, primitive)?.mode = None;
+		}
+
+		for normal in normals.iter_mut() {
+			if *normal == [0f32; 3] {
+				*normal = [0f32, 0f32, 1f32];
+			}
+		}
+
+		let accessor = self.push_accessor_array::<3>(&normals, Some(BufferViewTarget::ARRAY_BUFFER))?;
+		self.primitive_mut(mesh, primitive)?.attributes.insert("NORMAL".to_string(), accessor);
+
+		Ok(())
+	}
+}
+
+/// the normal of every triangle corner: the angle weighted sum of the faces at the same position whose normal is
+/// within the crease angle of the corner's own face
+fn smooth_normals(triangles: &[[u32; 3]], positions: &[Vec3], faces: &[Vec3], crease_cos: f32) -> Vec<Vec3> {
+	let key = |v: u32| positions[v as usize].map(f32::to_bits);
+
+	// the corners around every position, as (triangle, angle of the triangle at the corner)
+	let mut around: HashMap<[u32; 3], Vec<(usize, f32)>> = HashMap::new();
+	for (t, triangle) in triangles.iter().enumerate() {
+		for c in 0..3 {
+			let p = positions[triangle[c] as usize];
+			let e1 = normalize(sub(positions[triangle[(c + 1) % 3] as usize], p));
+			let e2 = normalize(sub(positions[triangle[(c + 2) % 3] as usize], p));
+			let angle = dot(e1, e2).clamp(-1f32, 1f32).acos();
+
+			around.entry(key(triangle[c])).or_default().push((t, angle));
+		}
+	}
+
+	let mut normals = Vec::with_capacity(triangles.len() * 3);
+	for (t, triangle) in triangles.iter().enumerate() {
+		for vertex in triangle {
+			let mut normal = [0f32; 3];
+			for (other, angle) in &around[&key(*vertex)] {
+				if faces[*other] != [0f32; 3] && dot(faces[t], faces[*other]) >= crease_cos - 1e-6 {
+					normal = add(normal, scale(faces[*other], *angle));
+				}
+			}
+			normals.push(normalize(normal));
+		}
+	}
+
+	normals
+}
@@ -0,0 +1,390 @@
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::{GltfObject, GltrError, GltrResult};
+use crate::math::*;
+use std::collections::HashMap;
+
+/// how much more the planes along borders weigh than the faces, keeping the outline of open meshes in place
+const BORDER_WEIGHT: f64 = 10f64;
+
+/// Options of [GltfObject::simplify_primitive]
+#[derive(Debug, Clone, Copy)]
+pub struct SimplifyOptions {
+	/// the fraction of the triangles to keep, simplification stops once it is reached
+	pub ratio: f32,
+
+	/// the largest distance a surface may move, relative to the diagonal of the bounding box of the primitive.
+	/// simplification stops before the ratio is reached when no collapse stays within it
+	pub max_error: f32,
+
+	/// keeps every vertex on a border of the mesh, instead of letting them collapse along the border
+	pub lock_borders: bool,
+}
+
+impl Default for SimplifyOptions {
+	fn default() -> Self {
+		SimplifyOptions {
+			ratio: 0.5f32,
+			max_error: 0.01f32,
+			lock_borders: false,
+		}
+	}
+}
+
+/// A simplified index list, see [GltfObject::simplify_primitive]
+#[derive(Debug, Clone, Default)]
+pub struct Simplified {
+	/// triangle list indices into the vertices of the original primitive
+	pub indices: Vec<u32>,
+
+	/// the largest distance a surface moved, relative to the diagonal of the bounding box of the primitive
+	pub error: f32,
+}
+
+/// a symmetric 4x4 matrix measuring the squared distance to a set of planes, with the total weight of the planes
+#[derive(Debug, Clone, Copy, Default)]
+struct Quadric {
+	a: [f64; 10],
+	weight: f64,
+}
+
+impl Quadric {
+	/// the plane through `point` with unit `normal`
+	fn plane(normal: Vec3, point: Vec3, weight: f64) -> Self {
+		let [x, y, z] = normal.map(|c| c as f64);
+		let d = -(x * point[0] as f64 + y * point[1] as f64 + z * point[2] as f64);
+		let a = [x * x, x * y, x * z, x * d, y * y, y * z, y * d, z * z, z * d, d * d];
+
+		Quadric { a: a.map(|c| c * weight), weight }
+	}
+
+	fn add(&mut self, other: &Quadric) {
+		for (a, b) in self.a.iter_mut().zip(&other.a) {
+			*a += b;
+		}
+		self.weight += other.weight;
+	}
+
+	/// the weighted mean of the squared distances from `p` to the planes
+	fn error(&self, p: Vec3) -> f64 {
+		let [x, y, z] = p.map(|c| c as f64);
+		let a = &self.a;
+		let e = a[0] * x * x + 2f64 * a[1] * x * y + 2f64 * a[2] * x * z + 2f64 * a[3] * x
+			+ a[4] * y * y + 2f64 * a[5] * y * z + 2f64 * a[6] * y
+			+ a[7] * z * z + 2f64 * a[8] * z
+			+ a[9];
+
+		if self.weight > 0f64 {
+			(e / self.weight).abs()
+		} else {
+			0f64
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum VertexKind {
+	/// may collapse onto any neighbour
+	Interior,
+	/// on an open edge of the mesh, may only collapse along it
+	Border,
+	/// on a uv or normal seam, a non-manifold edge or a corner of a border, never moves
+	Locked,
+}
+
+/// quadric error metric edge collapse over `triangles`, collapsing vertices onto their neighbours so the remaining
+/// triangles only use vertices of the input. `wedges` maps every vertex to the one standing for all the vertices
+/// that are the same for simplification, the result only uses those
+pub(crate) fn simplify_triangles(triangles: &[[u32; 3]], positions: &[Vec3], wedges: &[u32], options: SimplifyOptions) -> Simplified {
+	let vertex_count = positions.len();
+	let triangles: Vec<[u32; 3]> = triangles.iter().map(|t| t.map(|v| wedges[v as usize])).collect();
+	let triangles = &triangles[..];
+	let target_count = (triangles.len() as f32 * options.ratio.clamp(0f32, 1f32)).ceil() as usize;
+
+	let (lo, hi) = triangles.iter().flatten().fold(([f32::MAX; 3], [f32::MIN; 3]), |(lo, hi), v| {
+		(min(lo, positions[*v as usize]), max(hi, positions[*v as usize]))
+	});
+	let extent = if triangles.is_empty() { 0f64 } else { length(sub(hi, lo)) as f64 };
+	let max_error = options.max_error.max(0f32) as f64 * extent;
+
+	// vertices at the same position, with different values for other attributes, are one point of the surface
+	let mut points: HashMap<[u32; 3], u32> = HashMap::new();
+	let point: Vec<u32> = positions
+		.iter()
+		.map(|p| {
+			let next = points.len() as u32;
+			*points.entry(p.map(f32::to_bits)).or_insert(next)
+		})
+		.collect();
+
+	// triangles per surface edge, and the vertices used at every point
+	let edge_key = |a: u32, b: u32| (point[a as usize].min(point[b as usize]), point[a as usize].max(point[b as usize]));
+	let mut edges: HashMap<(u32, u32), usize> = HashMap::new();
+	let mut point_vertices: Vec<Vec<u32>> = vec![vec![]; points.len()];
+	for triangle in triangles {
+		for c in 0..3 {
+			*edges.entry(edge_key(triangle[c], triangle[(c + 1) % 3])).or_default() += 1;
+
+			let used = &mut point_vertices[point[triangle[c] as usize] as usize];
+			if !used.contains(&triangle[c]) {
+				used.push(triangle[c]);
+			}
+		}
+	}
+
+	let mut border_edges = vec![0; vertex_count];
+	let mut kind = vec![VertexKind::Interior; vertex_count];
+	for triangle in triangles {
+		for c in 0..3 {
+			let (a, b) = (triangle[c], triangle[(c + 1) % 3]);
+			match edges[&edge_key(a, b)] {
+				1 => {
+					border_edges[a as usize] += 1;
+					border_edges[b as usize] += 1;
+				}
+				2 => {}
+				_ => {
+					kind[a as usize] = VertexKind::Locked;
+					kind[b as usize] = VertexKind::Locked;
+				}
+			}
+		}
+	}
+
+	for v in 0..vertex_count {
+		if point_vertices[point[v] as usize].len() > 1 {
+			kind[v] = VertexKind::Locked;
+		} else if kind[v] == VertexKind::Interior && border_edges[v] > 0 {
+			// a border passing through has two edges, anything else is where borders meet
+			kind[v] = if border_edges[v] == 2 && !options.lock_borders { VertexKind::Border } else { VertexKind::Locked };
+		}
+	}
+
+	let mut quadrics = vec![Quadric::default(); vertex_count];
+	for triangle in triangles {
+		let [p0, p1, p2] = triangle.map(|v| positions[v as usize]);
+		let normal = cross(sub(p1, p0), sub(p2, p0));
+		let area = length(normal) as f64 / 2f64;
+		if area == 0f64 {
+			continue;
+		}
+		let normal = normalize(normal);
+
+		let face = Quadric::plane(normal, p0, area);
+		for v in triangle {
+			quadrics[*v as usize].add(&face);
+		}
+
+		for c in 0..3 {
+			let (a, b) = (triangle[c], triangle[(c + 1) % 3]);
+			if edges[&edge_key(a, b)] == 1 {
+				let edge = sub(positions[b as usize], positions[a as usize]);
+				let weight = dot(edge, edge) as f64 * BORDER_WEIGHT;
+				let border = Quadric::plane(normalize(cross(edge, normal)), positions[a as usize], weight);
+				quadrics[a as usize].add(&border);
+				quadrics[b as usize].add(&border);
+			}
+		}
+	}
+
+	let mut triangles = triangles.to_vec();
+	let mut alive = vec![true; triangles.len()];
+	let mut alive_count = triangles.len();
+	let mut adjacency: Vec<Vec<usize>> = vec![vec![]; vertex_count];
+	for (t, triangle) in triangles.iter().enumerate() {
+		for v in triangle {
+			if !adjacency[*v as usize].contains(&t) {
+				adjacency[*v as usize].push(t);
+			}
+		}
+	}
+
+	let mut error = 0f64;
+
+	while alive_count > target_count {
+		// the cheapest collapse of every vertex that can move
+		let mut collapses: Vec<(f64, u32, u32)> = vec![];
+		for v in 0..vertex_count as u32 {
+			if kind[v as usize] == VertexKind::Locked {
+				continue;
+			}
+
+			let mut best: Option<(f64, u32)> = None;
+			for t in &adjacency[v as usize] {
+				for target in triangles[*t] {
+					if target == v || (kind[v as usize] == VertexKind::Border && edges.get(&edge_key(v, target)) != Some(&1)) {
+						continue;
+					}
+
+					let cost = quadrics[v as usize].error(positions[target as usize]);
+					if best.is_none_or(|(c, _)| cost < c) {
+						best = Some((cost, target));
+					}
+				}
+			}
+
+			if let Some((cost, target)) = best {
+				collapses.push((cost, v, target));
+			}
+		}
+
+		collapses.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+		// collapses in one pass don't touch each other's neighbourhood, so the costs they were sorted by still hold
+		let mut dirty = vec![false; vertex_count];
+		let mut collapsed = 0;
+		for (cost, v, target) in collapses {
+			if cost.sqrt() > max_error {
+				break;
+			}
+			if dirty[v as usize] || dirty[target as usize] {
+				continue;
+			}
+			if !keeps_manifold(&triangles, &adjacency, &point, v, target) || flips(&triangles, &adjacency[v as usize], v, target, positions) {
+				continue;
+			}
+
+			for t in std::mem::take(&mut adjacency[v as usize]) {
+				for c in 0..3 {
+					dirty[triangles[t][c] as usize] = true;
+					*edges.entry(edge_key(triangles[t][c], triangles[t][(c + 1) % 3])).or_default() -= 1;
+				}
+
+				if triangles[t].contains(&target) {
+					alive[t] = false;
+					alive_count -= 1;
+					for u in triangles[t] {
+						adjacency[u as usize].retain(|x| *x != t);
+					}
+				} else {
+					for u in triangles[t].iter_mut() {
+						if *u == v {
+							*u = target;
+						}
+					}
+					for c in 0..3 {
+						*edges.entry(edge_key(triangles[t][c], triangles[t][(c + 1) % 3])).or_default() += 1;
+					}
+					adjacency[target as usize].push(t);
+				}
+			}
+
+			let q = quadrics[v as usize];
+			quadrics[target as usize].add(&q);
+			error = error.max(cost);
+			collapsed += 1;
+
+			if alive_count <= target_count {
+				break;
+			}
+		}
+
+		if collapsed == 0 {
+			break;
+		}
+	}
+
+	let indices = triangles.iter().zip(&alive).filter(|(_, a)| **a).flat_map(|(t, _)| *t).collect();
+	let error = if extent > 0f64 { error.sqrt() / extent } else { 0f64 };
+
+	Simplified {
+		indices,
+		error: error as f32,
+	}
+}
+
+/// the link condition: the points next to both `v` and `target` must be the third corners of the triangles
+/// sharing their edge, otherwise the collapse pinches the surface into a non-manifold edge
+fn keeps_manifold(triangles: &[[u32; 3]], adjacency: &[Vec<usize>], point: &[u32], v: u32, target: u32) -> bool {
+	let neighbours = |x: u32| -> Vec<u32> {
+		let mut n: Vec<u32> = adjacency[x as usize].iter().flat_map(|t| triangles[*t]).map(|u| point[u as usize]).collect();
+		n.sort_unstable();
+		n.dedup();
+		n.retain(|p| *p != point[x as usize]);
+		n
+	};
+
+	let (a, b) = (neighbours(v), neighbours(target));
+	let common = a.iter().filter(|p| b.contains(p)).count();
+	let shared = adjacency[v as usize].iter().filter(|t| triangles[**t].contains(&target)).count();
+
+	common == shared
+}
+
+/// whether collapsing `v` onto `target` turns any of the triangles around `v` that survive the collapse over, or
+/// makes them degenerate
+fn flips(triangles: &[[u32; 3]], around: &[usize], v: u32, target: u32, positions: &[Vec3]) -> bool {
+	let normal = |t: [u32; 3]| {
+		let [p0, p1, p2] = t.map(|v| positions[v as usize]);
+		cross(sub(p1, p0), sub(p2, p0))
+	};
+
+	around.iter().map(|t| triangles[*t]).filter(|t| !t.contains(&target)).any(|t| {
+		let before = normal(t);
+		let after = normal(t.map(|u| if u == v { target } else { u }));
+		dot(before, after) <= 1e-3 * length(before) * length(after) || length(after) <= f32::EPSILON * length(before)
+	})
+}
+
+impl GltfObject {
+	/// simplifies a triangle primitive by quadric error metric edge collapse, see [SimplifyOptions].
+	///
+	/// vertices only ever collapse onto one of their neighbours, so the result indexes the vertices of the
+	/// primitive and its attributes can be shared as they are. vertices with the same position, normal and texture
+	/// coordinates are merged first, whatever their other attributes. vertices on uv and normal seams (several
+	/// texture coordinates or normals at one position, eg the hard edges of a faceted mesh) never move and vertices
+	/// on the border of open meshes only move along it. the primitive itself is not modified
+	pub fn simplify_primitive(&self, mesh: Index<GltfMesh>, primitive: usize, options: SimplifyOptions) -> GltrResult<Simplified> {
+		let triangles: Vec<[u32; 3]> = self.primitive_triangles(mesh, primitive)?.collect();
+		let p = self.primitive(mesh, primitive)?;
+		let positions = self.read_accessor_array::<3>(p.attributes["POSITION"])?;
+
+		if let Some(i) = triangles.iter().flatten().find(|i| **i as usize >= positions.len()) {
+			return Err(GltrError::ConstraintViolation(format!(
+				"/meshes/{mesh}/primitives/{primitive} uses vertex {i}, POSITION has {} vertices",
+				positions.len()
+			)));
+		}
+
+		// the key of every vertex: its position, normal and every set of texture coordinates
+		let mut keys: Vec<Vec<u32>> = positions.iter().map(|p| p.map(f32::to_bits).to_vec()).collect();
+		for (semantic, accessor) in &p.attributes {
+			if semantic == "NORMAL" {
+				let values = self.read_accessor_array::<3>(*accessor)?;
+				for (key, value) in keys.iter_mut().zip(values) {
+					key.extend(value.map(f32::to_bits));
+				}
+			} else if semantic.starts_with("TEXCOORD_") {
+				let values = self.read_accessor_array::<2>(*accessor)?;
+				for (key, value) in keys.iter_mut().zip(values) {
+					key.extend(value.map(f32::to_bits));
+				}
+			}
+		}
+
+		let mut first: HashMap<&[u32], u32> = HashMap::new();
+		let wedges: Vec<u32> = keys.iter().enumerate().map(|(v, key)| *first.entry(key).or_insert(v as u32)).collect();
+
+		Ok(simplify_triangles(&triangles, &positions, &wedges, options))
+	}
+
+	/// adds a copy of `mesh` whose triangle primitives are simplified, sharing the vertex attributes of the original
+	/// with new index accessors. points and lines are kept as they are. returns the new mesh and the largest relative
+	/// error of its primitives
+	pub fn simplify_mesh(&mut self, mesh: Index<GltfMesh>, options: SimplifyOptions) -> GltrResult<(Index<GltfMesh>, f32)> {
+		let mut simplified = self.try_get(mesh)?.clone();
+		let mut error = 0f32;
+
+		for (i, primitive) in simplified.primitives.iter_mut().enumerate() {
+			if !primitive.mode().is_triangles() {
+				continue;
+			}
+
+			let result = self.simplify_primitive(mesh, i, options)?;
+			primitive.accessor = Some(self.push_accessor_indices(&result.indices)?);
+			primitive.mode = None;
+			error = error.max(result.error);
+		}
+
+		Ok((self.push(simplified), error))
+	}
+}
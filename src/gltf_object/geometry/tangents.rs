@@ -0,0 +1,480 @@
+use crate::buffers::BufferViewTarget;
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::{GltfObject, GltrError, GltrResult};
+use crate::math::*;
+use std::collections::HashMap;
+
+/// The tangent space MikkTSpace gives a triangle corner, see [GltfObject::primitive_tangent_spaces]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TangentSpace {
+	/// the unit tangent, pointing the way u grows
+	pub tangent: [f32; 3],
+
+	/// the unit bitangent, pointing the way v grows with the texture origin at the bottom left
+	pub bitangent: [f32; 3],
+
+	/// the distance moved along the surface per unit of u
+	pub magnitude_s: f32,
+
+	/// the distance moved along the surface per unit of v
+	pub magnitude_t: f32,
+
+	/// whether the uv mapping keeps the winding of the triangles, the w of a gltf tangent is 1 when it does
+	pub orientation_preserving: bool,
+}
+
+impl Default for TangentSpace {
+	/// the tangent space of corners no space could be derived for, as MikkTSpace leaves them
+	fn default() -> Self {
+		TangentSpace {
+			tangent: [1f32, 0f32, 0f32],
+			bitangent: [0f32, 1f32, 0f32],
+			magnitude_s: 1f32,
+			magnitude_t: 1f32,
+			orientation_preserving: false,
+		}
+	}
+}
+
+impl GltfObject {
+	/// writes a TANGENT attribute for every triangle primitive whose material has a normal texture, see
+	/// [GltfObject::generate_primitive_tangents]. returns the number of primitives that got tangents
+	pub fn generate_tangents(&mut self) -> GltrResult<usize> {
+		let mut count = 0;
+
+		for mesh in self.indices::<GltfMesh>().collect::<Vec<_>>() {
+			for primitive in 0..self.meshes[mesh.value()].primitives.len() {
+				let p = &self.meshes[mesh.value()].primitives[primitive];
+				let normal_texture = p.material.and_then(|m| self.get(m)).and_then(|m| m.normal_texture.as_ref());
+
+				if normal_texture.is_some() && p.mode().is_triangles() {
+					self.generate_primitive_tangents(mesh, primitive)?;
+					count += 1;
+				}
+			}
+		}
+
+		Ok(count)
+	}
+
+	/// writes a VEC4 TANGENT attribute for a triangle primitive, replacing the existing one. the tangents are the
+	/// MikkTSpace tangents of [GltfObject::primitive_tangent_spaces], w holding the handedness, the bitangent being
+	/// `cross(normal, tangent.xyz) * w`. vertices whose corners get different tangents, eg on a mirrored uv seam, are
+	/// split and the primitive is rebuilt as an indexed TRIANGLES primitive
+	pub fn generate_primitive_tangents(&mut self, mesh: Index<GltfMesh>, primitive: usize) -> GltrResult<()> {
+		let spaces = self.primitive_tangent_spaces(mesh, primitive)?;
+		let triangles: Vec<[u32; 3]> = self.primitive_triangles(mesh, primitive)?.collect();
+		let vertex_count = self.primitive_vertex_count(mesh, primitive)?;
+
+		// a vertex is kept once per tangent of its corners
+		let mut vertices: HashMap<(u32, [u32; 4]), u32> = HashMap::new();
+		let mut sources = vec![];
+		let mut tangents = vec![];
+		let mut indices = Vec::with_capacity(spaces.len());
+		for (vertex, space) in triangles.iter().flatten().zip(&spaces) {
+			let [x, y, z] = space.tangent;
+			let tangent = [x, y, z, if space.orientation_preserving { 1f32 } else { -1f32 }];
+
+			let index = *vertices.entry((*vertex, tangent.map(f32::to_bits))).or_insert_with(|| {
+				sources.push(*vertex);
+				tangents.push(tangent);
+				sources.len() as u32 - 1
+			});
+			indices.push(index);
+		}
+
+		// when no vertex is split or dropped the tangents already are in vertex order
+		let unchanged = sources.len() == vertex_count && sources.iter().enumerate().all(|(i, s)| i as u32 == *s);
+		if !unchanged {
+			let indices = self.push_accessor_indices(&indices)?;
+			self.remap_vertices(mesh, primitive, &sources, indices)?;
+			self.primitive_mut(mesh, primitive)?.mode = None;
+		}
+
+		let accessor = self.push_accessor_array::<4>(&tangents, Some(BufferViewTarget::ARRAY_BUFFER))?;
+		self.primitive_mut(mesh, primitive)?.attributes.insert("TANGENT".to_string(), accessor);
+
+		Ok(())
+	}
+
+	/// the MikkTSpace tangent space of every corner of the triangles of a primitive, three per triangle in the order
+	/// of [GltfObject::primitive_triangles].
+	///
+	/// spaces come from POSITION, NORMAL and the texture coordinates used by the normal texture of the material,
+	/// TEXCOORD_0 when the primitive has no material or the material no normal texture
+	pub fn primitive_tangent_spaces(&self, mesh: Index<GltfMesh>, primitive: usize) -> GltrResult<Vec<TangentSpace>> {
+		let triangles: Vec<[u32; 3]> = self.primitive_triangles(mesh, primitive)?.collect();
+		let p = self.primitive(mesh, primitive)?;
+
+		let tex_coord = match p.material.and_then(|m| self.get(m)).and_then(|m| m.normal_texture.as_ref()) {
+			None => 0,
+			Some(t) => t.tex_coord,
+		};
+
+		let attribute = |semantic: &str| match p.attributes.get(semantic) {
+			None => Err(GltrError::ConstraintViolation(format!(
+				"/meshes/{mesh}/primitives/{primitive} has no {semantic} attribute"
+			))),
+			Some(accessor) => Ok(*accessor),
+		};
+
+		let positions = self.read_accessor_array::<3>(attribute("POSITION")?)?;
+		let normals = self.read_accessor_array::<3>(attribute("NORMAL")?)?;
+		let tex_coords = self.read_accessor_array::<2>(attribute(&format!("TEXCOORD_{tex_coord}"))?)?;
+
+		let vertex_count = positions.len().min(normals.len()).min(tex_coords.len());
+		if let Some(corner) = triangles.iter().flatten().find(|v| **v as usize >= vertex_count) {
+			return Err(GltrError::ConstraintViolation(format!(
+				"/meshes/{mesh}/primitives/{primitive} uses vertex {corner}, its attributes have {vertex_count} vertices"
+			)));
+		}
+
+		Ok(Mikktspace { positions, normals, tex_coords }.tangent_spaces(&triangles))
+	}
+}
+
+/// the cosine of the default angular threshold of MikkTSpace, 180 degrees. corners of a group are only split when
+/// their tangents or bitangents point opposite ways
+const ANGULAR_THRESHOLD_COS: f32 = -1f32;
+
+/// the vertex attributes MikkTSpace reads, a port of the reference implementation by Morten S. Mikkelsen
+struct Mikktspace {
+	positions: Vec<Vec3>,
+	normals: Vec<Vec3>,
+	tex_coords: Vec<[f32; 2]>,
+}
+
+/// a triangle that isn't degenerate, with its vertices welded
+struct Triangle {
+	/// the index of the triangle in the primitive
+	index: usize,
+	vertices: [u32; 3],
+
+	/// the triangle across the edge from each corner to the next
+	neighbours: [Option<usize>; 3],
+
+	/// the group of each corner
+	groups: [Option<usize>; 3],
+
+	/// the unit tangent and bitangent of the uv mapping, zero when the mapping is degenerate
+	os: Vec3,
+	ot: Vec3,
+	magnitude_s: f32,
+	magnitude_t: f32,
+	orientation_preserving: bool,
+
+	/// the uv mapping is degenerate, the triangle joins any group it touches but adds nothing to its space
+	group_with_any: bool,
+}
+
+impl Triangle {
+	/// the corner at a welded vertex
+	fn corner(&self, vertex: u32) -> Option<usize> {
+		self.vertices.iter().position(|v| *v == vertex)
+	}
+}
+
+/// the corners around a welded vertex reached by walking across edges between triangles of the same orientation
+struct Group {
+	vertex: u32,
+	orientation_preserving: bool,
+	triangles: Vec<usize>,
+}
+
+/// MikkTSpace treats values as zero down to the smallest normal float
+fn not_zero(x: f32) -> bool {
+	x.abs() > f32::MIN_POSITIVE
+}
+
+fn normalize_not_zero(v: Vec3) -> Vec3 {
+	if v.iter().any(|x| not_zero(*x)) {
+		normalize(v)
+	} else {
+		v
+	}
+}
+
+/// `v` projected on the plane of the unit normal `n` and normalized
+fn project(v: Vec3, n: Vec3) -> Vec3 {
+	normalize_not_zero(sub(v, scale(n, dot(n, v))))
+}
+
+impl Mikktspace {
+	fn position(&self, v: u32) -> Vec3 {
+		self.positions[v as usize]
+	}
+
+	fn normal(&self, v: u32) -> Vec3 {
+		self.normals[v as usize]
+	}
+
+	/// the texture coordinates of gltf start at the top left, MikkTSpace expects the bottom left
+	fn tex_coord(&self, v: u32) -> [f32; 2] {
+		[self.tex_coords[v as usize][0], 1f32 - self.tex_coords[v as usize][1]]
+	}
+
+	/// the tangent space of every triangle corner
+	fn tangent_spaces(&self, triangles: &[[u32; 3]]) -> Vec<TangentSpace> {
+		// corners with the same position, normal and texture coordinates are welded to the first vertex holding them,
+		// -0 and 0 being the same
+		let bits = |x: f32| (x + 0f32).to_bits();
+		let mut welded: HashMap<[u32; 8], u32> = HashMap::new();
+		let triangles: Vec<[u32; 3]> = triangles
+			.iter()
+			.map(|t| {
+				t.map(|v| {
+					let [x, y, z] = self.position(v).map(bits);
+					let [nx, ny, nz] = self.normal(v).map(bits);
+					let [s, t] = self.tex_coords[v as usize].map(bits);
+					*welded.entry([x, y, z, nx, ny, nz, s, t]).or_insert(v)
+				})
+			})
+			.collect();
+
+		// triangles with two corners at the same position get spaces from the good triangles after
+		let degenerate = |t: &[u32; 3]| {
+			let [p0, p1, p2] = t.map(|v| self.position(v));
+			p0 == p1 || p0 == p2 || p1 == p2
+		};
+
+		let mut good: Vec<Triangle> = triangles
+			.iter()
+			.enumerate()
+			.filter(|(_, t)| !degenerate(t))
+			.map(|(index, t)| self.triangle(index, *t))
+			.collect();
+
+		Self::build_neighbours(&mut good);
+		let groups = Self::build_groups(&mut good);
+
+		let mut spaces = vec![TangentSpace::default(); triangles.len() * 3];
+		for group in &groups {
+			let n = self.normal(group.vertex);
+
+			// the triangles of the group are split in subgroups of similar tangents, each with its own space
+			let mut subgroups: Vec<(Vec<usize>, TangentSpace)> = vec![];
+			for t in &group.triangles {
+				let triangle = &good[*t];
+				let (os, ot) = (project(triangle.os, n), project(triangle.ot, n));
+
+				let mut members: Vec<usize> = group
+					.triangles
+					.iter()
+					.copied()
+					.filter(|u| {
+						let other = &good[*u];
+						let any = triangle.group_with_any || other.group_with_any;
+						let cos_s = dot(os, project(other.os, n));
+						let cos_t = dot(ot, project(other.ot, n));
+						any || t == u || (cos_s > ANGULAR_THRESHOLD_COS && cos_t > ANGULAR_THRESHOLD_COS)
+					})
+					.collect();
+				members.sort_unstable();
+
+				let space = match subgroups.iter().find(|(m, _)| *m == members) {
+					Some((_, space)) => *space,
+					None => {
+						let space = self.eval_space(&good, &members, group.vertex);
+						subgroups.push((members, space));
+						space
+					}
+				};
+
+				if let Some(corner) = triangle.corner(group.vertex) {
+					spaces[triangle.index * 3 + corner] = TangentSpace { orientation_preserving: group.orientation_preserving, ..space };
+				}
+			}
+		}
+
+		// the corners of degenerate triangles copy the space of the first good corner at the same welded vertex
+		let mut first: HashMap<u32, usize> = HashMap::new();
+		for triangle in &good {
+			for (corner, vertex) in triangle.vertices.iter().enumerate() {
+				first.entry(*vertex).or_insert(triangle.index * 3 + corner);
+			}
+		}
+		for (index, t) in triangles.iter().enumerate().filter(|(_, t)| degenerate(t)) {
+			for (corner, vertex) in t.iter().enumerate() {
+				if let Some(source) = first.get(vertex) {
+					spaces[index * 3 + corner] = spaces[*source];
+				}
+			}
+		}
+
+		spaces
+	}
+
+	/// the tangent, bitangent and magnitudes of the uv mapping of a triangle
+	fn triangle(&self, index: usize, vertices: [u32; 3]) -> Triangle {
+		let [p0, p1, p2] = vertices.map(|v| self.position(v));
+		let [t0, t1, t2] = vertices.map(|v| self.tex_coord(v));
+
+		let (d1, d2) = (sub(p1, p0), sub(p2, p0));
+		let (s1, s2) = ([t1[0] - t0[0], t1[1] - t0[1]], [t2[0] - t0[0], t2[1] - t0[1]]);
+
+		// the uv area is signed, its sign is the orientation of the uv mapping
+		let area = s1[0] * s2[1] - s1[1] * s2[0];
+		let os = sub(scale(d1, s2[1]), scale(d2, s1[1]));
+		let ot = add(scale(d1, -s2[0]), scale(d2, s1[0]));
+
+		let mut triangle = Triangle {
+			index,
+			vertices,
+			neighbours: [None; 3],
+			groups: [None; 3],
+			os: [0f32; 3],
+			ot: [0f32; 3],
+			magnitude_s: 0f32,
+			magnitude_t: 0f32,
+			orientation_preserving: area > 0f32,
+			group_with_any: true,
+		};
+
+		if not_zero(area) {
+			let sign = area.signum();
+			let (length_s, length_t) = (length(os), length(ot));
+			if not_zero(length_s) {
+				triangle.os = scale(os, sign / length_s);
+			}
+			if not_zero(length_t) {
+				triangle.ot = scale(ot, sign / length_t);
+			}
+
+			triangle.magnitude_s = length_s / area.abs();
+			triangle.magnitude_t = length_t / area.abs();
+			triangle.group_with_any = !not_zero(triangle.magnitude_s) || !not_zero(triangle.magnitude_t);
+		}
+
+		triangle
+	}
+
+	/// pairs every edge with the first unpaired edge running the other way between the same welded vertices
+	fn build_neighbours(triangles: &mut [Triangle]) {
+		// (lower vertex, higher vertex, triangle, corner) of every edge, in order
+		let mut edges = Vec::with_capacity(triangles.len() * 3);
+		for (t, triangle) in triangles.iter().enumerate() {
+			for corner in 0..3 {
+				let (a, b) = (triangle.vertices[corner], triangle.vertices[(corner + 1) % 3]);
+				edges.push((a.min(b), a.max(b), t, corner));
+			}
+		}
+		edges.sort_unstable();
+
+		for (e, (low, high, t, corner)) in edges.iter().enumerate() {
+			if triangles[*t].neighbours[*corner].is_some() {
+				continue;
+			}
+
+			let start = triangles[*t].vertices[*corner];
+			let other = edges[e + 1..]
+				.iter()
+				.take_while(|(l, h, ..)| (l, h) == (low, high))
+				.find(|(_, _, u, c)| triangles[*u].neighbours[*c].is_none() && triangles[*u].vertices[(c + 1) % 3] == start);
+
+			if let Some((_, _, u, c)) = other {
+				triangles[*t].neighbours[*corner] = Some(*u);
+				triangles[*u].neighbours[*c] = Some(*t);
+			}
+		}
+	}
+
+	/// groups the corners of every welded vertex, each group starting at the first unassigned corner of a triangle
+	/// with a usable uv mapping
+	fn build_groups(triangles: &mut [Triangle]) -> Vec<Group> {
+		let mut groups = vec![];
+
+		for t in 0..triangles.len() {
+			for corner in 0..3 {
+				let triangle = &mut triangles[t];
+				if triangle.group_with_any || triangle.groups[corner].is_some() {
+					continue;
+				}
+
+				triangle.groups[corner] = Some(groups.len());
+				let neighbours = [triangle.neighbours[corner], triangle.neighbours[(corner + 2) % 3]];
+				let mut group = Group {
+					vertex: triangle.vertices[corner],
+					orientation_preserving: triangle.orientation_preserving,
+					triangles: vec![t],
+				};
+
+				for neighbour in neighbours.into_iter().flatten() {
+					Self::assign(triangles, &mut group, groups.len(), neighbour);
+				}
+				groups.push(group);
+			}
+		}
+
+		groups
+	}
+
+	/// grows a group from a triangle across the edges meeting at the vertex of the group, in the depth first order
+	/// of the reference implementation. a triangle without a usable uv mapping takes the orientation of the first
+	/// group reaching it
+	fn assign(triangles: &mut [Triangle], group: &mut Group, index: usize, start: usize) {
+		let mut stack = vec![start];
+
+		while let Some(t) = stack.pop() {
+			let triangle = &mut triangles[t];
+			let Some(corner) = triangle.corner(group.vertex) else {
+				continue;
+			};
+
+			if triangle.groups[corner].is_some() {
+				continue;
+			}
+			if triangle.group_with_any && triangle.groups == [None; 3] {
+				triangle.orientation_preserving = group.orientation_preserving;
+			}
+			if triangle.orientation_preserving != group.orientation_preserving {
+				continue;
+			}
+
+			group.triangles.push(t);
+			triangle.groups[corner] = Some(index);
+
+			// the neighbour after the corner is walked first
+			stack.extend([triangle.neighbours[(corner + 2) % 3], triangle.neighbours[corner]].into_iter().flatten());
+		}
+	}
+
+	/// the space of a subgroup, the projected tangents, bitangents and magnitudes of its triangles weighted by the
+	/// angle of each triangle at the vertex
+	fn eval_space(&self, triangles: &[Triangle], members: &[usize], vertex: u32) -> TangentSpace {
+		let n = self.normal(vertex);
+		let mut space = TangentSpace {
+			tangent: [0f32; 3],
+			bitangent: [0f32; 3],
+			magnitude_s: 0f32,
+			magnitude_t: 0f32,
+			orientation_preserving: false,
+		};
+		let mut angle_sum = 0f32;
+
+		for triangle in members.iter().map(|t| &triangles[*t]).filter(|t| !t.group_with_any) {
+			let Some(corner) = triangle.corner(vertex) else {
+				continue;
+			};
+
+			let p = self.position(vertex);
+			let previous = project(sub(self.position(triangle.vertices[(corner + 2) % 3]), p), n);
+			let next = project(sub(self.position(triangle.vertices[(corner + 1) % 3]), p), n);
+			let angle = dot(previous, next).clamp(-1f32, 1f32).acos();
+
+			space.tangent = add(space.tangent, scale(project(triangle.os, n), angle));
+			space.bitangent = add(space.bitangent, scale(project(triangle.ot, n), angle));
+			space.magnitude_s += angle * triangle.magnitude_s;
+			space.magnitude_t += angle * triangle.magnitude_t;
+			angle_sum += angle;
+		}
+
+		space.tangent = normalize_not_zero(space.tangent);
+		space.bitangent = normalize_not_zero(space.bitangent);
+		if angle_sum > 0f32 {
+			space.magnitude_s /= angle_sum;
+			space.magnitude_t /= angle_sum;
+		}
+
+		space
+	}
+}
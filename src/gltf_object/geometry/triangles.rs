@@ -0,0 +1,115 @@
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::{GltfObject, GltrError, GltrResult};
+
+/// The triangles of a TRIANGLES, TRIANGLE_STRIP or TRIANGLE_FAN primitive as vertex indices, in the winding order
+/// the primitive is drawn with. see [GltfObject::primitive_triangles]
+#[derive(Debug, Clone)]
+pub struct Triangles {
+	/// None for non-indexed primitives, whose vertices are used in order
+	indices: Option<Vec<u32>>,
+	count: usize,
+	mode: PrimitiveMode,
+	next: usize,
+}
+
+impl Triangles {
+	pub fn new(mode: PrimitiveMode, indices: Option<Vec<u32>>, vertex_count: usize) -> Self {
+		let count = match &indices {
+			None => vertex_count,
+			Some(i) => i.len(),
+		};
+
+		Triangles { indices, count, mode, next: 0 }
+	}
+
+	fn vertex(&self, i: usize) -> u32 {
+		match &self.indices {
+			None => i as u32,
+			Some(indices) => indices[i],
+		}
+	}
+}
+
+impl Iterator for Triangles {
+	type Item = [u32; 3];
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let i = self.next;
+		if i >= self.mode.triangle_count(self.count) {
+			return None;
+		}
+		self.next += 1;
+
+		// 3.7.2.1 of the specification, strips alternate their winding so every triangle faces the same way
+		let corners = match self.mode {
+			PrimitiveMode::TRIANGLE_STRIP => [i, i + 1 + i % 2, i + 2 - i % 2],
+			PrimitiveMode::TRIANGLE_FAN => [i + 1, i + 2, 0],
+			_ => [3 * i, 3 * i + 1, 3 * i + 2],
+		};
+
+		Some(corners.map(|c| self.vertex(c)))
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let remaining = self.mode.triangle_count(self.count) - self.next;
+		(remaining, Some(remaining))
+	}
+}
+
+impl ExactSizeIterator for Triangles {}
+
+impl GltfObject {
+	/// the triangles drawn by a primitive, for indexed and non-indexed primitives of any triangle mode
+	pub fn primitive_triangles(&self, mesh: Index<GltfMesh>, primitive: usize) -> GltrResult<Triangles> {
+		let p = self.primitive(mesh, primitive)?;
+
+		if !p.mode().is_triangles() {
+			return Err(GltrError::ConstraintViolation(format!(
+				"/meshes/{mesh}/primitives/{primitive} has mode {}, which does not draw triangles",
+				p.mode().value()
+			)));
+		}
+
+		let indices = match p.accessor {
+			None => None,
+			Some(accessor) => Some(self.read_accessor_u32(accessor)?),
+		};
+
+		Ok(Triangles::new(p.mode(), indices, self.primitive_vertex_count(mesh, primitive)?))
+	}
+
+	/// converts a TRIANGLE_STRIP or TRIANGLE_FAN primitive to an indexed TRIANGLES primitive, writing a new index
+	/// accessor. degenerate triangles, which strips use to join separate runs, are dropped.
+	/// returns false when the primitive already was a TRIANGLES primitive
+	pub fn triangulate_primitive(&mut self, mesh: Index<GltfMesh>, primitive: usize) -> GltrResult<bool> {
+		let triangles = self.primitive_triangles(mesh, primitive)?;
+		if self.primitive(mesh, primitive)?.mode() == PrimitiveMode::TRIANGLES {
+			return Ok(false);
+		}
+
+		let indices: Vec<u32> = triangles.filter(|[a, b, c]| a != b && b != c && a != c).flatten().collect();
+		let accessor = self.push_accessor_indices(&indices)?;
+
+		let p = self.primitive_mut(mesh, primitive)?;
+		p.accessor = Some(accessor);
+		p.mode = None;
+
+		Ok(true)
+	}
+
+	/// converts every TRIANGLE_STRIP and TRIANGLE_FAN primitive to TRIANGLES, see [GltfObject::triangulate_primitive].
+	/// the index accessors that were replaced stay in the document until it is pruned
+	pub fn triangulate_meshes(&mut self) -> GltrResult<()> {
+		for mesh in self.indices::<GltfMesh>().collect::<Vec<_>>() {
+			for primitive in 0..self.meshes[mesh.value()].primitives.len() {
+				let mode = self.meshes[mesh.value()].primitives[primitive].mode();
+
+				if mode == PrimitiveMode::TRIANGLE_STRIP || mode == PrimitiveMode::TRIANGLE_FAN {
+					self.triangulate_primitive(mesh, primitive)?;
+				}
+			}
+		}
+
+		Ok(())
+	}
+}
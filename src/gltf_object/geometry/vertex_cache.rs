@@ -0,0 +1,217 @@
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::{GltfObject, GltrError, GltrResult};
+use serde_derive::Serialize;
+
+/// the number of vertices of the cache modelled when ordering triangles
+const CACHE_SIZE: usize = 32;
+
+/// the number of entries of the FIFO cache used to measure ACMR, a common size of post-transform caches
+const ACMR_CACHE_SIZE: usize = 16;
+
+/// The average cache miss ratio, vertex shader invocations per triangle, of a primitive before and after
+/// [GltfObject::optimize_vertex_cache]. 3 is the worst case, 0.5 the best a regular grid can do
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct VertexCacheStats {
+	#[serde(rename = "acmrBefore")]
+	pub acmr_before: f32,
+
+	#[serde(rename = "acmrAfter")]
+	pub acmr_after: f32,
+}
+
+/// the average cache miss ratio of drawing `triangles` with a FIFO cache of ACMR_CACHE_SIZE vertices
+fn acmr(triangles: &[[u32; 3]]) -> f32 {
+	if triangles.is_empty() {
+		return 0f32;
+	}
+
+	let mut cache = std::collections::VecDeque::with_capacity(ACMR_CACHE_SIZE);
+	let mut misses = 0;
+	for vertex in triangles.iter().flatten() {
+		if !cache.contains(vertex) {
+			misses += 1;
+			if cache.len() == ACMR_CACHE_SIZE {
+				cache.pop_front();
+			}
+			cache.push_back(*vertex);
+		}
+	}
+
+	misses as f32 / triangles.len() as f32
+}
+
+/// the score of a vertex in Forsyth's "Linear-Speed Vertex Cache Optimisation": vertices in the cache score by
+/// their position in it, the three most recent ones equally since they belong to the last triangle, and vertices
+/// with few triangles left get a boost so they are finished off
+fn vertex_score(cache_position: Option<usize>, remaining: usize) -> f32 {
+	if remaining == 0 {
+		return -1f32;
+	}
+
+	let cache = match cache_position {
+		None => 0f32,
+		Some(p) if p < 3 => 0.75f32,
+		Some(p) => (1f32 - (p - 3) as f32 / (CACHE_SIZE - 3) as f32).powf(1.5f32),
+	};
+
+	cache + 2f32 * (remaining as f32).powf(-0.5f32)
+}
+
+/// reorders `triangles` for the post-transform cache with Forsyth's algorithm
+fn order_triangles(triangles: &[[u32; 3]], vertex_count: usize) -> Vec<[u32; 3]> {
+	// the triangles using every vertex, the ones not emitted yet are first
+	let mut adjacency: Vec<Vec<usize>> = vec![vec![]; vertex_count];
+	for (t, triangle) in triangles.iter().enumerate() {
+		for v in triangle {
+			adjacency[*v as usize].push(t);
+		}
+	}
+
+	let mut remaining: Vec<usize> = adjacency.iter().map(|a| a.len()).collect();
+	let mut scores: Vec<f32> = remaining.iter().map(|r| vertex_score(None, *r)).collect();
+	let mut triangle_scores: Vec<f32> = triangles.iter().map(|t| t.iter().map(|v| scores[*v as usize]).sum()).collect();
+	let mut emitted = vec![false; triangles.len()];
+
+	let mut cache: Vec<u32> = Vec::with_capacity(CACHE_SIZE + 3);
+	let mut order = Vec::with_capacity(triangles.len());
+
+	// the next triangle to look at when no triangle of the cache is left
+	let mut cursor = 0;
+
+	while order.len() < triangles.len() {
+		let mut best: Option<usize> = None;
+		for t in cache.iter().flat_map(|v| &adjacency[*v as usize][..remaining[*v as usize]]) {
+			if best.is_none_or(|b| triangle_scores[*t] > triangle_scores[b]) {
+				best = Some(*t);
+			}
+		}
+
+		let best = match best {
+			Some(t) => t,
+			None => {
+				while emitted[cursor] {
+					cursor += 1;
+				}
+				cursor
+			}
+		};
+
+		emitted[best] = true;
+		order.push(triangles[best]);
+
+		// the triangle is moved past the ones left to emit of every vertex
+		for v in triangles[best] {
+			let v = v as usize;
+			let at = adjacency[v][..remaining[v]].iter().position(|t| *t == best).unwrap_or_default();
+			adjacency[v].swap(at, remaining[v] - 1);
+			remaining[v] -= 1;
+		}
+
+		// the vertices of the triangle move to the front of the cache
+		let mut updated: Vec<u32> = Vec::with_capacity(CACHE_SIZE + 3);
+		for v in triangles[best].iter().chain(&cache) {
+			if !updated.contains(v) {
+				updated.push(*v);
+			}
+		}
+		let evicted = updated.split_off(updated.len().min(CACHE_SIZE));
+		cache = updated;
+
+		for v in &evicted {
+			scores[*v as usize] = vertex_score(None, remaining[*v as usize]);
+		}
+		for (position, v) in cache.iter().enumerate() {
+			scores[*v as usize] = vertex_score(Some(position), remaining[*v as usize]);
+		}
+
+		for v in cache.iter().chain(&evicted) {
+			for t in &adjacency[*v as usize][..remaining[*v as usize]] {
+				triangle_scores[*t] = triangles[*t].iter().map(|v| scores[*v as usize]).sum();
+			}
+		}
+	}
+
+	order
+}
+
+impl GltfObject {
+	/// reorders the triangles of a primitive so the vertices of consecutive triangles hit the post-transform cache,
+	/// then reorders the vertices in the order the triangles first use them so they are fetched sequentially.
+	///
+	/// strips and fans are converted to TRIANGLES, the primitive becomes indexed if it wasn't and every attribute
+	/// is rewritten, the replaced accessors stay in the document until it is pruned
+	pub fn optimize_primitive_vertex_cache(&mut self, mesh: Index<GltfMesh>, primitive: usize) -> GltrResult<VertexCacheStats> {
+		let triangles: Vec<[u32; 3]> = self.primitive_triangles(mesh, primitive)?.collect();
+		let vertex_count = self.primitive_vertex_count(mesh, primitive)?;
+
+		if let Some(i) = triangles.iter().flatten().find(|i| **i as usize >= vertex_count) {
+			return Err(GltrError::ConstraintViolation(format!(
+				"/meshes/{mesh}/primitives/{primitive} uses vertex {i}, POSITION has {vertex_count} vertices"
+			)));
+		}
+
+		let ordered = order_triangles(&triangles, vertex_count);
+
+		// vertices in first use order, the ones no triangle uses are dropped
+		let mut remap: Vec<Option<u32>> = vec![None; vertex_count];
+		let mut sources = vec![];
+		let mut indices = Vec::with_capacity(ordered.len() * 3);
+		for v in ordered.iter().flatten() {
+			let index = *remap[*v as usize].get_or_insert_with(|| {
+				sources.push(*v);
+				sources.len() as u32 - 1
+			});
+			indices.push(index);
+		}
+
+		let stats = VertexCacheStats {
+			acmr_before: acmr(&triangles),
+			acmr_after: acmr(&ordered),
+		};
+
+		// indices that were UNSIGNED_BYTE, eg after welding, stay as small as they were
+		let compact = match self.primitive(mesh, primitive)?.accessor {
+			None => false,
+			Some(accessor) => self.try_get(accessor)?.component_type == ComponentType::UNSIGNED_BYTE,
+		};
+		let indices = if compact {
+			self.push_accessor_indices_compact(&indices)?
+		} else {
+			self.push_accessor_indices(&indices)?
+		};
+
+		self.remap_vertices(mesh, primitive, &sources, indices)?;
+		self.primitive_mut(mesh, primitive)?.mode = None;
+
+		Ok(stats)
+	}
+
+	/// optimizes every triangle primitive of every mesh, see [GltfObject::optimize_primitive_vertex_cache].
+	/// returns the ACMR over all of them, weighted by their number of triangles
+	pub fn optimize_vertex_cache(&mut self) -> GltrResult<VertexCacheStats> {
+		let mut total = VertexCacheStats::default();
+		let mut triangle_count = 0;
+
+		for mesh in self.indices::<GltfMesh>().collect::<Vec<_>>() {
+			for primitive in 0..self.meshes[mesh.value()].primitives.len() {
+				if !self.meshes[mesh.value()].primitives[primitive].mode().is_triangles() {
+					continue;
+				}
+
+				let count = self.primitive_triangles(mesh, primitive)?.len();
+				let stats = self.optimize_primitive_vertex_cache(mesh, primitive)?;
+
+				total.acmr_before += stats.acmr_before * count as f32;
+				total.acmr_after += stats.acmr_after * count as f32;
+				triangle_count += count;
+			}
+		}
+
+		if triangle_count > 0 {
+			total.acmr_before /= triangle_count as f32;
+			total.acmr_after /= triangle_count as f32;
+		}
+
+		Ok(total)
+	}
+}
@@ -0,0 +1,158 @@
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::{GltfObject, GltrError, GltrResult};
+use std::collections::HashMap;
+
+/// Options of [GltfObject::weld_primitive]
+#[derive(Debug, Clone, Copy)]
+pub struct WeldOptions {
+	/// the largest difference between two components of POSITION, NORMAL or TEXCOORD_n (and their morph target
+	/// displacements) for the vertices to still be welded. every other attribute has to match exactly
+	pub epsilon: f32,
+}
+
+impl Default for WeldOptions {
+	fn default() -> Self {
+		WeldOptions { epsilon: 0f32 }
+	}
+}
+
+/// the values of one attribute or morph target of every vertex
+struct Attribute {
+	values: Vec<f32>,
+	components: usize,
+	tolerant: bool,
+}
+
+impl Attribute {
+	fn element(&self, vertex: usize) -> &[f32] {
+		&self.values[vertex * self.components..(vertex + 1) * self.components]
+	}
+}
+
+impl GltfObject {
+	/// merges the vertices of a primitive whose attributes are all equal, see [WeldOptions], and writes an index
+	/// accessor with the smallest component type that fits, see [GltfObject::push_accessor_indices_compact].
+	///
+	/// the attributes are rewritten with only the vertices that are drawn, each welded vertex keeping the values of
+	/// the first of its copies. the mode of the primitive is kept. returns the number of vertices removed
+	pub fn weld_primitive(&mut self, mesh: Index<GltfMesh>, primitive: usize, options: WeldOptions) -> GltrResult<usize> {
+		let vertex_count = self.primitive_vertex_count(mesh, primitive)?;
+		let p = self.primitive(mesh, primitive)?;
+
+		let old_indices: Vec<u32> = match p.accessor {
+			None => (0..vertex_count as u32).collect(),
+			Some(accessor) => self.read_accessor_u32(accessor)?,
+		};
+
+		if let Some(i) = old_indices.iter().find(|i| **i as usize >= vertex_count) {
+			return Err(GltrError::ConstraintViolation(format!(
+				"/meshes/{mesh}/primitives/{primitive} uses vertex {i}, POSITION has {vertex_count} vertices"
+			)));
+		}
+
+		let tolerant = |semantic: &str| semantic == "POSITION" || semantic == "NORMAL" || semantic.starts_with("TEXCOORD_");
+		let semantics = p.attributes.iter().chain(p.targets.iter().flatten().flatten());
+
+		let mut attributes = vec![];
+		for (semantic, accessor) in semantics {
+			let a = self.try_get(*accessor)?;
+			if a.count < vertex_count {
+				return Err(GltrError::ConstraintViolation(format!(
+					"/meshes/{mesh}/primitives/{primitive} has {vertex_count} vertices but its {semantic} accessor {accessor} has {}",
+					a.count
+				)));
+			}
+
+			attributes.push(Attribute {
+				values: self.read_accessor_f32(*accessor)?,
+				components: a.component_count(),
+				tolerant: tolerant(semantic),
+			});
+		}
+
+		let position = self.read_accessor_array::<3>(p.attributes["POSITION"])?;
+
+		let epsilon = options.epsilon.max(0f32);
+		let equal = |a: usize, b: usize| {
+			attributes.iter().all(|attribute| {
+				attribute.element(a).iter().zip(attribute.element(b)).all(|(x, y)| {
+					if attribute.tolerant {
+						(x - y).abs() <= epsilon
+					} else {
+						x.to_bits() == y.to_bits()
+					}
+				})
+			})
+		};
+
+		// vertices are looked up by position in a grid of epsilon sized cells, a vertex within epsilon of another
+		// is in the same or a neighbouring cell
+		let cell = |p: [f32; 3]| {
+			if epsilon > 0f32 {
+				p.map(|x| (x / epsilon).floor() as i64)
+			} else {
+				p.map(|x| x.to_bits() as i64)
+			}
+		};
+		let neighbours = if epsilon > 0f32 { -1..=1 } else { 0..=0 };
+
+		let mut grid: HashMap<[i64; 3], Vec<u32>> = HashMap::new();
+		let mut remap: Vec<Option<u32>> = vec![None; vertex_count];
+		let mut sources: Vec<u32> = vec![];
+
+		for vertex in old_indices.iter().map(|i| *i as usize) {
+			if remap[vertex].is_some() {
+				continue;
+			}
+
+			let [x, y, z] = cell(position[vertex]);
+			let mut found = None;
+			'search: for dx in neighbours.clone() {
+				for dy in neighbours.clone() {
+					for dz in neighbours.clone() {
+						let candidates = grid.get(&[x + dx, y + dy, z + dz]).into_iter().flatten();
+						for candidate in candidates {
+							if equal(sources[*candidate as usize] as usize, vertex) {
+								found = Some(*candidate);
+								break 'search;
+							}
+						}
+					}
+				}
+			}
+
+			remap[vertex] = Some(match found {
+				Some(welded) => welded,
+				None => {
+					sources.push(vertex as u32);
+					grid.entry([x, y, z]).or_default().push(sources.len() as u32 - 1);
+					sources.len() as u32 - 1
+				}
+			});
+		}
+
+		let already_compact = p.accessor.is_some() && sources.len() == vertex_count;
+		if already_compact && sources.iter().enumerate().all(|(i, s)| i as u32 == *s) {
+			return Ok(0);
+		}
+
+		let indices: Vec<u32> = old_indices.iter().filter_map(|i| remap[*i as usize]).collect();
+		let indices = self.push_accessor_indices_compact(&indices)?;
+		self.remap_vertices(mesh, primitive, &sources, indices)?;
+
+		Ok(vertex_count - sources.len())
+	}
+
+	/// welds every primitive of every mesh, see [GltfObject::weld_primitive]. returns the number of vertices removed
+	pub fn weld_meshes(&mut self, options: WeldOptions) -> GltrResult<usize> {
+		let mut removed = 0;
+
+		for mesh in self.indices::<GltfMesh>().collect::<Vec<_>>() {
+			for primitive in 0..self.meshes[mesh.value()].primitives.len() {
+				removed += self.weld_primitive(mesh, primitive, options)?;
+			}
+		}
+
+		Ok(removed)
+	}
+}
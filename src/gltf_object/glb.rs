@@ -0,0 +1,150 @@
+use crate::buffers::GltfBase64Buffer;
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::{GltfObject, GltrError, GltrResult};
+
+const GLB_MAGIC: u32 = 0x46546C67;
+const GLB_VERSION: u32 = 2;
+const CHUNK_JSON: u32 = 0x4E4F534A;
+const CHUNK_BIN: u32 = 0x004E4942;
+const HEADER_LENGTH: usize = 12;
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+	let b = bytes.get(offset..offset + 4)?;
+	Some(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn invalid_glb(message: &str) -> GltrError {
+	GltrError::ConstraintViolation(format!("invalid GLB file: {message}"))
+}
+
+/// true when `bytes` starts with the magic of a GLB file
+pub(crate) fn is_glb(bytes: &[u8]) -> bool {
+	read_u32(bytes, 0) == Some(GLB_MAGIC)
+}
+
+impl GltfObject {
+	/// parses a binary glTF file. the binary chunk, when there is one, becomes the data of the first buffer
+	pub fn parse_glb(bytes: &[u8]) -> GltrResult<Self> {
+		if !is_glb(bytes) {
+			return Err(invalid_glb("missing glTF magic"));
+		}
+
+		match read_u32(bytes, 4) {
+			Some(GLB_VERSION) => {}
+			Some(version) => return Err(invalid_glb(&format!("unsupported container version {version}"))),
+			None => return Err(invalid_glb("truncated header")),
+		}
+
+		let length = match read_u32(bytes, 8) {
+			None => return Err(invalid_glb("truncated header")),
+			Some(length) => (length as usize).min(bytes.len()),
+		};
+
+		let mut json = None;
+		let mut bin = None;
+		let mut offset = HEADER_LENGTH;
+
+		while offset + 8 <= length {
+			let chunk_length = read_u32(bytes, offset).unwrap_or_default() as usize;
+			let chunk_type = read_u32(bytes, offset + 4).unwrap_or_default();
+
+			let data = match bytes.get(offset + 8..offset + 8 + chunk_length) {
+				None => return Err(invalid_glb("chunk extends past the end of the file")),
+				Some(data) => data,
+			};
+
+			match chunk_type {
+				CHUNK_JSON if json.is_none() => json = Some(data),
+				CHUNK_BIN if bin.is_none() => bin = Some(data),
+				// unknown chunks must be ignored
+				_ => {}
+			}
+
+			offset += 8 + chunk_length.next_multiple_of(4);
+		}
+
+		let mut object: GltfObject = match json {
+			None => return Err(invalid_glb("missing JSON chunk")),
+			Some(json) => serde_json::from_slice(json)?,
+		};
+
+		if let (Some(bin), Some(buffer)) = (bin, object.buffers.0.first_mut()) {
+			if buffer.uri.is_none() {
+				match bin.get(..buffer.byte_length) {
+					None => {
+						return Err(GltrError::BufferLength {
+							expected: buffer.byte_length,
+							actual: bin.len(),
+						})
+					}
+					Some(data) => buffer.set_bytes(data),
+				}
+			}
+		}
+
+		Ok(object)
+	}
+
+	/// writes the document as a binary glTF file, with every buffer combined into the binary chunk
+	pub fn to_glb(&self) -> GltrResult<Vec<u8>> {
+		let mut object = self.clone();
+		let bin = object.combine_buffers()?;
+
+		let mut json = serde_json::to_vec(&object)?;
+		json.resize(json.len().next_multiple_of(4), b' ');
+
+		let mut chunks = vec![(CHUNK_JSON, json)];
+		if !bin.is_empty() {
+			let mut bin = bin;
+			bin.resize(bin.len().next_multiple_of(4), 0);
+			chunks.push((CHUNK_BIN, bin));
+		}
+
+		let length = HEADER_LENGTH + chunks.iter().map(|(_, data)| 8 + data.len()).sum::<usize>();
+
+		let mut out = Vec::with_capacity(length);
+		for value in [GLB_MAGIC, GLB_VERSION, length as u32] {
+			out.extend_from_slice(&value.to_le_bytes());
+		}
+		for (chunk_type, data) in chunks {
+			out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+			out.extend_from_slice(&chunk_type.to_le_bytes());
+			out.extend_from_slice(&data);
+		}
+
+		Ok(out)
+	}
+
+	/// concatenates every buffer into one, 4 byte aligned, pointing every buffer view at it. returns its bytes,
+	/// the single remaining buffer has no uri set yet
+	pub(crate) fn combine_buffers(&mut self) -> GltrResult<Vec<u8>> {
+		let mut bytes = Vec::new();
+		let mut offsets = Vec::with_capacity(self.buffers.0.len());
+
+		for buffer in &self.buffers.0 {
+			bytes.resize(bytes.len().next_multiple_of(4), 0);
+			offsets.push(bytes.len());
+			bytes.extend_from_slice(&buffer.bytes()?);
+		}
+
+		for (i, view) in self.buffer_views.iter_mut().enumerate() {
+			match offsets.get(view.buffer.value()) {
+				None => return Err(GltrError::invalid_index(format!("/bufferViews/{i}/buffer"), view.buffer.value())),
+				Some(offset) => view.byte_offset += offset,
+			}
+			view.buffer = Index::new(0);
+		}
+
+		self.buffers.0 = match bytes.is_empty() {
+			true => Vec::new(),
+			false => {
+				let mut buffer = GltfBase64Buffer::from_bytes(&[]);
+				buffer.byte_length = bytes.len();
+				buffer.uri = None;
+				vec![buffer]
+			}
+		};
+
+		Ok(bytes)
+	}
+}
@@ -1,16 +1,17 @@
+use crate::buffers::GltfBufferView;
 use crate::gltf_object::prelude::*;
 use serde_derive::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GltfAccessor {
-	#[serde(rename = "bufferView")]
-	pub buffer_view: Option<usize>,
+	#[serde(rename = "bufferView", skip_serializing_if = "Option::is_none")]
+	pub buffer_view: Option<Index<GltfBufferView>>,
 
-	#[serde(rename = "byteOffset")]
+	#[serde(rename = "byteOffset", skip_serializing_if = "Option::is_none")]
 	pub byte_offset: Option<usize>,
 
 	#[serde(rename = "componentType")]
-	pub component_type: usize, //todo check valid values? current tests use 5126
+	pub component_type: ComponentType,
 
 	#[serde(default = "bool::default")]
 	pub normalized: bool,
@@ -19,19 +20,25 @@ pub struct GltfAccessor {
 
 	pub r#type: String,
 
-	#[serde(default = "Vec::new")]
+	#[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
 	pub max: Vec<f32>,
-	#[serde(default = "Vec::new")]
+	#[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
 	pub min: Vec<f32>,
 
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub sparse: Option<GltfAccessorSparse>,
 
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub name: Option<String>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extensions: Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extras: Extras,
 
-	#[serde(skip)]
-	pub(crate) original_index: Option<usize>,
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
 }
 
 
@@ -40,36 +47,87 @@ pub struct GltfAccessorSparse {
 	pub count: usize,
 	pub indices: GltfAccessorSparseIndices,
 	pub values: GltfAccessorSparseValues,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extensions: Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extras: Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
 }
 
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GltfAccessorSparseIndices {
 	#[serde(rename = "bufferView")]
-	pub buffer_view: usize,
+	pub buffer_view: Index<GltfBufferView>,
 
-	#[serde(rename = "byteOffset")]
+	#[serde(rename = "byteOffset", skip_serializing_if = "Option::is_none")]
 	pub byte_offset: Option<usize>,
 
 	#[serde(rename = "componentType")]
-	pub component_type: usize,
+	pub component_type: ComponentType,
 
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extras: Extras,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extensions: Extensions,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
 }
 
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GltfAccessorSparseValues {
-	#[serde(rename = "bufferView")]
-	buffer_view: Option<usize>,
+	#[serde(rename = "bufferView", skip_serializing_if = "Option::is_none")]
+	pub buffer_view: Option<Index<GltfBufferView>>,
+
+	#[serde(rename = "byteOffset", skip_serializing_if = "Option::is_none")]
+	pub byte_offset: Option<usize>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras: Extras,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extensions: Extensions,
 
-	#[serde(rename = "byteOffset")]
-	byte_offset: Option<usize>,
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
+}
 
-	extras: Extras,
 
-	extensions: Extensions,
+//https://registry.khronos.org/glTF/specs/2.0/glTF-2.0.pdf see 5.1.3 accessor.componentType
+//enum
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ComponentType(usize);
+impl ComponentType {
+	pub const BYTE: ComponentType = ComponentType(5120);
+	pub const UNSIGNED_BYTE: ComponentType = ComponentType(5121);
+	pub const SHORT: ComponentType = ComponentType(5122);
+	pub const UNSIGNED_SHORT: ComponentType = ComponentType(5123);
+	pub const UNSIGNED_INT: ComponentType = ComponentType(5125);
+	pub const FLOAT: ComponentType = ComponentType(5126);
+
+	pub fn value(&self) -> usize {
+		self.0
+	}
+
+	pub fn is_valid(&self) -> bool {
+		let v = self.0;
+		matches!(v, 5120..=5123 | 5125 | 5126)
+	}
+
+	/// size of a single component in bytes, 0 for invalid component types
+	pub fn byte_size(&self) -> usize {
+		match self.0 {
+			5120 | 5121 => 1,
+			5122 | 5123 => 2,
+			5125 | 5126 => 4,
+			_ => 0,
+		}
+	}
 }
@@ -1,47 +1,77 @@
 use serde_derive::{Deserialize, Serialize};
-use crate::prelude::{Extensions, Extras};
+use crate::gltf_object::prelude::*;
 
 use crate::defaults::*;
 
 #[derive(Deserialize, Serialize, Debug,Clone)]
 pub struct GltfAnimation {
 	pub channels: Vec<GltfAnimationChannel>,
-	pub sampler: Vec<GltfAnimationSampler>,
+	pub samplers: Vec<GltfAnimationSampler>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub name:Option<String>,
 
 
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extensions:Extensions,
-	pub extras:Extras
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras:Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GltfAnimationChannel {
 
-	pub sampler:usize,
+	/// index into the samplers of the animation holding the channel
+	pub sampler:Index<GltfAnimationSampler>,
 	pub target: GltfAnimationChannelTarget,
 
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extensions:Extensions,
-	pub extras:Extras
 
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras:Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GltfAnimationChannelTarget {
-	pub node:Option<usize>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub node:Option<Index<GltfNode>>,
 	pub path:String,
 
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extensions:Extensions,
-	pub extras:Extras
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras:Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
 }
 
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GltfAnimationSampler {
 
-	pub input:usize,
+	pub input:Index<GltfAccessor>,
 
 	#[serde(default="animation_sampler_default_interpolation")]
 	pub interpolation:String,
 
-	pub output:usize
+	pub output:Index<GltfAccessor>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extensions:Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras:Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
 }
\ No newline at end of file
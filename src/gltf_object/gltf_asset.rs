@@ -1,7 +1,28 @@
+use crate::gltf_object::extras::{Extensions, Extras, UnknownFields};
 use serde_derive::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct GltfAsset {
-	pub generator: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub copyright: Option<String>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub generator: Option<String>,
+
+	/// the glTF version the asset targets, "2.0"
 	pub version: String,
-}
\ No newline at end of file
+
+	/// the minimum glTF version a loader must support to load the asset, never greater than version
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub min_version: Option<String>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extensions: Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras: Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
+}
@@ -1,50 +1,78 @@
 use serde_derive::{Deserialize, Serialize};
-use crate::prelude::{Extensions, Extras};
+use crate::prelude::{Extensions, Extras, UnknownFields};
 
 #[derive(Deserialize, Serialize, Debug,Clone)]
 pub struct GltfCamera {
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub orthographic:Option<GltfCameraOrthographic>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub perspective:Option<GltfCameraPerspective>,
 
+	/// "perspective" or "orthographic", naming which of the two projections is defined
 	#[serde(rename="type")]
 	pub camera_type: String,
 
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub name:Option<String>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extensions:Extensions,
-	pub extras:Extras
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras:Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GltfCameraOrthographic {
 
 	#[serde(rename="xmag")]
-	pub x_magnitude:usize,
+	pub x_magnitude:f32,
 
 	#[serde(rename="ymag")]
-	pub y_magnitude:usize,
+	pub y_magnitude:f32,
 
 	#[serde(rename="zfar")]
-	pub z_far:usize,
+	pub z_far:f32,
 
 	#[serde(rename="znear")]
-	pub z_near:usize,
+	pub z_near:f32,
 
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extensions:Extensions,
-	pub extras:Extras
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras:Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GltfCameraPerspective {
-	#[serde(rename="aspectRatio")]
-	pub aspect_ratio:Option<usize>,
+	#[serde(rename="aspectRatio", skip_serializing_if = "Option::is_none")]
+	pub aspect_ratio:Option<f32>,
 
+	/// vertical field of view in radians
 	#[serde(rename="yfov")]
-	pub y_fov: usize,
-	#[serde(rename="zfar")]
-	pub z_far:Option<usize>,
+	pub y_fov: f32,
+
+	/// undefined for an infinite projection
+	#[serde(rename="zfar", skip_serializing_if = "Option::is_none")]
+	pub z_far:Option<f32>,
+
 	#[serde(rename="znear")]
-	pub z_near:usize,
+	pub z_near:f32,
 
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extensions:Extensions,
-	pub extras:Extras
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras:Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
 }
\ No newline at end of file
@@ -1,20 +1,27 @@
-use crate::gltf_object::extras::{Extensions, Extras};
+use crate::buffers::GltfBufferView;
+use crate::gltf_object::prelude::*;
 use serde_derive::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
 pub struct GltfImage {
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub uri: Option<String>,
 
-	#[serde(rename = "mimeType")]
+	#[serde(rename = "mimeType", skip_serializing_if = "Option::is_none")]
 	pub mime_type: Option<String>,
 
-	#[serde(rename = "bufferView")]
-	pub buffer_view: Option<usize>,
+	#[serde(rename = "bufferView", skip_serializing_if = "Option::is_none")]
+	pub buffer_view: Option<Index<GltfBufferView>>,
 
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub name: Option<String>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extensions: Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extras: Extras,
 
-	#[serde(skip)]
-	pub(crate) original_index: Option<usize>,
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
 }
\ No newline at end of file
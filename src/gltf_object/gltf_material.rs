@@ -1,85 +1,200 @@
 use serde_derive::{Deserialize, Serialize};
-use crate::prelude::{Extensions, Extras};
+use crate::gltf_object::prelude::*;
 
 use crate::defaults::*;
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct GltfMaterial {
-	name: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub name: Option<String>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub pbr_metallic_roughness: Option<GltfMetallicRoughness>,
 
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub normal_texture:Option<GltfNormalTexture>,
 
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub occlusion_texture:Option<GltfOcclusionTexture>,
 
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub emissive_texture:Option<GltfEmissiveTexture>,
 
-	pub emissive_factor: Option<[usize; 3]>,
+	#[serde(default="default_f32_vec_0")]
+	pub emissive_factor: [f32; 3],
 
+	/// "OPAQUE", "MASK" or "BLEND"
 	#[serde(default="material_default_alpha_mode")]
 	pub alpha_mode:String,
 
+	/// only used when alpha_mode is "MASK"
+	#[serde(default="material_default_alpha_cutoff")]
 	pub alpha_cutoff:f32,
 
+	#[serde(default="bool::default")]
+	pub double_sided:bool,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extensions:Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extras:Extras,
 
-	#[serde(skip)]
-	pub(crate) original_index: Option<usize>,
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
 }
 
 
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct GltfNormalTexture {
+	#[serde(rename = "index")]
+	pub texture_index: Index<GltfTexture>,
+
+	#[serde(default="default_0")]
+	pub tex_coord:usize,
+
+	#[serde(default="default_1f")]
+	pub scale:f32,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extensions:Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras:Extras,
 
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct GltfOcclusionTexture {
+	#[serde(rename = "index")]
+	pub texture_index: Index<GltfTexture>,
 
-}
+	#[serde(default="default_0")]
+	pub tex_coord:usize,
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
-pub struct GltfEmissiveTexture {
+	#[serde(default="default_1f")]
+	pub strength:f32,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extensions:Extensions,
 
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras:Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
 }
 
+/// the emissive texture is a plain textureInfo
+pub type GltfEmissiveTexture = GltfTextureInfo;
+
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct GltfMetallicRoughness {
 	
 	#[serde(default="default_f32_vec4_1")]
 	pub base_color_factor:[f32;4],
 	
-	#[serde(rename = "baseColorTexture")]
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub base_color_texture: Option<GltfTextureInfo>,
-	pub metallic_factor: Option<f32>,
-	pub roughness_factor:Option<f32>,
+
+	#[serde(default="default_1f")]
+	pub metallic_factor: f32,
+
+	#[serde(default="default_1f")]
+	pub roughness_factor:f32,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub metallic_roughness_texture:Option<GltfTextureInfo>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extensions:Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extras:Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
 }
 
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct GltfTextureInfo {
 	#[serde(rename = "index")]
-	pub texture_index: usize,
+	pub texture_index: Index<GltfTexture>,
 	
 	#[serde(default="default_0")]
 	pub tex_coord:usize,
 
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extensions:Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extras:Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
+}
+
+
+impl Default for GltfMaterial {
+	fn default() -> Self {
+		GltfMaterial {
+			name: None,
+			pbr_metallic_roughness: None,
+			normal_texture: None,
+			occlusion_texture: None,
+			emissive_texture: None,
+			emissive_factor: default_f32_vec_0(),
+			alpha_mode: material_default_alpha_mode(),
+			alpha_cutoff: material_default_alpha_cutoff(),
+			double_sided: false,
+			extensions: None,
+			extras: None,
+			unknown_fields: UnknownFields::new(),
+		}
+	}
 }
 
+impl Default for GltfMetallicRoughness {
+	fn default() -> Self {
+		GltfMetallicRoughness {
+			base_color_factor: default_f32_vec4_1(),
+			base_color_texture: None,
+			metallic_factor: default_1f(),
+			roughness_factor: default_1f(),
+			metallic_roughness_texture: None,
+			extensions: None,
+			extras: None,
+			unknown_fields: UnknownFields::new(),
+		}
+	}
+}
+
+impl GltfTextureInfo {
+	/// a reference to the texture at `texture_index`, sampled with TEXCOORD_0
+	pub fn new(texture_index: Index<GltfTexture>) -> Self {
+		GltfTextureInfo {
+			texture_index,
+			tex_coord: 0,
+			extensions: None,
+			extras: None,
+			unknown_fields: UnknownFields::new(),
+		}
+	}
+}
 
 impl GltfMaterial {
-	pub fn get_texture_index(&self) -> Option<usize> {
-		if self.pbr_metallic_roughness.is_some() {
-			let pmr = self.pbr_metallic_roughness.as_ref().unwrap();
+	pub fn get_texture_index(&self) -> Option<Index<GltfTexture>> {
+		if let Some(pmr) = &self.pbr_metallic_roughness {
 			let texture = &pmr.base_color_texture;
 			if texture.is_some() {
 				let idx = texture.as_ref()?.texture_index;
@@ -88,4 +203,169 @@ impl GltfMaterial {
 		}
 		None
 	}
+}
+
+
+/// the texCoord and extensions of one of a material's textures, whichever kind of textureInfo holds them
+pub struct GltfTextureSlot<'a> {
+	/// JSON pointer of the textureInfo relative to the material, eg `pbrMetallicRoughness/baseColorTexture`
+	pub pointer: &'static str,
+	pub texture_index: &'a mut Index<GltfTexture>,
+	pub tex_coord: &'a mut usize,
+	pub extensions: &'a mut Extensions,
+}
+
+impl Extensible for GltfTextureSlot<'_> {
+	fn extensions(&self) -> &Extensions {
+		self.extensions
+	}
+
+	fn extensions_mut(&mut self) -> &mut Extensions {
+		self.extensions
+	}
+}
+
+impl<'a> GltfTextureSlot<'a> {
+	fn new(pointer: &'static str, texture_index: &'a mut Index<GltfTexture>, tex_coord: &'a mut usize, extensions: &'a mut Extensions) -> Self {
+		GltfTextureSlot { pointer, texture_index, tex_coord, extensions }
+	}
+}
+
+/// a read-only [GltfTextureSlot]
+pub struct GltfTextureSlotRef<'a> {
+	/// JSON pointer of the textureInfo relative to the material, eg `pbrMetallicRoughness/baseColorTexture`
+	pub pointer: &'static str,
+	pub texture_index: Index<GltfTexture>,
+	pub tex_coord: usize,
+	pub extensions: &'a Extensions,
+}
+
+impl<'a> GltfTextureSlotRef<'a> {
+	fn new(pointer: &'static str, texture_index: Index<GltfTexture>, tex_coord: usize, extensions: &'a Extensions) -> Self {
+		GltfTextureSlotRef { pointer, texture_index, tex_coord, extensions }
+	}
+
+	pub fn extension<T: GltfExtensionType>(&self) -> Option<&'a T> {
+		self.extensions.as_ref()?.get::<T>()
+	}
+
+	pub fn has_extension(&self, name: &str) -> bool {
+		self.extensions.as_ref().is_some_and(|x| x.contains(name))
+	}
+}
+
+impl GltfMaterial {
+	/// every texture referenced by the material, including those in known material extensions
+	pub fn texture_slots(&self) -> Vec<GltfTextureSlotRef<'_>> {
+		fn push<'a>(pointer: &'static str, info: &'a Option<GltfTextureInfo>, slots: &mut Vec<GltfTextureSlotRef<'a>>) {
+			if let Some(t) = info {
+				slots.push(GltfTextureSlotRef::new(pointer, t.texture_index, t.tex_coord, &t.extensions));
+			}
+		}
+
+		let mut slots = Vec::new();
+
+		if let Some(pmr) = &self.pbr_metallic_roughness {
+			push("pbrMetallicRoughness/baseColorTexture", &pmr.base_color_texture, &mut slots);
+			push("pbrMetallicRoughness/metallicRoughnessTexture", &pmr.metallic_roughness_texture, &mut slots);
+		}
+
+		if let Some(t) = &self.normal_texture {
+			slots.push(GltfTextureSlotRef::new("normalTexture", t.texture_index, t.tex_coord, &t.extensions));
+		}
+
+		if let Some(t) = &self.occlusion_texture {
+			slots.push(GltfTextureSlotRef::new("occlusionTexture", t.texture_index, t.tex_coord, &t.extensions));
+		}
+
+		push("emissiveTexture", &self.emissive_texture, &mut slots);
+
+		if let Some(extensions) = &self.extensions {
+			for extension in extensions.0.values() {
+				match extension {
+					GltfExtension::KhrMaterialsClearcoat(c) => {
+						push("extensions/KHR_materials_clearcoat/clearcoatTexture", &c.clearcoat_texture, &mut slots);
+						push("extensions/KHR_materials_clearcoat/clearcoatRoughnessTexture", &c.clearcoat_roughness_texture, &mut slots);
+						if let Some(t) = &c.clearcoat_normal_texture {
+							slots.push(GltfTextureSlotRef::new(
+								"extensions/KHR_materials_clearcoat/clearcoatNormalTexture",
+								t.texture_index,
+								t.tex_coord,
+								&t.extensions,
+							));
+						}
+					}
+					GltfExtension::KhrMaterialsTransmission(t) => {
+						push("extensions/KHR_materials_transmission/transmissionTexture", &t.transmission_texture, &mut slots);
+					}
+					GltfExtension::KhrMaterialsSpecular(s) => {
+						push("extensions/KHR_materials_specular/specularTexture", &s.specular_texture, &mut slots);
+						push("extensions/KHR_materials_specular/specularColorTexture", &s.specular_color_texture, &mut slots);
+					}
+					_ => {}
+				}
+			}
+		}
+
+		slots
+	}
+
+	/// [GltfMaterial::texture_slots], with the index, texCoord and extensions of each texture writable
+	pub fn texture_slots_mut(&mut self) -> Vec<GltfTextureSlot<'_>> {
+		fn push<'a>(pointer: &'static str, info: &'a mut Option<GltfTextureInfo>, slots: &mut Vec<GltfTextureSlot<'a>>) {
+			if let Some(t) = info {
+				slots.push(GltfTextureSlot::new(pointer, &mut t.texture_index, &mut t.tex_coord, &mut t.extensions));
+			}
+		}
+
+		let mut slots = Vec::new();
+
+		if let Some(pmr) = &mut self.pbr_metallic_roughness {
+			push("pbrMetallicRoughness/baseColorTexture", &mut pmr.base_color_texture, &mut slots);
+			push("pbrMetallicRoughness/metallicRoughnessTexture", &mut pmr.metallic_roughness_texture, &mut slots);
+		}
+
+		if let Some(t) = &mut self.normal_texture {
+			slots.push(GltfTextureSlot::new("normalTexture", &mut t.texture_index, &mut t.tex_coord, &mut t.extensions));
+		}
+
+		if let Some(t) = &mut self.occlusion_texture {
+			slots.push(GltfTextureSlot::new("occlusionTexture", &mut t.texture_index, &mut t.tex_coord, &mut t.extensions));
+		}
+
+		push("emissiveTexture", &mut self.emissive_texture, &mut slots);
+
+		if let Some(extensions) = &mut self.extensions {
+			for extension in extensions.0.values_mut() {
+				match extension {
+					GltfExtension::KhrMaterialsClearcoat(c) => {
+						push("extensions/KHR_materials_clearcoat/clearcoatTexture", &mut c.clearcoat_texture, &mut slots);
+						push(
+							"extensions/KHR_materials_clearcoat/clearcoatRoughnessTexture",
+							&mut c.clearcoat_roughness_texture,
+							&mut slots,
+						);
+						if let Some(t) = &mut c.clearcoat_normal_texture {
+							slots.push(GltfTextureSlot::new(
+								"extensions/KHR_materials_clearcoat/clearcoatNormalTexture",
+								&mut t.texture_index,
+								&mut t.tex_coord,
+								&mut t.extensions,
+							));
+						}
+					}
+					GltfExtension::KhrMaterialsTransmission(t) => {
+						push("extensions/KHR_materials_transmission/transmissionTexture", &mut t.transmission_texture, &mut slots);
+					}
+					GltfExtension::KhrMaterialsSpecular(s) => {
+						push("extensions/KHR_materials_specular/specularTexture", &mut s.specular_texture, &mut slots);
+						push("extensions/KHR_materials_specular/specularColorTexture", &mut s.specular_color_texture, &mut slots);
+					}
+					_ => {}
+				}
+			}
+		}
+
+		slots
+	}
 }
\ No newline at end of file
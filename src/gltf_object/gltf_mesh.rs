@@ -1,27 +1,108 @@
+use crate::gltf_object::prelude::*;
 use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
 pub struct GltfMesh {
-	pub name: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub name: Option<String>,
+
 	pub primitives: Vec<GltfMeshPrimitive>,
 
-	// #[serde(skip)]
-	pub(crate) original_index: Option<usize>,
+	/// default morph target weights, one per target of the primitives
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub weights: Option<Vec<f32>>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extensions: Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras: Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
 }
 
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GltfMeshPrimitive {
-	pub attributes: serde_json::Value,
+	/// vertex attribute semantic, eg POSITION or TEXCOORD_0, to the accessor holding it
+	pub attributes: BTreeMap<String, Index<GltfAccessor>>,
+
+	#[serde(rename = "indices", skip_serializing_if = "Option::is_none")]
+	pub accessor: Option<Index<GltfAccessor>>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub material: Option<Index<GltfMaterial>>,
+
+	/// the topology of the primitive, TRIANGLES when undefined. see [GltfMeshPrimitive::mode]
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub mode: Option<PrimitiveMode>,
+
+	/// morph targets, each mapping POSITION/NORMAL/TANGENT to the accessor holding its displacements
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub targets: Option<Vec<BTreeMap<String, Index<GltfAccessor>>>>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extensions: Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras: Extras,
 
-	#[serde(rename = "indices")]
-	pub accessor: Option<usize>, // index
-	pub material: Option<usize>, // index
-	pub mode: Option<usize>, // index
-	pub targets: Option<usize>, //todo check valid targets here
-	pub extensions: Option<Vec<serde_json::Value>>,
-	pub extras: Option<Vec<serde_json::Value>>,
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
 
-	// #[serde(skip)]
+	#[serde(skip)]
+	#[allow(dead_code)]
 	pub(crate) original_index: Option<usize>,
 }
+
+impl GltfMeshPrimitive {
+	/// the topology of the primitive, with the default applied
+	pub fn mode(&self) -> PrimitiveMode {
+		self.mode.unwrap_or_default()
+	}
+}
+
+
+//https://registry.khronos.org/glTF/specs/2.0/glTF-2.0.pdf see 5.24.4 mesh.primitive.mode
+//enum
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PrimitiveMode(usize);
+impl PrimitiveMode {
+	pub const POINTS: PrimitiveMode = PrimitiveMode(0);
+	pub const LINES: PrimitiveMode = PrimitiveMode(1);
+	pub const LINE_LOOP: PrimitiveMode = PrimitiveMode(2);
+	pub const LINE_STRIP: PrimitiveMode = PrimitiveMode(3);
+	pub const TRIANGLES: PrimitiveMode = PrimitiveMode(4);
+	pub const TRIANGLE_STRIP: PrimitiveMode = PrimitiveMode(5);
+	pub const TRIANGLE_FAN: PrimitiveMode = PrimitiveMode(6);
+
+	pub fn value(&self) -> usize {
+		self.0
+	}
+
+	pub fn is_valid(&self) -> bool {
+		self.0 <= 6
+	}
+
+	/// true for TRIANGLES, TRIANGLE_STRIP and TRIANGLE_FAN
+	pub fn is_triangles(&self) -> bool {
+		matches!(self.0, 4..=6)
+	}
+
+	/// the number of triangles drawn from `count` vertices (or indices), 0 for points and lines
+	pub fn triangle_count(&self, count: usize) -> usize {
+		match *self {
+			PrimitiveMode::TRIANGLES => count / 3,
+			PrimitiveMode::TRIANGLE_STRIP | PrimitiveMode::TRIANGLE_FAN => count.saturating_sub(2),
+			_ => 0,
+		}
+	}
+}
+
+impl Default for PrimitiveMode {
+	fn default() -> Self {
+		PrimitiveMode::TRIANGLES
+	}
+}
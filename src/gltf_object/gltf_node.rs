@@ -1,21 +1,50 @@
 use crate::gltf_object::prelude::*;
+use crate::gltf_object::GltfSkin;
 use serde_derive::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
 pub struct GltfNode {
-	pub mesh: Option<usize>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub mesh: Option<Index<GltfMesh>>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub name: Option<String>,
-	pub camera: Option<usize>,
-	pub children: Option<Vec<usize>>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub camera: Option<Index<GltfCamera>>,
+
+	/// the skin used by the mesh of this node, which must then be defined
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub skin: Option<Index<GltfSkin>>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub children: Option<Vec<Index<GltfNode>>>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub translation: Option<[f32; 3]>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub matrix: Option<[f32; 16]>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub rotation: Option<[f32; 4]>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub scale: Option<[f32; 3]>,
-	pub weights: Option<Vec<usize>>,
 
+	/// morph target weights, overriding the weights of the mesh
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub weights: Option<Vec<f32>>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extras: Extras,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extensions: Extensions,
 
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
+
 	#[serde(skip)]
-	pub(crate) original_index: Option<usize>,
+	pub(crate) original_index: Option<Index<GltfNode>>,
 }
\ No newline at end of file
@@ -4,20 +4,26 @@ use serde_derive::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GltfSampler {
-	#[serde(rename = "magFilter")]
+	#[serde(rename = "magFilter", skip_serializing_if = "Option::is_none")]
 	pub mag_filter: Option<usize>,
-	#[serde(rename = "minFilter")]
+	#[serde(rename = "minFilter", skip_serializing_if = "Option::is_none")]
 	pub min_filter: Option<usize>,
 
-	#[serde(rename = "wrapS")]
+	#[serde(rename = "wrapS", skip_serializing_if = "Option::is_none")]
 	pub wrap_s: Option<usize>,
 
-	#[serde(rename = "wrapT")]
+	#[serde(rename = "wrapT", skip_serializing_if = "Option::is_none")]
 	pub wrap_t: Option<usize>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub name: Option<String>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extras: Extras,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extensions: Extensions,
 
-	#[serde(skip)]
-	pub(crate) original_index: Option<usize>,
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
 }
\ No newline at end of file
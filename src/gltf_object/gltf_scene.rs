@@ -1,12 +1,25 @@
+use crate::gltf_object::prelude::*;
 use serde_derive::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
 
 pub struct GltfScene {
-	pub name: String,
-	pub nodes: Vec<usize>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub name: Option<String>,
+
+	#[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
+	pub nodes: Vec<Index<GltfNode>>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extensions: Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras: Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
 
 	#[serde(skip)]
 	#[allow(dead_code)]
-	pub(crate) original_index: Option<usize>,
-}
\ No newline at end of file
+	pub(crate) original_index: Option<Index<GltfScene>>,
+}
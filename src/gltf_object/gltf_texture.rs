@@ -1,17 +1,23 @@
 use crate::gltf_object::prelude::*;
 use serde_derive::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
 pub struct GltfTexture {
-	#[serde(rename = "sampler")]
-	pub sample_index: Option<usize>,
+	#[serde(rename = "sampler", skip_serializing_if = "Option::is_none")]
+	pub sample_index: Option<Index<GltfSampler>>,
 
-	#[serde(rename = "source")]
-	pub source_image_index: Option<usize>,
+	#[serde(rename = "source", skip_serializing_if = "Option::is_none")]
+	pub source_image_index: Option<Index<GltfImage>>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub name: Option<String>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extensions: Extensions,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extras: Extras,
 
-	#[serde(skip)]
-	pub(crate) original_index: Option<usize>,
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
 }
\ No newline at end of file
@@ -0,0 +1,164 @@
+use crate::buffers::{GltfBase64Buffer, GltfBufferView};
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::{GltfObject, GltfSkin, GltrError, GltrResult};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+/// A reference from one glTF object to another, by its position in the array holding it.
+///
+/// `Index<GltfMesh>` and `Index<GltfAccessor>` are different types, so passing one where the other is expected does
+/// not compile. serialized as the bare number, exactly like the usize it wraps
+pub struct Index<T> {
+	value: usize,
+	marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Index<T> {
+	pub const fn new(value: usize) -> Self {
+		Index { value, marker: PhantomData }
+	}
+
+	/// the position of the referenced object in its array
+	pub const fn value(self) -> usize {
+		self.value
+	}
+}
+
+// implemented by hand, deriving would require T itself to implement each trait
+
+impl<T> Clone for Index<T> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+
+impl<T> Copy for Index<T> {}
+
+impl<T> PartialEq for Index<T> {
+	fn eq(&self, other: &Self) -> bool {
+		self.value == other.value
+	}
+}
+
+impl<T> Eq for Index<T> {}
+
+impl<T> PartialOrd for Index<T> {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl<T> Ord for Index<T> {
+	fn cmp(&self, other: &Self) -> Ordering {
+		self.value.cmp(&other.value)
+	}
+}
+
+impl<T> Hash for Index<T> {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.value.hash(state)
+	}
+}
+
+impl<T> Debug for Index<T> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		let name = std::any::type_name::<T>().rsplit("::").next().unwrap_or_default();
+		write!(f, "Index<{name}>({})", self.value)
+	}
+}
+
+impl<T> Display for Index<T> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}", self.value)
+	}
+}
+
+impl<T> Serialize for Index<T> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		self.value.serialize(serializer)
+	}
+}
+
+impl<'de, T> Deserialize<'de> for Index<T> {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		Ok(Index::new(usize::deserialize(deserializer)?))
+	}
+}
+
+/// An object stored in one of the top level arrays of [GltfObject], so that an [Index] to it can be resolved
+pub trait Indexed: Sized {
+	/// JSON pointer of the array, eg "/meshes"
+	const PATH: &'static str;
+
+	fn all(object: &GltfObject) -> &Vec<Self>;
+
+	fn all_mut(object: &mut GltfObject) -> &mut Vec<Self>;
+}
+
+macro_rules! impl_indexed {
+	($($t:ty => $path:literal, $($field:tt).+);* $(;)?) => {
+		$(
+			impl Indexed for $t {
+				const PATH: &'static str = $path;
+
+				fn all(object: &GltfObject) -> &Vec<Self> {
+					&object.$($field).+
+				}
+
+				fn all_mut(object: &mut GltfObject) -> &mut Vec<Self> {
+					&mut object.$($field).+
+				}
+			}
+		)*
+	};
+}
+
+impl_indexed! {
+	GltfAccessor => "/accessors", accessors;
+	GltfAnimation => "/animations", animations;
+	GltfBase64Buffer => "/buffers", buffers.0;
+	GltfBufferView => "/bufferViews", buffer_views;
+	GltfCamera => "/cameras", cameras;
+	GltfImage => "/images", images;
+	GltfMaterial => "/materials", materials;
+	GltfMesh => "/meshes", meshes;
+	GltfNode => "/nodes", nodes;
+	GltfSampler => "/samplers", samplers;
+	GltfScene => "/scenes", scenes;
+	GltfSkin => "/skins", skins;
+	GltfTexture => "/textures", textures;
+}
+
+impl GltfObject {
+	/// the object `index` refers to, None when it is out of range
+	pub fn get<T: Indexed>(&self, index: Index<T>) -> Option<&T> {
+		T::all(self).get(index.value)
+	}
+
+	pub fn get_mut<T: Indexed>(&mut self, index: Index<T>) -> Option<&mut T> {
+		T::all_mut(self).get_mut(index.value)
+	}
+
+	/// same as [GltfObject::get], with an [GltrError::InvalidIndex] naming the array when `index` is out of range
+	pub fn try_get<T: Indexed>(&self, index: Index<T>) -> GltrResult<&T> {
+		match self.get(index) {
+			None => Err(GltrError::invalid_index(T::PATH, index.value)),
+			Some(x) => Ok(x),
+		}
+	}
+
+	/// appends `value` to its array, returning its index
+	pub fn push<T: Indexed>(&mut self, value: T) -> Index<T> {
+		let all = T::all_mut(self);
+		all.push(value);
+		Index::new(all.len() - 1)
+	}
+
+	/// the index of every object in the array holding `T`
+	pub fn indices<T: Indexed>(&self) -> impl Iterator<Item = Index<T>> {
+		(0..T::all(self).len()).map(Index::new)
+	}
+}
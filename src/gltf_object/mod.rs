@@ -1,4 +1,6 @@
+mod builder;
 mod extras;
+mod extensions;
 mod gltf_image;
 mod gltf_accessor;
 mod gltf_texture;
@@ -9,12 +11,26 @@ mod gltf_node;
 mod gltf_material;
 mod gltf_mesh;
 mod extract_flags;
+mod index;
 mod gltf_camera;
 mod gltf_animation;
-
-use crate::buffers::{GltfBufferView, GltfBuffers};
+mod scene_graph;
+mod references;
+mod validation;
+mod glb;
+mod resources;
+mod stats;
+mod geometry;
+mod bounds;
+mod aabb;
+mod axes;
+mod raycast;
+
+use crate::buffers::{GltfBase64Buffer, GltfBufferView, GltfBuffers};
 
 use serde_derive::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
 
 #[allow(unused_imports)]
 pub mod prelude {
@@ -23,8 +39,11 @@ pub mod prelude {
 	}
 
 	pub use crate::buffers as gltf_buffers;
+	pub use crate::gltf_object::builder::*;
 	pub use crate::gltf_object::extract_flags::*;
 	pub use crate::gltf_object::extras::*;
+	pub use crate::gltf_object::index::*;
+	pub use crate::gltf_object::extensions::*;
 	pub use crate::gltf_object::gltf_accessor::*;
 	pub use crate::gltf_object::gltf_asset::*;
 	pub use crate::gltf_object::gltf_image::*;
@@ -37,67 +56,80 @@ pub mod prelude {
 	pub use crate::gltf_object::gltf_texture::*;
 	pub use crate::gltf_object::gltf_camera::*;
 	pub use crate::gltf_object::gltf_animation::*;
+	pub use crate::gltf_object::stats::*;
+	pub use crate::gltf_object::geometry::*;
+	pub use crate::gltf_object::bounds::*;
+	pub use crate::gltf_object::aabb::*;
+	pub use crate::gltf_object::axes::*;
+	pub use crate::gltf_object::raycast::*;
 }
 
 
 use crate::gltf_object::prelude::*;
-use crate::ops::VecExt;
-
-#[derive(Deserialize, Serialize, Debug)]
-pub enum GltrError {
-	InvalidJson(usize, usize, String), //line,column,message
-	ConstraintViolation(String),
-	InvalidIndex(&'static str, usize),
-}
 
-pub type GltrResult<T> = Result<T, GltrError>;
+pub use crate::error::{GltrError, GltrResult};
 
 
 #[derive(Deserialize, Serialize, Debug,Clone)]
 pub struct GltfSkin {
-	#[serde(rename="inverseBindMatrices")]
-	pub inverse_bind_matrices:Option<usize>,
+	#[serde(rename="inverseBindMatrices", skip_serializing_if = "Option::is_none")]
+	pub inverse_bind_matrices:Option<Index<GltfAccessor>>,
 
-	pub skeleton:Option<usize>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub skeleton:Option<Index<GltfNode>>,
 
-	pub joins:Vec<usize>,
+	pub joints:Vec<Index<GltfNode>>,
 
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub name:Option<String>,
 
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extensions:Extensions,
-	pub extras:Extras
 
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras:Extras,
+
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
 }
 
 
 ///see fields in https://registry.khronos.org/glTF/specs/2.0/glTF-2.0.pdf
 /// field numbering relative to 5.17 - glTF
-#[derive(Deserialize, Serialize, Debug)]
+///
+/// the derives are remote so the Serialize impl below can write the extension lists synced
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(remote = "Self")]
 pub struct GltfObject {
 
 	/// 5.17.1 - glTF.extensionsUsed
 	/// 
 	/// names of glTF extensions used in this asset
-	#[serde(rename="extensionsUsed",default = "Vec::new")]
+	///
+	/// kept as loaded until [GltfObject::sync_extensions] runs. serializing the object writes a synced copy
+	/// instead, without the extensions no longer present and with the ones added since
+	#[serde(rename="extensionsUsed",default = "Vec::new", skip_serializing_if = "Vec::is_empty", getter = "GltfObject::synced_extensions_used")]
 	pub extensions_used: Vec<String>,
 
 	/// 5.17.2 - glTF.extensionsRequired
 	/// 
 	/// Names of glTF extensions required to properly load this asset.
-	#[serde(rename="extensionsRequired",default = "Vec::new")]
+	///
+	/// synced along with extensions_used, see there
+	#[serde(rename="extensionsRequired",default = "Vec::new", skip_serializing_if = "Vec::is_empty", getter = "GltfObject::synced_extensions_required")]
 	pub extensions_required: Vec<String>,
 
 
 	/// 5.17.3 - glTF.accessors
 	/// 
 	/// An array of accessors. An accessor is a typed view into a bufferView
-	#[serde(default = "Vec::new")]
+	#[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
 	pub accessors: Vec<GltfAccessor>,
 
 	/// 5.17.4 - glTF.animations
 	/// 
 	/// An array of keyframe animations.
-	#[serde(default = "Vec::new")]
+	#[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
 	pub animations: Vec<GltfAnimation>,
 
 
@@ -109,294 +141,247 @@ pub struct GltfObject {
 	/// 5.17.6 - glTF.buffers
 	/// 
 	/// An array of buffers. A buffer points to binary geometry, animation, or skins.
-	#[serde(default = "GltfBuffers::empty")]
+	#[serde(default = "GltfBuffers::empty", skip_serializing_if = "GltfBuffers::is_empty")]
 	pub buffers: GltfBuffers,
 
 
 	/// 5.17.7 - glTF.bufferViews
 	/// 
 	/// An array of bufferViews. A bufferView is a view into a buffer generally representing a subset of the buffer.
-	#[serde(rename = "bufferViews", default = "Vec::new")]
+	#[serde(rename = "bufferViews", default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
 	pub buffer_views: Vec<GltfBufferView>,
 
 	/// 5.17.8 - glTF.cameras
 	/// 
 	/// An array of cameras. A camera defines a projection matrix.
-	#[serde(default = "Vec::new")]
+	#[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
 	pub cameras: Vec<GltfCamera>,
 
 	/// 5.17.9 - glTF.images
 	/// 
 	/// An array of images. An image defines data used to create a texture.
-	#[serde(default = "Vec::new")]
+	#[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
 	pub images: Vec<GltfImage>,
 
 
 	/// 5.17.10 - glTF.materials
 	/// 
 	/// An array of materials. A material defines the appearance of a primitive.
-	#[serde(default = "Vec::new")]
+	#[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
 	pub materials: Vec<GltfMaterial>,
 
 
 	/// 5.17.11 - glTF.meshes
 	/// 
 	/// An array of meshes. A mesh is a set of primitives to be rendered.
-	#[serde(default = "Vec::new")]
+	#[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
 	pub meshes: Vec<GltfMesh>,
 
 
 	/// 5.17.12 - glTF.nodes
 	/// 
 	/// An array of nodes
-	#[serde(default = "Vec::new")]
+	#[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
 	pub nodes: Vec<GltfNode>,
 
 	/// 5.17.13 - glTF.samplers
 	/// 
 	/// An array of samplers. A sampler contains properties for texture filtering and wrapping modes.
-	#[serde(default = "Vec::new")]
+	#[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
 	pub samplers: Vec<GltfSampler>,
 
 	/// 5.17.14 - glTF.scene
 	/// 
-	// The index of the default scene. This property MUST NOT be defined, when scenes is undefined.
-	pub scene: usize,
+	/// The index of the default scene. This property MUST NOT be defined, when scenes is undefined.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub scene: Option<Index<GltfScene>>,
 
 	/// 5.17.15 - glTF.scenes
 	/// 
 	/// An array of scenes
-	#[serde(default = "Vec::new")]
+	#[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
 	pub scenes: Vec<GltfScene>,
 
 	/// 5.17.16 - glTF.skins
 	/// 
 	/// An array of skins. A skin is defined by joints and matrices.
-	#[serde(default = "Vec::new")]
+	#[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
 	pub skins: Vec<GltfSkin>,
 
 	/// 5.17.17 - glTF.textures
 	/// 
 	/// An array of textures.
-	#[serde(default = "Vec::new")]
+	#[serde(default = "Vec::new", skip_serializing_if = "Vec::is_empty")]
 	pub textures: Vec<GltfTexture>,
 
 	/// 5.17.18 - glTF.extensions
 	/// 
 	/// JSON object with extension-specific objects.
+	#[serde(skip_serializing_if = "Option::is_none")]
 	pub extensions:Extensions,
 
 	/// 5.17.19 - glTF.extras
 	/// 
 	/// Application-specific data.
-	pub extras:Extras
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub extras:Extras,
 
+	#[serde(flatten)]
+	pub unknown_fields: UnknownFields,
 }
-impl GltfObject {
-	pub fn parse_json_str(string: &str) -> Self {
-		serde_json::from_str::<GltfObject>(string).unwrap()
+impl serde::Serialize for GltfObject {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		GltfObject::serialize(self, serializer)
 	}
+}
 
-	pub fn try_parse_json_str(string: &str) -> serde_json::Result<GltfObject> {
-		serde_json::from_str::<GltfObject>(string)
+impl<'de> serde::Deserialize<'de> for GltfObject {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		GltfObject::deserialize(deserializer)
 	}
+}
 
-	pub fn extract_node(&self, idx: usize, flags: GltrExtractFlags) -> GltrResult<Self> {
-		let node = self.nodes.get(idx);
-
-		let node = match node {
-			None => {
-				return Err(GltrError::InvalidIndex("Node", idx))
-			}
-			Some(node) => {
-				node.clone()
-			}
-		};
-
-		let mut new_object = GltfObject::new();
+impl Default for GltfObject {
+	fn default() -> Self {
+		Self::new()
+	}
+}
 
-		let mut node = node.clone();
+impl GltfObject {
+	pub fn parse_json_str(string: &str) -> GltrResult<Self> {
+		Ok(serde_json::from_str::<GltfObject>(string)?)
+	}
 
-		node.original_index = Some(idx);
+	/// same as [GltfObject::parse_json_str], which returns a Result now too
+	#[deprecated(note = "use GltfObject::parse_json_str")]
+	pub fn try_parse_json_str(string: &str) -> GltrResult<GltfObject> {
+		Self::parse_json_str(string)
+	}
 
+	/// reads and parses a .gltf or .glb file, telling them apart by content. buffers stored in external files are
+	/// left as they are, see [GltfObject::embed_buffers]
+	pub fn load(path: impl AsRef<Path>) -> GltrResult<Self> {
+		let content = fs::read(path)?;
 
-		if flags.has_flag(GltrExtractFlags::CENTER_OBJECTS) && node.translation.is_some() {
-			node.translation = Some([0f32, 0f32, 0f32])
+		if glb::is_glb(&content) {
+			return Self::parse_glb(&content);
 		}
 
+		Ok(serde_json::from_slice::<GltfObject>(&content)?)
+	}
 
-		let mesh_idx = &node.mesh;
-
-		if mesh_idx.is_some() {
-			let mut mesh = match self.meshes.get(mesh_idx.unwrap()) {
-				None => {
-					return Err(GltrError::InvalidIndex("Mesh", mesh_idx.unwrap()))
-				}
-				Some(mesh) => {
-					mesh.clone()
-				}
-			};
-
-			mesh.original_index = *mesh_idx;
-
-
-			for x in &mesh.primitives {
-				let accessor_index = x.accessor;
-				let material_index = x.material;
-
-				if accessor_index.is_some() {
-					let mut accessor = match self.accessors.get(accessor_index.unwrap()) {
-						None => {
-							return Err(GltrError::InvalidIndex("Accessor", mesh_idx.unwrap()))
-						}
-						Some(accessor) => {
-							accessor.clone()
-						}
-					};
+	/// writes the object as a .glb file when the path has that extension and as a .gltf file otherwise,
+	/// see [GltfObject::to_glb] and [GltfObject::to_json_string]
+	pub fn save(&self, path: impl AsRef<Path>) -> GltrResult<()> {
+		let path = path.as_ref();
 
-					accessor.original_index = accessor_index;
+		if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("glb")) {
+			fs::write(path, self.to_glb()?)?;
+		} else {
+			fs::write(path, self.to_json_string()?)?;
+		}
 
-					new_object.accessors.push_if_no_match(accessor.clone(), |x| x.original_index == mesh.original_index);
-				}
+		Ok(())
+	}
 
+	/// serializes to a JSON string, with extensionsUsed and extensionsRequired synced to the content
+	pub fn to_json_string(&self) -> GltrResult<String> {
+		Ok(serde_json::to_string(self)?)
+	}
 
-				if material_index.is_some() {
-					let mut material = match self.materials.get(material_index.unwrap()) {
-						None => {
-							return Err(GltrError::InvalidIndex("Material", mesh_idx.unwrap()))
-						}
-						Some(material) => {
-							material.clone()
-						}
-					};
+	pub fn to_json_string_pretty(&self) -> GltrResult<String> {
+		Ok(serde_json::to_string_pretty(self)?)
+	}
 
-					material.original_index = material_index;
+	/// creates a document holding only the node `idx` and the objects its mesh needs: accessors, buffer views,
+	/// buffers, materials, textures, images and samplers. children, camera and skin of the node are not extracted.
+	/// fails when the objects it needs have extensions whose references are unknown, see
+	/// [GltfObject::unchecked_extensions]
+	pub fn extract_node(&self, idx: Index<GltfNode>, flags: GltrExtractFlags) -> GltrResult<Self> {
+		let mut node = self.try_get(idx)?.clone();
+		node.original_index = Some(idx);
 
-					new_object.materials.push_if_no_match(material.clone(), |x| {
-						x.original_index == material.original_index
-					});
-				}
-			}
+		// only the node and its mesh are extracted, references to anything else would dangle
+		node.children = None;
+		node.camera = None;
+		node.skin = None;
 
-			new_object.meshes.push_if_no_match(mesh.clone(), |x| {
-				x.original_index == mesh.original_index
-			})
+		if flags.has_flag(GltrExtractFlags::CENTER_OBJECTS) && node.translation.is_some() {
+			node.translation = Some([0f32, 0f32, 0f32])
 		}
 
-
-		for x in &new_object.materials {
-			let texture_index = x.get_texture_index();
-
-			if texture_index.is_some() {
-				let mut texture = match self.textures.get(texture_index.unwrap()) {
-					None => {
-						return Err(GltrError::InvalidIndex("Texture", texture_index.unwrap()))
-					}
-					Some(tex) => {
-						tex.clone()
-					}
-				};
-				texture.original_index = texture_index;
-				new_object.textures.push_if_no_match(texture.clone(), |x| x.original_index == texture.original_index)
+		if let Some(mesh_idx) = node.mesh {
+			if self.get(mesh_idx).is_none() {
+				return Err(GltrError::invalid_index(format!("/nodes/{idx}/mesh"), mesh_idx.value()));
 			}
 		}
 
+		let light = match node.extension::<KhrLightsPunctualNode>() {
+			None => None,
+			Some(node_light) => match self.lights().get(node_light.light.value()) {
+				None => {
+					return Err(GltrError::invalid_index(format!("/nodes/{idx}/extensions/KHR_lights_punctual/light"), node_light.light.value()))
+				}
+				Some(light) => Some(light.clone()),
+			},
+		};
 
-		for x in &new_object.textures {
-			let image_idx = x.source_image_index;
-			if image_idx.is_some() {
-				let mut image = match self.images.get(image_idx.unwrap()) {
-					None => {
-						return Err(GltrError::InvalidIndex("Image", x.source_image_index.unwrap()))
-					}
-					Some(image) => {
-						image.clone()
-					}
-				};
-
-				image.original_index = image_idx;
-				new_object.images.push_if_no_match(image.clone(), |x| x.original_index == image.original_index)
-			}
+		let mut new_object = GltfObject::new();
 
-			let sampler_index = x.sample_index;
-			if sampler_index.is_some() {
-				let mut sampler = match self.samplers.get(sampler_index.unwrap()) {
-					None => {
-						return Err(GltrError::InvalidIndex("Sampler", x.sample_index.unwrap()))
-					}
-					Some(sampler) => {
-						sampler.clone()
-					}
-				};
-
-				sampler.original_index = sampler_index;
-				new_object.samplers.push_if_no_match(sampler.clone(), |x| x.original_index == sampler.original_index)
-			}
-		}
+		// every object the node could need is copied, then everything it does not refer to is removed again.
+		// the buffers are copied without their data, it is only needed for the buffers that remain
+		new_object.meshes = self.meshes.clone();
+		new_object.materials = self.materials.clone();
+		new_object.textures = self.textures.clone();
+		new_object.images = self.images.clone();
+		new_object.samplers = self.samplers.clone();
+		new_object.accessors = self.accessors.clone();
+		new_object.buffer_views = self.buffer_views.clone();
+		new_object.buffers.0 = self
+			.buffers
+			.0
+			.iter()
+			.enumerate()
+			.map(|(i, x)| GltfBase64Buffer {
+				uri: None,
+				original_index: Some(Index::new(i)),
+				byte_length: x.byte_length,
+				name: x.name.clone(),
+				extensions: x.extensions.clone(),
+				extras: x.extras.clone(),
+				unknown_fields: x.unknown_fields.clone(),
+			})
+			.collect();
 
-		for x in &new_object.accessors {
-			let buffer_view_index = x.buffer_view;
-			if buffer_view_index.is_some() {
-				let mut buffer_view = match self.buffer_views.get(buffer_view_index.unwrap()) {
-					None => {
-						return Err(GltrError::InvalidIndex("buffer_view", mesh_idx.unwrap()))
-					}
-					Some(bv) => {
-						bv.clone()
-					}
-				};
-
-				buffer_view.original_index = buffer_view_index;
-				new_object.buffer_views.push_if_no_match(buffer_view.clone(), |x| x.original_index == buffer_view.original_index)
+		// the extracted node becomes the only root of the only scene
+		new_object.nodes.push(node);
+		new_object.scenes.push(GltfScene {
+			nodes: vec![Index::new(0)],
+			..GltfScene::default()
+		});
+		new_object.scene = Some(Index::new(0));
+
+		new_object.ensure_references_known()?;
+		new_object.remove_unused()?;
+
+		for buffer in &mut new_object.buffers.0 {
+			if let Some(original) = buffer.original_index.and_then(|b| self.get(b)) {
+				buffer.uri = original.uri.clone();
 			}
 		}
 
-
-		for x in &new_object.images {
-			let buffer_view_index = x.buffer_view;
-			if buffer_view_index.is_some() {
-				let mut buffer_view = match self.buffer_views.get(buffer_view_index.unwrap()) {
-					None => {
-						return Err(GltrError::InvalidIndex("buffer_view", mesh_idx.unwrap()))
-					}
-					Some(bv) => {
-						bv.clone()
-					}
-				};
-
-				buffer_view.original_index = buffer_view_index;
-				new_object.buffer_views.push_if_no_match(buffer_view.clone(), |x| x.original_index == buffer_view.original_index)
-			}
+		if flags.has_flag(GltrExtractFlags::RECALCULATE_BUFFERS) {
+			new_object.compact_buffers()?;
 		}
 
-
-		for x in &new_object.buffer_views {
-			let buffer_index = x.buffer;
-
-			let mut has = false;
-			for x in &new_object.buffers.0 {
-				if x.original_index.is_some() && x.original_index.unwrap() == buffer_index {
-					has = true;
-				}
-			}
-
-			if !has {
-				let buffer = match self.buffers.0.get(buffer_index) {
-					None => {
-						return Err(GltrError::InvalidIndex("buffer", mesh_idx.unwrap()))
-					}
-					Some(b) => {
-						b.clone()
-					}
-				};
-
-				new_object.buffers.0.push(buffer)
-			}
+		if let Some(light) = light {
+			let new_light_index = new_object.add_light(light);
+			new_object.nodes[0].set_extension(KhrLightsPunctualNode::new(new_light_index));
 		}
 
-
-		new_object.scene = self.scene;
-		new_object.nodes.push(node);
+		new_object.extensions_used = self.extensions_used.clone();
+		new_object.extensions_required = self.extensions_required.clone();
+		new_object.sync_extensions();
 
 		Ok(new_object)
 	}
@@ -405,10 +390,15 @@ impl GltfObject {
 		GltfObject {
 			extensions_used: vec![],
 			asset: GltfAsset {
-				generator: "Gltr Library v0.0.1".to_string(),
+				copyright: None,
+				generator: Some("Gltr Library v0.0.1".to_string()),
 				version: "2.0".to_string(),
+				min_version: None,
+				extensions: None,
+				extras: None,
+				unknown_fields: UnknownFields::new(),
 			},
-			scene: 0,
+			scene: None,
 			scenes: vec![],
 			nodes: vec![],
 			meshes: vec![],
@@ -425,6 +415,7 @@ impl GltfObject {
 			cameras: vec![],
 			skins: vec![],
 			extras: None,
+			unknown_fields: UnknownFields::new(),
 		}
 	}
 }
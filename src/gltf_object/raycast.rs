@@ -0,0 +1,353 @@
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::{GltfObject, GltrError, GltrResult};
+use crate::math::*;
+
+/// the most triangles in a leaf of the hierarchy
+const LEAF_SIZE: usize = 4;
+
+/// Where a query met the geometry of a [SceneBvh]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit {
+	pub node: Index<GltfNode>,
+	pub mesh: Index<GltfMesh>,
+	pub primitive: usize,
+
+	/// the index of the triangle in the order of [GltfObject::primitive_triangles]
+	pub triangle: usize,
+
+	/// the index of the EXT_mesh_gpu_instancing instance hit, None for nodes without instancing
+	pub instance: Option<usize>,
+
+	/// from the origin of the ray or the queried point, in world units
+	pub distance: f32,
+
+	/// the weights of the three corners of the triangle at `point`
+	pub barycentrics: [f32; 3],
+
+	/// in world space
+	pub point: [f32; 3],
+}
+
+/// a world space triangle and where it comes from
+#[derive(Debug, Clone, Copy)]
+struct Triangle {
+	corners: [Vec3; 3],
+	node: Index<GltfNode>,
+	mesh: Index<GltfMesh>,
+	primitive: usize,
+	triangle: usize,
+	instance: Option<usize>,
+}
+
+impl Triangle {
+	fn centroid(&self) -> Vec3 {
+		scale(add(add(self.corners[0], self.corners[1]), self.corners[2]), 1f32 / 3f32)
+	}
+
+	fn hit(&self, distance: f32, barycentrics: [f32; 3], point: Vec3) -> Hit {
+		Hit {
+			node: self.node,
+			mesh: self.mesh,
+			primitive: self.primitive,
+			triangle: self.triangle,
+			instance: self.instance,
+			distance,
+			barycentrics,
+			point,
+		}
+	}
+
+	/// Möller-Trumbore, hitting both faces. the distance along the normalized `dir` and the barycentrics
+	fn intersect(&self, origin: Vec3, dir: Vec3) -> Option<(f32, [f32; 3])> {
+		let [a, b, c] = self.corners;
+		let (ab, ac) = (sub(b, a), sub(c, a));
+
+		let p = cross(dir, ac);
+		let det = dot(ab, p);
+		if det.abs() < f32::EPSILON * length(ab) * length(ac) {
+			return None;
+		}
+
+		let inv = 1f32 / det;
+		let to_origin = sub(origin, a);
+		let u = dot(to_origin, p) * inv;
+		if !(0f32..=1f32).contains(&u) {
+			return None;
+		}
+
+		let q = cross(to_origin, ab);
+		let v = dot(dir, q) * inv;
+		if v < 0f32 || u + v > 1f32 {
+			return None;
+		}
+
+		let t = dot(ac, q) * inv;
+		(t >= 0f32).then_some((t, [1f32 - u - v, u, v]))
+	}
+
+	/// the point of the triangle closest to `p` and its barycentrics, from Ericson's Real-Time Collision Detection
+	fn closest_point(&self, p: Vec3) -> (Vec3, [f32; 3]) {
+		let [a, b, c] = self.corners;
+		let (ab, ac, ap) = (sub(b, a), sub(c, a), sub(p, a));
+
+		let (d1, d2) = (dot(ab, ap), dot(ac, ap));
+		if d1 <= 0f32 && d2 <= 0f32 {
+			return (a, [1f32, 0f32, 0f32]);
+		}
+
+		let bp = sub(p, b);
+		let (d3, d4) = (dot(ab, bp), dot(ac, bp));
+		if d3 >= 0f32 && d4 <= d3 {
+			return (b, [0f32, 1f32, 0f32]);
+		}
+
+		let vc = d1 * d4 - d3 * d2;
+		if vc <= 0f32 && d1 >= 0f32 && d3 <= 0f32 {
+			let v = d1 / (d1 - d3);
+			return (add(a, scale(ab, v)), [1f32 - v, v, 0f32]);
+		}
+
+		let cp = sub(p, c);
+		let (d5, d6) = (dot(ab, cp), dot(ac, cp));
+		if d6 >= 0f32 && d5 <= d6 {
+			return (c, [0f32, 0f32, 1f32]);
+		}
+
+		let vb = d5 * d2 - d1 * d6;
+		if vb <= 0f32 && d2 >= 0f32 && d6 <= 0f32 {
+			let w = d2 / (d2 - d6);
+			return (add(a, scale(ac, w)), [1f32 - w, 0f32, w]);
+		}
+
+		let va = d3 * d6 - d5 * d4;
+		if va <= 0f32 && d4 - d3 >= 0f32 && d5 - d6 >= 0f32 {
+			let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+			return (add(b, scale(sub(c, b), w)), [0f32, 1f32 - w, w]);
+		}
+
+		let denominator = 1f32 / (va + vb + vc);
+		let (v, w) = (vb * denominator, vc * denominator);
+		(add(a, add(scale(ab, v), scale(ac, w))), [1f32 - v - w, v, w])
+	}
+}
+
+/// a node of the hierarchy: with `count` 0 its children are at `first` and `first + 1`, otherwise it is a leaf
+/// holding the triangles from `first`
+#[derive(Debug, Clone, Copy)]
+struct BvhNode {
+	bounds: Aabb,
+	first: usize,
+	count: usize,
+}
+
+/// A bounding volume hierarchy over the world space triangles of a scene, see [GltfObject::scene_bvh]. it owns
+/// its triangles, the document can change after it is built without it following
+#[derive(Debug, Clone)]
+pub struct SceneBvh {
+	triangles: Vec<Triangle>,
+	nodes: Vec<BvhNode>,
+}
+
+/// the distance along a ray with inverse direction `inv` to where it enters `bounds`, None if it misses them or
+/// enters past `limit`
+fn enter_box(bounds: &Aabb, origin: Vec3, inv: Vec3, limit: f32) -> Option<f32> {
+	let (mut near, mut far) = (0f32, limit);
+	for axis in 0..3 {
+		let t0 = (bounds.min[axis] - origin[axis]) * inv[axis];
+		let t1 = (bounds.max[axis] - origin[axis]) * inv[axis];
+		// NaN from a ray along the face of the box leaves the bounds as they were
+		near = near.max(t0.min(t1));
+		far = far.min(t0.max(t1));
+	}
+
+	(near <= far).then_some(near)
+}
+
+/// the distance from `p` to the closest point of `bounds`, 0 inside them
+fn box_distance(bounds: &Aabb, p: Vec3) -> f32 {
+	length(sub(p, min(max(p, bounds.min), bounds.max)))
+}
+
+fn triangle_bounds(triangles: &[Triangle]) -> Aabb {
+	let corners: Vec<Vec3> = triangles.iter().flat_map(|t| t.corners).collect();
+	Aabb::from_points(&corners).unwrap_or(Aabb { min: [0f32; 3], max: [0f32; 3] })
+}
+
+impl SceneBvh {
+	fn new(mut triangles: Vec<Triangle>) -> SceneBvh {
+		let mut nodes = vec![BvhNode {
+			bounds: triangle_bounds(&triangles),
+			first: 0,
+			count: triangles.len(),
+		}];
+
+		// leaves are split at the median centroid along their longest axis until small enough
+		let mut stack = vec![0];
+		while let Some(n) = stack.pop() {
+			let BvhNode { first, count, .. } = nodes[n];
+			if count <= LEAF_SIZE {
+				continue;
+			}
+
+			let slice = &mut triangles[first..first + count];
+			let centroids: Vec<Vec3> = slice.iter().map(Triangle::centroid).collect();
+			let extent = Aabb::from_points(&centroids).map_or([0f32; 3], |b| b.size());
+			let axis = (0..3).fold(0, |a, b| if extent[b] > extent[a] { b } else { a });
+
+			let half = count / 2;
+			slice.select_nth_unstable_by(half, |a, b| a.centroid()[axis].total_cmp(&b.centroid()[axis]));
+
+			let left = BvhNode {
+				bounds: triangle_bounds(&slice[..half]),
+				first,
+				count: half,
+			};
+			let right = BvhNode {
+				bounds: triangle_bounds(&slice[half..]),
+				first: first + half,
+				count: count - half,
+			};
+
+			nodes[n] = BvhNode { first: nodes.len(), count: 0, ..nodes[n] };
+			stack.extend([nodes.len(), nodes.len() + 1]);
+			nodes.extend([left, right]);
+		}
+
+		SceneBvh { triangles, nodes }
+	}
+
+	pub fn triangle_count(&self) -> usize {
+		self.triangles.len()
+	}
+
+	/// the bounds of every triangle, None without triangles
+	pub fn bounds(&self) -> Option<Aabb> {
+		(!self.triangles.is_empty()).then_some(self.nodes[0].bounds)
+	}
+
+	/// the first triangle along the ray from `origin` in direction `dir`, from either side. `dir` doesn't need to be
+	/// normalized, the distance of the hit is in world units. None when nothing is hit or `dir` is zero
+	pub fn raycast(&self, origin: [f32; 3], dir: [f32; 3]) -> Option<Hit> {
+		if self.triangles.is_empty() || length(dir) == 0f32 {
+			return None;
+		}
+
+		let dir = normalize(dir);
+		let inv = dir.map(|d| 1f32 / d);
+
+		let mut best: Option<Hit> = None;
+		let mut stack = vec![0];
+		while let Some(n) = stack.pop() {
+			let limit = best.map_or(f32::INFINITY, |h| h.distance);
+			let node = &self.nodes[n];
+			if enter_box(&node.bounds, origin, inv, limit).is_none() {
+				continue;
+			}
+
+			if node.count == 0 {
+				// the nearer child is popped first
+				let near = |c: usize| enter_box(&self.nodes[c].bounds, origin, inv, limit).unwrap_or(f32::INFINITY);
+				let (a, b) = (node.first, node.first + 1);
+				stack.extend(if near(a) <= near(b) { [b, a] } else { [a, b] });
+				continue;
+			}
+
+			for triangle in &self.triangles[node.first..node.first + node.count] {
+				if let Some((distance, barycentrics)) = triangle.intersect(origin, dir) {
+					if distance < limit && best.is_none_or(|h| distance < h.distance) {
+						best = Some(triangle.hit(distance, barycentrics, add(origin, scale(dir, distance))));
+					}
+				}
+			}
+		}
+
+		best
+	}
+
+	/// the point of the geometry closest to `point`, None without triangles
+	pub fn closest_point(&self, point: [f32; 3]) -> Option<Hit> {
+		if self.triangles.is_empty() {
+			return None;
+		}
+
+		let mut best: Option<Hit> = None;
+		let mut stack = vec![0];
+
+		while let Some(n) = stack.pop() {
+			let node = &self.nodes[n];
+			if best.is_some_and(|h| box_distance(&node.bounds, point) >= h.distance) {
+				continue;
+			}
+
+			if node.count == 0 {
+				let (a, b) = (node.first, node.first + 1);
+				let near_a = box_distance(&self.nodes[a].bounds, point) <= box_distance(&self.nodes[b].bounds, point);
+				stack.extend(if near_a { [b, a] } else { [a, b] });
+				continue;
+			}
+
+			for triangle in &self.triangles[node.first..node.first + node.count] {
+				let (closest, barycentrics) = triangle.closest_point(point);
+				let distance = length(sub(closest, point));
+				if best.is_none_or(|h| distance < h.distance) {
+					best = Some(triangle.hit(distance, barycentrics, closest));
+				}
+			}
+		}
+
+		best
+	}
+}
+
+impl GltfObject {
+	/// builds a [SceneBvh] over the triangles of every primitive drawn by a scene, in world space. points and
+	/// lines are left out, instanced nodes contribute every instance, skins and morph targets are not applied
+	pub fn scene_bvh(&self, scene: Index<GltfScene>) -> GltrResult<SceneBvh> {
+		let parents = self.node_parents();
+		let mut triangles = vec![];
+
+		for idx in self.bounded_scene_nodes(scene)? {
+			let mesh = match self.nodes[idx.value()].mesh {
+				None => continue,
+				Some(mesh) => mesh,
+			};
+
+			let world = self.node_world_matrix_with(idx, &parents)?;
+			let matrices: Vec<(Option<usize>, Mat4)> = match self.nodes[idx.value()].has_extension(ExtMeshGpuInstancing::NAME) {
+				false => vec![(None, world)],
+				true => self.node_instance_matrices(idx)?.iter().enumerate().map(|(i, instance)| (Some(i), mat4_mul(&world, instance))).collect(),
+			};
+
+			for primitive in 0..self.try_get(mesh)?.primitives.len() {
+				let p = self.primitive(mesh, primitive)?;
+				let position = match p.attributes.get("POSITION") {
+					Some(position) if p.mode().is_triangles() => *position,
+					_ => continue,
+				};
+
+				let positions = self.read_accessor_array::<3>(position)?;
+				for (t, corners) in self.primitive_triangles(mesh, primitive)?.enumerate() {
+					if let Some(i) = corners.iter().find(|i| **i as usize >= positions.len()) {
+						return Err(GltrError::ConstraintViolation(format!(
+							"/meshes/{mesh}/primitives/{primitive} uses vertex {i}, POSITION has {} vertices",
+							positions.len()
+						)));
+					}
+
+					for (instance, matrix) in &matrices {
+						triangles.push(Triangle {
+							corners: corners.map(|i| transform_point(matrix, positions[i as usize])),
+							node: idx,
+							mesh,
+							primitive,
+							triangle: t,
+							instance: *instance,
+						});
+					}
+				}
+			}
+		}
+
+		Ok(SceneBvh::new(triangles))
+	}
+}
@@ -0,0 +1,624 @@
+use crate::buffers::{GltfBase64Buffer, GltfBufferView};
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::{GltfObject, GltfSkin, GltrError, GltrResult};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+
+/// Receives every [Index] held by a document, see [GltfObject::visit_indices_mut]
+pub(crate) trait IndexVisitor {
+	/// `path` builds the JSON pointer of the property holding the index, it is only worth calling when reporting it
+	fn visit<T: Indexed>(&mut self, index: &mut Index<T>, path: &dyn Fn() -> String);
+}
+
+fn visit_option<T: Indexed>(visitor: &mut impl IndexVisitor, index: &mut Option<Index<T>>, path: &dyn Fn() -> String) {
+	if let Some(index) = index {
+		visitor.visit(index, path);
+	}
+}
+
+/// the index-bearing properties of the Khronos extensions that have no typed struct, as (extension, path of the
+/// index within it) where `*` stands for every element of an array. the indices refer to textures
+const MATERIAL_TEXTURES: &[(&str, &str)] = &[
+	("KHR_materials_anisotropy", "anisotropyTexture/index"),
+	("KHR_materials_diffuse_transmission", "diffuseTransmissionTexture/index"),
+	("KHR_materials_diffuse_transmission", "diffuseTransmissionColorTexture/index"),
+	("KHR_materials_iridescence", "iridescenceTexture/index"),
+	("KHR_materials_iridescence", "iridescenceThicknessTexture/index"),
+	("KHR_materials_pbrSpecularGlossiness", "diffuseTexture/index"),
+	("KHR_materials_pbrSpecularGlossiness", "specularGlossinessTexture/index"),
+	("KHR_materials_sheen", "sheenColorTexture/index"),
+	("KHR_materials_sheen", "sheenRoughnessTexture/index"),
+	("KHR_materials_volume", "thicknessTexture/index"),
+];
+
+/// see [MATERIAL_TEXTURES], the indices refer to images
+const TEXTURE_IMAGES: &[(&str, &str)] = &[
+	("EXT_texture_avif", "source"),
+	("EXT_texture_webp", "source"),
+	("KHR_texture_basisu", "source"),
+	("MSFT_texture_dds", "source"),
+];
+
+/// see [MATERIAL_TEXTURES], the indices refer to buffer views
+const PRIMITIVE_BUFFER_VIEWS: &[(&str, &str)] = &[("KHR_draco_mesh_compression", "bufferView")];
+
+/// see [MATERIAL_TEXTURES], the indices refer to materials
+const PRIMITIVE_MATERIALS: &[(&str, &str)] = &[("KHR_materials_variants", "mappings/*/material")];
+
+/// see [MATERIAL_TEXTURES], the indices refer to buffers
+const BUFFER_VIEW_BUFFERS: &[(&str, &str)] = &[("EXT_meshopt_compression", "buffer")];
+
+/// the Khronos extensions without a typed struct that hold no index into a top level array
+const WITHOUT_REFERENCES: &[&str] = &[
+	"KHR_materials_dispersion",
+	"KHR_materials_unlit",
+	"KHR_materials_variants",
+	"KHR_xmp_json_ld",
+	"EXT_meshopt_compression",
+];
+
+/// the texCoord sets read by the textures of the material extensions listed in [MATERIAL_TEXTURES], which
+/// [GltfMaterial::texture_slots] does not know about. a KHR_texture_transform of the texture may override the set
+pub(crate) fn untyped_tex_coords(material: &GltfMaterial) -> BTreeSet<usize> {
+	let mut sets = BTreeSet::new();
+
+	for (name, index_path) in MATERIAL_TEXTURES {
+		let raw = match material.extensions.as_ref().and_then(|e| e.get_raw(name)) {
+			Some(GltfExtension::Unknown(raw)) => raw,
+			_ => continue,
+		};
+
+		let texture = match raw.pointer(&format!("/{}", index_path.trim_end_matches("/index"))) {
+			None => continue,
+			Some(texture) => texture,
+		};
+
+		let set = texture.pointer("/extensions/KHR_texture_transform/texCoord").or(texture.get("texCoord"));
+		sets.insert(set.and_then(Value::as_u64).unwrap_or(0) as usize);
+	}
+
+	sets
+}
+
+/// the values at `keys` under `value`, with their JSON pointer relative to it
+fn raw_indices<'a>(value: &'a mut Value, keys: &[&str], pointer: String, out: &mut Vec<(String, &'a mut Value)>) {
+	match (keys.split_first(), value) {
+		(None, value) => out.push((pointer, value)),
+		(Some((&"*", rest)), Value::Array(values)) => {
+			for (i, v) in values.iter_mut().enumerate() {
+				raw_indices(v, rest, format!("{pointer}/{i}"), out);
+			}
+		}
+		(Some((key, rest)), Value::Object(map)) => {
+			if let Some(v) = map.get_mut(*key) {
+				raw_indices(v, rest, format!("{pointer}/{key}"), out);
+			}
+		}
+		_ => {}
+	}
+}
+
+/// visits the indices held by the extensions of `references` that are kept as raw JSON, `path` being the JSON
+/// pointer of the object holding `extensions`
+fn visit_raw<T: Indexed>(v: &mut impl IndexVisitor, extensions: &mut Extensions, references: &[(&str, &str)], path: &dyn Fn() -> String) {
+	let extensions = match extensions {
+		None => return,
+		Some(e) => e,
+	};
+
+	for (name, index_path) in references {
+		let raw = match extensions.0.get_mut(*name) {
+			Some(GltfExtension::Unknown(raw)) => raw,
+			_ => continue,
+		};
+
+		let keys: Vec<&str> = index_path.split('/').collect();
+		let mut found = vec![];
+		raw_indices(raw, &keys, String::new(), &mut found);
+
+		for (pointer, value) in found {
+			if let Some(n) = value.as_u64() {
+				let mut index = Index::<T>::new(n as usize);
+				v.visit(&mut index, &|| format!("{}/extensions/{name}{pointer}", path()));
+				*value = index.value().into();
+			}
+		}
+	}
+}
+
+/// collects the JSON pointer of every extension under `value` whose references are not known, see
+/// [GltfObject::unchecked_extensions]
+fn find_unchecked(value: &Value, pointer: &str, out: &mut Vec<String>) {
+	let known = |name: &str| {
+		WITHOUT_REFERENCES.contains(&name)
+			|| [MATERIAL_TEXTURES, TEXTURE_IMAGES, PRIMITIVE_BUFFER_VIEWS, PRIMITIVE_MATERIALS, BUFFER_VIEW_BUFFERS]
+				.iter()
+				.any(|r| r.iter().any(|(n, _)| *n == name))
+	};
+
+	match value {
+		Value::Object(map) => {
+			for (key, v) in map {
+				match (key.as_str(), v) {
+					("extras", _) => {}
+					("extensions", Value::Object(extensions)) => {
+						for (name, extension) in extensions {
+							let path = format!("{pointer}/extensions/{name}");
+							if !known(name) && !GltfExtension::parse(name, extension.clone()).is_known() {
+								out.push(path.clone());
+							}
+							find_unchecked(extension, &path, out);
+						}
+					}
+					_ => find_unchecked(v, &format!("{pointer}/{key}"), out),
+				}
+			}
+		}
+		Value::Array(values) => {
+			for (i, v) in values.iter().enumerate() {
+				find_unchecked(v, &format!("{pointer}/{i}"), out);
+			}
+		}
+		_ => {}
+	}
+}
+
+/// the length of every top level array, by the [Indexed::PATH] of its objects
+fn array_lengths(object: &GltfObject) -> HashMap<&'static str, usize> {
+	fn entry<T: Indexed>(object: &GltfObject) -> (&'static str, usize) {
+		(T::PATH, T::all(object).len())
+	}
+
+	HashMap::from([
+		entry::<GltfAccessor>(object),
+		entry::<GltfAnimation>(object),
+		entry::<GltfBase64Buffer>(object),
+		entry::<GltfBufferView>(object),
+		entry::<GltfCamera>(object),
+		entry::<GltfImage>(object),
+		entry::<GltfMaterial>(object),
+		entry::<GltfMesh>(object),
+		entry::<GltfNode>(object),
+		entry::<GltfSampler>(object),
+		entry::<GltfScene>(object),
+		entry::<GltfSkin>(object),
+		entry::<GltfTexture>(object),
+	])
+}
+
+/// counts the references to every object, collecting the indices that refer to nothing
+struct References {
+	counts: HashMap<&'static str, Vec<usize>>,
+	invalid: Vec<GltrError>,
+}
+
+impl References {
+	fn new(object: &GltfObject) -> Self {
+		References {
+			counts: array_lengths(object).into_iter().map(|(path, len)| (path, vec![0; len])).collect(),
+			invalid: Vec::new(),
+		}
+	}
+}
+
+impl IndexVisitor for References {
+	fn visit<T: Indexed>(&mut self, index: &mut Index<T>, path: &dyn Fn() -> String) {
+		match self.counts.get_mut(T::PATH).and_then(|c| c.get_mut(index.value())) {
+			None => self.invalid.push(GltrError::invalid_index(path(), index.value())),
+			Some(count) => *count += 1,
+		}
+	}
+}
+
+/// moves indices to the new position of the object they refer to, after objects were removed
+#[derive(Default)]
+struct Remap(HashMap<&'static str, Vec<Option<usize>>>);
+
+impl Remap {
+	/// removes the objects of type T that nothing refers to, returning how many were removed
+	fn retain_used<T: Indexed>(&mut self, object: &mut GltfObject, references: &References) -> usize {
+		let counts = &references.counts[T::PATH];
+
+		let mut map = Vec::with_capacity(counts.len());
+		let mut next = 0;
+		for count in counts {
+			if *count > 0 {
+				map.push(Some(next));
+				next += 1;
+			} else {
+				map.push(None);
+			}
+		}
+
+		let mut idx = 0;
+		T::all_mut(object).retain(|_| {
+			idx += 1;
+			counts[idx - 1] > 0
+		});
+
+		let removed = counts.len() - next;
+		if removed > 0 {
+			self.0.insert(T::PATH, map);
+		}
+
+		removed
+	}
+}
+
+impl IndexVisitor for Remap {
+	fn visit<T: Indexed>(&mut self, index: &mut Index<T>, _: &dyn Fn() -> String) {
+		// indices that were out of range stay out of range, arrays only get shorter
+		if let Some(Some(new)) = self.0.get(T::PATH).and_then(|m| m.get(index.value())) {
+			*index = Index::new(*new);
+		}
+	}
+}
+
+/// shifts every index by the length of the arrays the objects are appended to
+struct Offsets(HashMap<&'static str, usize>);
+
+impl IndexVisitor for Offsets {
+	fn visit<T: Indexed>(&mut self, index: &mut Index<T>, _: &dyn Fn() -> String) {
+		*index = Index::new(index.value() + self.0[T::PATH]);
+	}
+}
+
+impl GltfObject {
+	/// calls the visitor with every index in the document that refers to an object in one of the top level arrays,
+	/// including those of the Khronos extensions kept as raw JSON. indices into other lists (the samplers of an
+	/// animation, KHR_lights_punctual lights) and indices held by other extensions are not visited, see
+	/// [GltfObject::unchecked_extensions]
+	pub(crate) fn visit_indices_mut(&mut self, v: &mut impl IndexVisitor) {
+		visit_option(v, &mut self.scene, &|| "/scene".to_string());
+
+		for (i, scene) in self.scenes.iter_mut().enumerate() {
+			for (j, node) in scene.nodes.iter_mut().enumerate() {
+				v.visit(node, &|| format!("/scenes/{i}/nodes/{j}"));
+			}
+		}
+
+		for (i, node) in self.nodes.iter_mut().enumerate() {
+			visit_option(v, &mut node.mesh, &|| format!("/nodes/{i}/mesh"));
+			visit_option(v, &mut node.camera, &|| format!("/nodes/{i}/camera"));
+			visit_option(v, &mut node.skin, &|| format!("/nodes/{i}/skin"));
+
+			for (j, child) in node.children.iter_mut().flatten().enumerate() {
+				v.visit(child, &|| format!("/nodes/{i}/children/{j}"));
+			}
+
+			if let Some(instancing) = node.extension_mut::<ExtMeshGpuInstancing>() {
+				for (name, accessor) in instancing.attributes.iter_mut() {
+					v.visit(accessor, &|| format!("/nodes/{i}/extensions/EXT_mesh_gpu_instancing/attributes/{name}"));
+				}
+			}
+
+			if let Some(lod) = node.extension_mut::<MsftLod>() {
+				for (j, id) in lod.ids.iter_mut().enumerate() {
+					v.visit(id, &|| format!("/nodes/{i}/extensions/MSFT_lod/ids/{j}"));
+				}
+			}
+		}
+
+		for (i, skin) in self.skins.iter_mut().enumerate() {
+			visit_option(v, &mut skin.inverse_bind_matrices, &|| format!("/skins/{i}/inverseBindMatrices"));
+			visit_option(v, &mut skin.skeleton, &|| format!("/skins/{i}/skeleton"));
+
+			for (j, joint) in skin.joints.iter_mut().enumerate() {
+				v.visit(joint, &|| format!("/skins/{i}/joints/{j}"));
+			}
+		}
+
+		for (i, mesh) in self.meshes.iter_mut().enumerate() {
+			for (p, primitive) in mesh.primitives.iter_mut().enumerate() {
+				for (name, accessor) in primitive.attributes.iter_mut() {
+					v.visit(accessor, &|| format!("/meshes/{i}/primitives/{p}/attributes/{name}"));
+				}
+
+				visit_option(v, &mut primitive.accessor, &|| format!("/meshes/{i}/primitives/{p}/indices"));
+				visit_option(v, &mut primitive.material, &|| format!("/meshes/{i}/primitives/{p}/material"));
+				visit_raw::<GltfBufferView>(v, &mut primitive.extensions, PRIMITIVE_BUFFER_VIEWS, &|| format!("/meshes/{i}/primitives/{p}"));
+				visit_raw::<GltfMaterial>(v, &mut primitive.extensions, PRIMITIVE_MATERIALS, &|| format!("/meshes/{i}/primitives/{p}"));
+
+				for (t, target) in primitive.targets.iter_mut().flatten().enumerate() {
+					for (name, accessor) in target.iter_mut() {
+						v.visit(accessor, &|| format!("/meshes/{i}/primitives/{p}/targets/{t}/{name}"));
+					}
+				}
+			}
+		}
+
+		for (i, material) in self.materials.iter_mut().enumerate() {
+			for slot in material.texture_slots_mut() {
+				let pointer = slot.pointer;
+				v.visit(slot.texture_index, &|| format!("/materials/{i}/{pointer}/index"));
+			}
+
+			visit_raw::<GltfTexture>(v, &mut material.extensions, MATERIAL_TEXTURES, &|| format!("/materials/{i}"));
+		}
+
+		for (i, texture) in self.textures.iter_mut().enumerate() {
+			visit_option(v, &mut texture.sample_index, &|| format!("/textures/{i}/sampler"));
+			visit_option(v, &mut texture.source_image_index, &|| format!("/textures/{i}/source"));
+			visit_raw::<GltfImage>(v, &mut texture.extensions, TEXTURE_IMAGES, &|| format!("/textures/{i}"));
+		}
+
+		for (i, image) in self.images.iter_mut().enumerate() {
+			visit_option(v, &mut image.buffer_view, &|| format!("/images/{i}/bufferView"));
+		}
+
+		for (i, accessor) in self.accessors.iter_mut().enumerate() {
+			visit_option(v, &mut accessor.buffer_view, &|| format!("/accessors/{i}/bufferView"));
+
+			if let Some(sparse) = &mut accessor.sparse {
+				v.visit(&mut sparse.indices.buffer_view, &|| format!("/accessors/{i}/sparse/indices/bufferView"));
+				visit_option(v, &mut sparse.values.buffer_view, &|| format!("/accessors/{i}/sparse/values/bufferView"));
+			}
+		}
+
+		for (i, view) in self.buffer_views.iter_mut().enumerate() {
+			v.visit(&mut view.buffer, &|| format!("/bufferViews/{i}/buffer"));
+			visit_raw::<GltfBase64Buffer>(v, &mut view.extensions, BUFFER_VIEW_BUFFERS, &|| format!("/bufferViews/{i}"));
+		}
+
+		for (i, animation) in self.animations.iter_mut().enumerate() {
+			for (c, channel) in animation.channels.iter_mut().enumerate() {
+				visit_option(v, &mut channel.target.node, &|| format!("/animations/{i}/channels/{c}/target/node"));
+			}
+
+			for (s, sampler) in animation.samplers.iter_mut().enumerate() {
+				v.visit(&mut sampler.input, &|| format!("/animations/{i}/samplers/{s}/input"));
+				v.visit(&mut sampler.output, &|| format!("/animations/{i}/samplers/{s}/output"));
+			}
+		}
+	}
+
+	/// removes everything the document does not use, then drops the bytes no buffer view covers from every buffer.
+	///
+	/// when the document has scenes, nodes that are not part of one (or used as a skin joint) are removed. then
+	/// meshes, materials, textures, images, samplers, cameras, skins, accessors, buffer views and buffers that nothing
+	/// refers to are removed, remapping every index. returns the number of objects removed. fails without modifying
+	/// anything when the document has extensions whose references are unknown, see [GltfObject::unchecked_extensions]
+	pub fn prune(&mut self) -> GltrResult<usize> {
+		self.ensure_references_known()?;
+
+		let removed = self.remove_unreachable_nodes()? + self.remove_unused()?;
+		self.compact_buffers()?;
+
+		Ok(removed)
+	}
+
+	/// removes the nodes that are not part of a scene, used as a skin joint or a level of detail of a node in a scene,
+	/// when the document has scenes
+	fn remove_unreachable_nodes(&mut self) -> GltrResult<usize> {
+		if self.scenes.is_empty() {
+			return Ok(0);
+		}
+
+		let mut roots: Vec<Index<GltfNode>> = self
+			.scenes
+			.iter()
+			.flat_map(|s| s.nodes.iter())
+			.chain(self.skins.iter().flat_map(|s| s.joints.iter().chain(s.skeleton.iter())))
+			.copied()
+			.filter(|n| n.value() < self.nodes.len())
+			.collect();
+
+		let mut used = vec![false; self.nodes.len()];
+		while let Some(root) = roots.pop() {
+			if used[root.value()] {
+				continue;
+			}
+
+			for node in self.node_subtree(root)? {
+				used[node.value()] = true;
+
+				// the levels of detail of a node are drawn in its place
+				if let Some(lod) = self.nodes[node.value()].extension::<MsftLod>() {
+					roots.extend(lod.ids.iter().copied().filter(|n| n.value() < self.nodes.len()));
+				}
+			}
+		}
+
+		let unused: Vec<Index<GltfNode>> = self.indices::<GltfNode>().filter(|n| !used[n.value()]).collect();
+		self.remove_nodes(&unused)?;
+
+		Ok(unused.len())
+	}
+
+	/// the part of [GltfObject::prune] that removes the objects nothing refers to, leaving nodes and buffer data
+	/// as they are
+	pub(crate) fn remove_unused(&mut self) -> GltrResult<usize> {
+		let mut removed = 0;
+
+		// removing an object can leave the objects it referred to unused, so repeat until nothing changes
+		loop {
+			let mut references = References::new(self);
+			self.visit_indices_mut(&mut references);
+
+			let mut remap = Remap::default();
+			let count = remap.retain_used::<GltfMesh>(self, &references)
+				+ remap.retain_used::<GltfMaterial>(self, &references)
+				+ remap.retain_used::<GltfTexture>(self, &references)
+				+ remap.retain_used::<GltfImage>(self, &references)
+				+ remap.retain_used::<GltfSampler>(self, &references)
+				+ remap.retain_used::<GltfCamera>(self, &references)
+				+ remap.retain_used::<GltfSkin>(self, &references)
+				+ remap.retain_used::<GltfAccessor>(self, &references)
+				+ remap.retain_used::<GltfBufferView>(self, &references)
+				+ remap.retain_used::<GltfBase64Buffer>(self, &references);
+
+			if count == 0 {
+				return match references.invalid.into_iter().next() {
+					None => Ok(removed),
+					Some(e) => Err(e),
+				};
+			}
+
+			removed += count;
+			self.visit_indices_mut(&mut remap);
+		}
+	}
+
+	/// removes those of `views` that nothing refers to any more, leaving every other object where it is
+	pub(crate) fn remove_unused_buffer_views(&mut self, views: &[Index<GltfBufferView>]) -> GltrResult<usize> {
+		let mut references = References::new(self);
+		self.visit_indices_mut(&mut references);
+
+		if let Some(e) = references.invalid.pop() {
+			return Err(e);
+		}
+
+		// views that weren't asked about count as used
+		if let Some(counts) = references.counts.get_mut(GltfBufferView::PATH) {
+			for (idx, count) in counts.iter_mut().enumerate() {
+				if !views.contains(&Index::new(idx)) {
+					*count = (*count).max(1);
+				}
+			}
+		}
+
+		let mut remap = Remap::default();
+		let removed = remap.retain_used::<GltfBufferView>(self, &references);
+		self.visit_indices_mut(&mut remap);
+
+		Ok(removed)
+	}
+
+	/// rewrites every buffer to only hold the bytes covered by its buffer views, dropping the gaps left by removed
+	/// views. overlapping views stay overlapped, and every view keeps its offset modulo 4 so accessor alignment holds
+	pub fn compact_buffers(&mut self) -> GltrResult<()> {
+		for buffer_idx in 0..self.buffers.0.len() {
+			let mut views: Vec<usize> = (0..self.buffer_views.len())
+				.filter(|v| self.buffer_views[*v].buffer.value() == buffer_idx)
+				.collect();
+			views.sort_by_key(|v| self.buffer_views[*v].byte_offset);
+
+			let bytes = self.buffers.0[buffer_idx].bytes()?;
+
+			let mut out = Vec::with_capacity(bytes.len());
+			let mut new_offsets = Vec::with_capacity(views.len());
+
+			// (offset in the old buffer, offset in the new buffer) of the run of overlapping views being copied
+			let mut run = (0, 0);
+			let mut copied_to = 0;
+
+			for v in &views {
+				let view = &self.buffer_views[*v];
+				let end = view.byte_offset + view.byte_length;
+
+				if end > bytes.len() {
+					return Err(GltrError::OutOfRange {
+						path: format!("/bufferViews/{v}"),
+						offset: view.byte_offset,
+						length: view.byte_length,
+						available: bytes.len(),
+					});
+				}
+
+				if out.is_empty() || view.byte_offset >= copied_to {
+					while out.len() % 4 != view.byte_offset % 4 {
+						out.push(0);
+					}
+
+					run = (view.byte_offset, out.len());
+					out.extend_from_slice(&bytes[view.byte_offset..end]);
+				} else if end > copied_to {
+					out.extend_from_slice(&bytes[copied_to..end]);
+				}
+
+				copied_to = copied_to.max(end);
+				new_offsets.push(run.1 + view.byte_offset - run.0);
+			}
+
+			for (v, offset) in views.iter().zip(new_offsets) {
+				self.buffer_views[*v].byte_offset = offset;
+			}
+
+			if out != bytes {
+				self.buffers.0[buffer_idx].set_bytes(&out);
+			}
+		}
+
+		Ok(())
+	}
+
+	/// appends every object of `other` to this document, keeping the asset information of this document.
+	///
+	/// the scenes of `other` are appended. the root nodes of its default scene are also added to the default scene of
+	/// this document, or its default scene becomes the default when this document has none
+	pub fn merge(&mut self, mut other: GltfObject) {
+		let other_default = other.scene;
+		other.visit_indices_mut(&mut Offsets(array_lengths(self)));
+
+		let light_offset = self.lights().len();
+		for light in other.lights() {
+			self.add_light(light.clone());
+		}
+		for node in &mut other.nodes {
+			if let Some(l) = node.extension_mut::<KhrLightsPunctualNode>() {
+				l.light = Index::new(l.light.value() + light_offset);
+			}
+		}
+
+		let other_roots = other_default.and_then(|s| other.scenes.get(s.value())).map(|s| s.nodes.clone());
+		match self.scene.and_then(|s| self.scenes.get_mut(s.value())) {
+			Some(scene) => scene.nodes.extend(other_roots.unwrap_or_default()),
+			None => self.scene = self.scene.or(other.scene),
+		}
+
+		self.accessors.append(&mut other.accessors);
+		self.animations.append(&mut other.animations);
+		self.buffers.0.append(&mut other.buffers.0);
+		self.buffer_views.append(&mut other.buffer_views);
+		self.cameras.append(&mut other.cameras);
+		self.images.append(&mut other.images);
+		self.materials.append(&mut other.materials);
+		self.meshes.append(&mut other.meshes);
+		self.nodes.append(&mut other.nodes);
+		self.samplers.append(&mut other.samplers);
+		self.scenes.append(&mut other.scenes);
+		self.skins.append(&mut other.skins);
+		self.textures.append(&mut other.textures);
+
+		for name in other.extensions_used {
+			if !self.extensions_used.contains(&name) {
+				self.extensions_used.push(name);
+			}
+		}
+		for name in other.extensions_required {
+			if !self.extensions_required.contains(&name) {
+				self.extensions_required.push(name);
+			}
+		}
+
+		self.sync_extensions();
+	}
+
+	/// the JSON pointer of every extension that may hold indices this crate does not know of: extensions that are
+	/// neither typed nor a Khronos extension whose references are known, and typed extensions that do not match
+	/// their schema. removing or reordering objects would leave their indices dangling
+	pub fn unchecked_extensions(&self) -> Vec<String> {
+		let mut unchecked = vec![];
+		if let Ok(json) = serde_json::to_value(self) {
+			find_unchecked(&json, "", &mut unchecked);
+		}
+		unchecked
+	}
+
+	/// fails when the document has an extension listed by [GltfObject::unchecked_extensions]
+	pub(crate) fn ensure_references_known(&self) -> GltrResult<()> {
+		match self.unchecked_extensions().into_iter().next() {
+			None => Ok(()),
+			Some(pointer) => Err(GltrError::ConstraintViolation(format!(
+				"{pointer}: the references held by this extension are unknown and would be left dangling"
+			))),
+		}
+	}
+
+	/// every index in the document that does not refer to an existing object, see [GltfObject::validate]
+	pub(crate) fn invalid_indices(&self) -> Vec<GltrError> {
+		let mut references = References::new(self);
+		self.clone().visit_indices_mut(&mut references);
+		references.invalid
+	}
+}
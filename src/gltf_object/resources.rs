@@ -0,0 +1,163 @@
+use crate::gltf_object::{GltfObject, GltrError, GltrResult};
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use std::fs;
+use std::path::Path;
+
+/// decodes the %XX escapes of a relative uri into the file path it refers to
+fn uri_to_path(uri: &str) -> String {
+	let bytes = uri.as_bytes();
+	let mut out = Vec::with_capacity(bytes.len());
+
+	let mut i = 0;
+	while i < bytes.len() {
+		let escaped = match (bytes[i], bytes.get(i + 1..i + 3)) {
+			(b'%', Some(hex)) => std::str::from_utf8(hex).ok().and_then(|h| u8::from_str_radix(h, 16).ok()),
+			_ => None,
+		};
+
+		match escaped {
+			None => {
+				out.push(bytes[i]);
+				i += 1;
+			}
+			Some(b) => {
+				out.push(b);
+				i += 3;
+			}
+		}
+	}
+
+	String::from_utf8_lossy(&out).into_owned()
+}
+
+/// the content of a data uri, or of the file a relative uri refers to
+pub(crate) fn read_uri(uri: &str, base: &Path) -> GltrResult<Vec<u8>> {
+	if uri.starts_with("data:") {
+		return match uri.split_once(";base64,") {
+			None => Err(GltrError::InvalidDataUri(uri.to_string())),
+			Some((_, payload)) => Ok(BASE64_STANDARD.decode(payload)?),
+		};
+	}
+
+	Ok(fs::read(base.join(uri_to_path(uri)))?)
+}
+
+fn mime_type_extension(mime_type: &str) -> &str {
+	match mime_type {
+		"image/png" => "png",
+		"image/jpeg" => "jpg",
+		"image/webp" => "webp",
+		"image/ktx2" => "ktx2",
+		_ => "bin",
+	}
+}
+
+fn uri_mime_type(uri: &str) -> Option<&'static str> {
+	if let Some(data) = uri.strip_prefix("data:") {
+		return ["image/png", "image/jpeg", "image/webp", "image/ktx2"].into_iter().find(|m| data.starts_with(m));
+	}
+
+	match uri.rsplit('.').next()?.to_ascii_lowercase().as_str() {
+		"png" => Some("image/png"),
+		"jpg" | "jpeg" => Some("image/jpeg"),
+		"webp" => Some("image/webp"),
+		"ktx2" => Some("image/ktx2"),
+		_ => None,
+	}
+}
+
+impl GltfObject {
+	/// replaces the uri of every buffer stored in an external file with a data uri holding its content.
+	/// relative uris are resolved against `base`, the directory of the .gltf file
+	pub fn embed_buffers(&mut self, base: &Path) -> GltrResult<()> {
+		for buffer in &mut self.buffers.0 {
+			match &buffer.uri {
+				Some(uri) if !uri.starts_with("data:") => {
+					let bytes = read_uri(uri, base)?;
+					if bytes.len() < buffer.byte_length {
+						return Err(GltrError::BufferLength {
+							expected: buffer.byte_length,
+							actual: bytes.len(),
+						});
+					}
+
+					buffer.set_bytes(&bytes[..buffer.byte_length]);
+				}
+				_ => {}
+			}
+		}
+
+		Ok(())
+	}
+
+	/// moves the content of every image referenced by uri into a buffer view, as GLB files require. every image is
+	/// read and its type resolved before any is moved, nothing is modified when one fails
+	pub fn embed_images(&mut self, base: &Path) -> GltrResult<()> {
+		let mut embedded = vec![];
+		for (i, image) in self.images.iter().enumerate() {
+			let uri = match &image.uri {
+				None => continue,
+				Some(uri) => uri,
+			};
+
+			let mime_type = match image.mime_type.as_deref().or(uri_mime_type(uri)) {
+				None => return Err(GltrError::ConstraintViolation(format!("/images/{i}: unknown image type of {uri}"))),
+				Some(mime_type) => mime_type.to_string(),
+			};
+
+			embedded.push((i, mime_type, read_uri(uri, base)?));
+		}
+
+		for (i, mime_type, bytes) in embedded {
+			let view = self.push_buffer_view(&bytes, None)?;
+
+			let image = &mut self.images[i];
+			image.mime_type = Some(mime_type);
+			image.buffer_view = Some(view);
+			image.uri = None;
+		}
+
+		Ok(())
+	}
+
+	/// moves all binary data out of the document: images stored in buffer views become `{stem}_{i}.{ext}` and every
+	/// buffer is combined into `{stem}.bin`. returns the name and content of each file to write next to the .gltf.
+	/// fails without modifying anything when the document has extensions whose references are unknown, see
+	/// [GltfObject::unchecked_extensions]
+	pub fn externalize(&mut self, stem: &str) -> GltrResult<Vec<(String, Vec<u8>)>> {
+		self.ensure_references_known()?;
+		let mut files = Vec::new();
+		let mut moved = Vec::new();
+
+		for i in 0..self.images.len() {
+			let view = match self.images[i].buffer_view {
+				None => continue,
+				Some(view) => view,
+			};
+
+			let bytes = self.buffer_view_bytes(view)?;
+			let image = &mut self.images[i];
+			let name = format!("{stem}_{i}.{}", mime_type_extension(image.mime_type.as_deref().unwrap_or_default()));
+
+			image.buffer_view = None;
+			image.mime_type = None;
+			image.uri = Some(name.clone());
+			files.push((name, bytes));
+			moved.push(view);
+		}
+
+		// the image views are unused now, unless something else shares them
+		self.remove_unused_buffer_views(&moved)?;
+		self.compact_buffers()?;
+
+		let bytes = self.combine_buffers()?;
+		if let Some(buffer) = self.buffers.0.first_mut() {
+			let name = format!("{stem}.bin");
+			buffer.uri = Some(name.clone());
+			files.push((name, bytes));
+		}
+
+		Ok(files)
+	}
+}
@@ -0,0 +1,196 @@
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::{GltfObject, GltrError, GltrResult};
+use crate::math::*;
+
+impl GltfNode {
+	/// the node's local transform as a column-major matrix, from either `matrix` or translation/rotation/scale
+	pub fn local_matrix(&self) -> [f32; 16] {
+		if let Some(m) = self.matrix {
+			return m;
+		}
+
+		mat4_from_trs(
+			self.translation.unwrap_or([0f32; 3]),
+			self.rotation.unwrap_or(QUAT_IDENTITY),
+			self.scale.unwrap_or([1f32; 3]),
+		)
+	}
+
+	/// the node's local transform as (translation, rotation, scale), decomposing `matrix` if that is what the node uses
+	pub fn local_trs(&self) -> ([f32; 3], [f32; 4], [f32; 3]) {
+		if let Some(m) = &self.matrix {
+			return mat4_decompose(m);
+		}
+
+		(
+			self.translation.unwrap_or([0f32; 3]),
+			self.rotation.unwrap_or(QUAT_IDENTITY),
+			self.scale.unwrap_or([1f32; 3]),
+		)
+	}
+
+	/// replaces the node's transform, clearing `matrix`. identity components are left undefined
+	pub fn set_local_trs(&mut self, translation: [f32; 3], rotation: [f32; 4], scale: [f32; 3]) {
+		self.matrix = None;
+		self.translation = if translation == [0f32; 3] { None } else { Some(translation) };
+		self.rotation = if rotation == QUAT_IDENTITY { None } else { Some(rotation) };
+		self.scale = if scale == [1f32; 3] { None } else { Some(scale) };
+	}
+
+	pub fn child_indices(&self) -> &[Index<GltfNode>] {
+		match &self.children {
+			None => &[],
+			Some(c) => c,
+		}
+	}
+}
+
+impl GltfObject {
+	/// the parent of every node, None for root nodes
+	pub fn node_parents(&self) -> Vec<Option<Index<GltfNode>>> {
+		let mut parents = vec![None; self.nodes.len()];
+
+		for (idx, node) in self.nodes.iter().enumerate() {
+			for child in node.child_indices() {
+				if let Some(p) = parents.get_mut(child.value()) {
+					*p = Some(Index::new(idx));
+				}
+			}
+		}
+
+		parents
+	}
+
+	/// the transform from the node's local space to world space
+	pub fn node_world_matrix(&self, node: Index<GltfNode>) -> GltrResult<[f32; 16]> {
+		let parents = self.node_parents();
+		self.node_world_matrix_with(node, &parents)
+	}
+
+	pub(crate) fn node_world_matrix_with(&self, node: Index<GltfNode>, parents: &[Option<Index<GltfNode>>]) -> GltrResult<[f32; 16]> {
+		let mut matrix = self.try_get(node)?.local_matrix();
+
+		let mut current = node;
+		let mut depth = 0;
+		while let Some(parent) = parents[current.value()] {
+			matrix = mat4_mul(&self.nodes[parent.value()].local_matrix(), &matrix);
+			current = parent;
+
+			depth += 1;
+			if depth > self.nodes.len() {
+				return Err(GltrError::ConstraintViolation(format!("node {node} is part of a cycle")));
+			}
+		}
+
+		Ok(matrix)
+	}
+
+	/// the node and all of its descendants, depth first with the node itself first
+	pub fn node_subtree(&self, node: Index<GltfNode>) -> GltrResult<Vec<Index<GltfNode>>> {
+		self.try_get(node)?;
+
+		let mut visited = vec![false; self.nodes.len()];
+		let mut out = Vec::new();
+		let mut stack = vec![node];
+
+		while let Some(current) = stack.pop() {
+			if visited[current.value()] {
+				return Err(GltrError::ConstraintViolation(format!("node {current} is reachable twice from node {node}")));
+			}
+			visited[current.value()] = true;
+			out.push(current);
+
+			for child in self.nodes[current.value()].child_indices().iter().rev() {
+				if child.value() >= self.nodes.len() {
+					return Err(GltrError::invalid_index(format!("/nodes/{current}/children"), child.value()));
+				}
+				stack.push(*child);
+			}
+		}
+
+		Ok(out)
+	}
+
+	/// true for every node that is a joint of a skin, which [GltfObject::remove_nodes] refuses to remove
+	pub(crate) fn skin_joints(&self) -> Vec<bool> {
+		let mut joints = vec![false; self.nodes.len()];
+		for joint in self.skins.iter().flat_map(|s| &s.joints) {
+			if let Some(j) = joints.get_mut(joint.value()) {
+				*j = true;
+			}
+		}
+		joints
+	}
+
+	/// removes nodes from the document and remaps every reference to the nodes that remain.
+	///
+	/// references to removed nodes are dropped from children, scenes, skeletons and animation channels. the children
+	/// of a removed node are not removed, they just lose their parent. fails without modifying anything if a node
+	/// is a skin joint, as JOINTS_n and inverseBindMatrices refer to joints by their position
+	pub fn remove_nodes(&mut self, remove: &[Index<GltfNode>]) -> GltrResult<()> {
+		let mut removed = vec![false; self.nodes.len()];
+		for node in remove {
+			match removed.get_mut(node.value()) {
+				None => return Err(GltrError::invalid_index(GltfNode::PATH, node.value())),
+				Some(r) => *r = true,
+			}
+		}
+
+		for (s, skin) in self.skins.iter().enumerate() {
+			if let Some(joint) = skin.joints.iter().find(|j| removed.get(j.value()).copied().unwrap_or(false)) {
+				return Err(GltrError::ConstraintViolation(format!("node {joint} is a joint of skin {s} and can't be removed")));
+			}
+		}
+
+		let mut remap = vec![None; self.nodes.len()];
+		let mut next = 0;
+		for (idx, r) in removed.iter().enumerate() {
+			if !r {
+				remap[idx] = Some(next);
+				next += 1;
+			}
+		}
+
+		let map = |idx: &Index<GltfNode>| remap.get(idx.value()).copied().flatten().map(Index::new);
+
+		let mut idx = 0;
+		self.nodes.retain(|_| {
+			idx += 1;
+			!removed[idx - 1]
+		});
+
+		for node in &mut self.nodes {
+			if let Some(children) = &mut node.children {
+				*children = children.iter().filter_map(map).collect();
+				if children.is_empty() {
+					node.children = None;
+				}
+			}
+
+			if let Some(lod) = node.extension_mut::<MsftLod>() {
+				lod.ids = lod.ids.iter().filter_map(map).collect();
+				if lod.ids.is_empty() {
+					node.remove_extension(MsftLod::NAME);
+				}
+			}
+		}
+
+		for scene in &mut self.scenes {
+			scene.nodes = scene.nodes.iter().filter_map(map).collect();
+		}
+
+		for skin in &mut self.skins {
+			skin.joints = skin.joints.iter().map(|j| map(j).unwrap_or(*j)).collect();
+			skin.skeleton = skin.skeleton.as_ref().and_then(map);
+		}
+
+		for animation in &mut self.animations {
+			animation.channels.retain(|c| c.target.node.as_ref().is_none_or(|n| map(n).is_some()));
+			for channel in &mut animation.channels {
+				channel.target.node = channel.target.node.as_ref().and_then(map);
+			}
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,345 @@
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::{resources, GltfObject, GltrResult};
+use serde_derive::Serialize;
+use std::collections::BTreeSet;
+
+/// Counts and sizes describing the cost of a document, see [GltfObject::stats]
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct GltfStats {
+	pub meshes: Vec<MeshStats>,
+
+	/// one per primitive of every mesh drawn by a node in the scene, an instanced node is a single draw call
+	#[serde(rename = "drawCalls")]
+	pub draw_calls: usize,
+
+	/// vertices and triangles drawn by the scene, counting every instance
+	#[serde(rename = "sceneVertices")]
+	pub scene_vertices: usize,
+
+	#[serde(rename = "sceneTriangles")]
+	pub scene_triangles: usize,
+
+	pub accessors: Vec<AccessorStats>,
+
+	#[serde(rename = "bufferViews")]
+	pub buffer_views: Vec<BufferViewStats>,
+
+	pub buffers: Vec<BufferStats>,
+
+	pub images: Vec<ImageStats>,
+
+	/// totals for the subtree under every root node of the scene
+	#[serde(rename = "rootNodes")]
+	pub root_nodes: Vec<NodeStats>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct MeshStats {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub name: Option<String>,
+
+	pub primitives: Vec<PrimitiveStats>,
+
+	pub vertices: usize,
+
+	pub triangles: usize,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct PrimitiveStats {
+	pub mode: PrimitiveMode,
+
+	/// the count of the POSITION accessor
+	pub vertices: usize,
+
+	/// None for non-indexed primitives
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub indices: Option<usize>,
+
+	/// 0 for points and lines
+	pub triangles: usize,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct AccessorStats {
+	/// the bytes read by the accessor, not counting the padding of interleaved views
+	#[serde(rename = "byteLength")]
+	pub byte_length: usize,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct BufferViewStats {
+	#[serde(rename = "byteLength")]
+	pub byte_length: usize,
+
+	/// how many accessors and images read from the view
+	pub users: usize,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct BufferStats {
+	#[serde(rename = "byteLength")]
+	pub byte_length: usize,
+
+	/// bytes of the buffer that no buffer view covers
+	#[serde(rename = "unusedBytes")]
+	pub unused_bytes: usize,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct ImageStats {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub name: Option<String>,
+
+	#[serde(rename = "mimeType", skip_serializing_if = "Option::is_none")]
+	pub mime_type: Option<String>,
+
+	/// size of the encoded image, None when it is stored in an external file
+	#[serde(rename = "byteLength", skip_serializing_if = "Option::is_none")]
+	pub byte_length: Option<usize>,
+
+	/// read from the PNG or JPEG header, None for other formats
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub width: Option<u32>,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub height: Option<u32>,
+
+	/// the memory used by the decoded RGBA8 image with a full mip chain
+	#[serde(rename = "gpuBytes", skip_serializing_if = "Option::is_none")]
+	pub gpu_bytes: Option<usize>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct NodeStats {
+	pub node: usize,
+
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub name: Option<String>,
+
+	/// the node and all of its descendants
+	pub nodes: usize,
+
+	#[serde(rename = "drawCalls")]
+	pub draw_calls: usize,
+
+	pub vertices: usize,
+
+	pub triangles: usize,
+
+	/// bytes of the accessors used by the meshes of the subtree, each counted once
+	#[serde(rename = "geometryBytes")]
+	pub geometry_bytes: usize,
+
+	/// bytes of the encoded images used by the materials of the subtree, each counted once
+	#[serde(rename = "imageBytes")]
+	pub image_bytes: usize,
+}
+
+/// width and height from the header of a PNG or baseline/progressive JPEG file
+fn image_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+	let be_u32 = |b: &[u8]| u32::from_be_bytes([b[0], b[1], b[2], b[3]]);
+	let be_u16 = |b: &[u8]| u16::from_be_bytes([b[0], b[1]]) as u32;
+
+	if bytes.starts_with(b"\x89PNG\r\n\x1a\n") && bytes.len() >= 24 {
+		return Some((be_u32(&bytes[16..20]), be_u32(&bytes[20..24])));
+	}
+
+	if !bytes.starts_with(&[0xFF, 0xD8]) {
+		return None;
+	}
+
+	// walk the segments up to the start of frame, which holds the dimensions
+	let mut offset = 2;
+	while offset + 9 < bytes.len() {
+		if bytes[offset] != 0xFF {
+			return None;
+		}
+
+		let marker = bytes[offset + 1];
+		let length = be_u16(&bytes[offset + 2..offset + 4]) as usize;
+
+		if matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+			return Some((be_u16(&bytes[offset + 7..offset + 9]), be_u16(&bytes[offset + 5..offset + 7])));
+		}
+
+		offset += 2 + length;
+	}
+
+	None
+}
+
+impl GltfObject {
+	/// collects vertex, triangle and draw call counts, the bytes used by accessors, buffer views, buffers and
+	/// images, and the totals of every root node of the default scene (of every node without a parent when the
+	/// document has no scene)
+	pub fn stats(&self) -> GltrResult<GltfStats> {
+		let mut stats = GltfStats::default();
+
+		for mesh in &self.meshes {
+			let mut mesh_stats = MeshStats {
+				name: mesh.name.clone(),
+				..MeshStats::default()
+			};
+
+			for primitive in &mesh.primitives {
+				let vertices = primitive.attributes.get("POSITION").and_then(|a| self.get(*a)).map_or(0, |a| a.count);
+				let indices = primitive.accessor.and_then(|a| self.get(a)).map(|a| a.count);
+				let mode = primitive.mode();
+
+				let primitive_stats = PrimitiveStats {
+					mode,
+					vertices,
+					indices,
+					triangles: mode.triangle_count(indices.unwrap_or(vertices)),
+				};
+
+				mesh_stats.vertices += primitive_stats.vertices;
+				mesh_stats.triangles += primitive_stats.triangles;
+				mesh_stats.primitives.push(primitive_stats);
+			}
+
+			stats.meshes.push(mesh_stats);
+		}
+
+		for accessor in &self.accessors {
+			stats.accessors.push(AccessorStats {
+				byte_length: accessor.count * accessor.element_size(),
+			});
+		}
+
+		let mut view_users = vec![0; self.buffer_views.len()];
+		let sparse_views = self.accessors.iter().filter_map(|a| a.sparse.as_ref()).flat_map(|s| [Some(s.indices.buffer_view), s.values.buffer_view]);
+		let views = self.accessors.iter().map(|a| a.buffer_view).chain(self.images.iter().map(|i| i.buffer_view)).chain(sparse_views);
+		for view in views.flatten() {
+			if let Some(users) = view_users.get_mut(view.value()) {
+				*users += 1;
+			}
+		}
+
+		for (view, users) in self.buffer_views.iter().zip(view_users) {
+			stats.buffer_views.push(BufferViewStats {
+				byte_length: view.byte_length,
+				users,
+			});
+		}
+
+		for (i, buffer) in self.buffers.0.iter().enumerate() {
+			let mut ranges: Vec<(usize, usize)> = self
+				.buffer_views
+				.iter()
+				.filter(|v| v.buffer.value() == i)
+				.map(|v| (v.byte_offset, (v.byte_offset + v.byte_length).min(buffer.byte_length)))
+				.collect();
+			ranges.sort();
+
+			let mut covered = 0;
+			let mut end = 0;
+			for (start, stop) in ranges {
+				let start = start.max(end);
+				if stop > start {
+					covered += stop - start;
+					end = stop;
+				}
+			}
+
+			stats.buffers.push(BufferStats {
+				byte_length: buffer.byte_length,
+				unused_bytes: buffer.byte_length.saturating_sub(covered),
+			});
+		}
+
+		for image in &self.images {
+			let bytes = match (&image.uri, image.buffer_view) {
+				(_, Some(view)) => Some(self.buffer_view_bytes(view)?),
+				(Some(uri), None) if uri.starts_with("data:") => Some(resources::read_uri(uri, "".as_ref())?),
+				_ => None,
+			};
+
+			let dimensions = bytes.as_deref().and_then(image_dimensions);
+
+			stats.images.push(ImageStats {
+				name: image.name.clone(),
+				mime_type: image.mime_type.clone(),
+				byte_length: bytes.map(|b| b.len()),
+				width: dimensions.map(|d| d.0),
+				height: dimensions.map(|d| d.1),
+				gpu_bytes: dimensions.map(|(w, h)| w as usize * h as usize * 4 * 4 / 3),
+			});
+		}
+
+		let roots: Vec<Index<GltfNode>> = match self.scene.and_then(|s| self.get(s)) {
+			Some(scene) => scene.nodes.clone(),
+			None => {
+				let parents = self.node_parents();
+				self.indices::<GltfNode>().filter(|n| parents[n.value()].is_none()).collect()
+			}
+		};
+
+		for root in roots {
+			let node_stats = self.node_stats(root, &stats)?;
+
+			stats.draw_calls += node_stats.draw_calls;
+			stats.scene_vertices += node_stats.vertices;
+			stats.scene_triangles += node_stats.triangles;
+			stats.root_nodes.push(node_stats);
+		}
+
+		Ok(stats)
+	}
+
+	/// the totals of the subtree under `root`, using the per mesh, accessor and image numbers of `stats`
+	fn node_stats(&self, root: Index<GltfNode>, stats: &GltfStats) -> GltrResult<NodeStats> {
+		let subtree = self.node_subtree(root)?;
+
+		let mut node_stats = NodeStats {
+			node: root.value(),
+			name: self.nodes[root.value()].name.clone(),
+			nodes: subtree.len(),
+			..NodeStats::default()
+		};
+
+		let mut accessors = BTreeSet::new();
+		let mut images = BTreeSet::new();
+
+		for idx in subtree {
+			let node = &self.nodes[idx.value()];
+
+			if let Some(instancing) = node.extension::<ExtMeshGpuInstancing>() {
+				accessors.extend(instancing.attributes.values().copied());
+			}
+
+			let (mesh, mesh_stats) = match node.mesh.and_then(|m| Some((self.get(m)?, stats.meshes.get(m.value())?))) {
+				None => continue,
+				Some(m) => m,
+			};
+
+			let instances = self.node_instance_count(idx)?;
+			node_stats.draw_calls += mesh.primitives.len();
+			node_stats.vertices += mesh_stats.vertices * instances;
+			node_stats.triangles += mesh_stats.triangles * instances;
+
+			for primitive in &mesh.primitives {
+				accessors.extend(primitive.attributes.values().copied());
+				accessors.extend(primitive.accessor);
+				accessors.extend(primitive.targets.iter().flatten().flat_map(|t| t.values().copied()));
+
+				let material = match primitive.material.and_then(|m| self.get(m)) {
+					None => continue,
+					Some(material) => material,
+				};
+
+				for slot in material.texture_slots() {
+					if let Some(image) = self.get(slot.texture_index).and_then(|t| t.source_image_index) {
+						images.insert(image);
+					}
+				}
+			}
+		}
+
+		node_stats.geometry_bytes = accessors.iter().filter_map(|a| stats.accessors.get(a.value())).map(|a| a.byte_length).sum();
+		node_stats.image_bytes = images.iter().filter_map(|i| stats.images.get(i.value())).filter_map(|i| i.byte_length).sum();
+
+		Ok(node_stats)
+	}
+}
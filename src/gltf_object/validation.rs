@@ -0,0 +1,175 @@
+use crate::gltf_object::prelude::*;
+use crate::gltf_object::{GltfObject, GltrError};
+
+impl GltfObject {
+	/// checks the document against the parts of the glTF 2.0 specification that make it unusable when broken,
+	/// returning every problem found. an empty list means the document is valid.
+	///
+	/// checked: every index refers to an existing object, buffers hold the number of bytes they declare, buffer
+	/// views and accessors stay within the data they read, byte strides are in range, the node hierarchy is a
+	/// forest, the attributes of a primitive have the same count and every required extension is also used. the
+	/// indices held by the extensions of [GltfObject::unchecked_extensions] are not checked
+	pub fn validate(&self) -> Vec<GltrError> {
+		let mut errors = self.invalid_indices();
+
+		let mut buffer_lengths = Vec::with_capacity(self.buffers.0.len());
+		for (i, buffer) in self.buffers.0.iter().enumerate() {
+			// buffers without a uri are filled from the GLB binary chunk or an external file when loaded
+			let bytes = match &buffer.uri {
+				Some(uri) if uri.starts_with("data:") => buffer.bytes(),
+				_ => {
+					buffer_lengths.push(buffer.byte_length);
+					continue;
+				}
+			};
+
+			match bytes {
+				Err(e) => errors.push(e),
+				Ok(bytes) if bytes.len() != buffer.byte_length => {
+					errors.push(GltrError::BufferLength {
+						expected: buffer.byte_length,
+						actual: bytes.len(),
+					});
+				}
+				Ok(_) => {}
+			}
+
+			if buffer.byte_length == 0 {
+				errors.push(GltrError::ConstraintViolation(format!("/buffers/{i}: byteLength must be at least 1")));
+			}
+
+			buffer_lengths.push(buffer.byte_length);
+		}
+
+		for (i, view) in self.buffer_views.iter().enumerate() {
+			if let Some(available) = buffer_lengths.get(view.buffer.value()) {
+				if view.byte_offset + view.byte_length > *available {
+					errors.push(GltrError::OutOfRange {
+						path: format!("/bufferViews/{i}"),
+						offset: view.byte_offset,
+						length: view.byte_length,
+						available: *available,
+					});
+				}
+			}
+
+			if let Some(stride) = view.byte_stride {
+				if !(4..=252).contains(&stride) || stride % 4 != 0 {
+					errors.push(GltrError::ConstraintViolation(format!(
+						"/bufferViews/{i}/byteStride: {stride} is not a multiple of 4 between 4 and 252"
+					)));
+				}
+			}
+		}
+
+		for (i, accessor) in self.accessors.iter().enumerate() {
+			if accessor.component_count() == 0 || !accessor.component_type.is_valid() {
+				errors.push(GltrError::ConstraintViolation(format!("/accessors/{i}: invalid type or componentType")));
+				continue;
+			}
+
+			let view = match accessor.buffer_view.and_then(|v| self.get(v)) {
+				None => continue,
+				Some(view) => view,
+			};
+
+			let offset = accessor.byte_offset.unwrap_or(0);
+			let element_size = accessor.element_size();
+			let stride = view.byte_stride.unwrap_or(element_size);
+			let length = match accessor.count {
+				0 => 0,
+				count => stride * (count - 1) + element_size,
+			};
+
+			if offset + length > view.byte_length {
+				errors.push(GltrError::OutOfRange {
+					path: format!("/accessors/{i}"),
+					offset,
+					length,
+					available: view.byte_length,
+				});
+			}
+
+			if offset % accessor.component_type.byte_size() != 0 {
+				errors.push(GltrError::ConstraintViolation(format!(
+					"/accessors/{i}/byteOffset: {offset} is not a multiple of the component size"
+				)));
+			}
+		}
+
+		let mut parents = vec![None; self.nodes.len()];
+		for (i, node) in self.nodes.iter().enumerate() {
+			for child in node.child_indices() {
+				match parents.get_mut(child.value()) {
+					// reported by invalid_indices
+					None => {}
+					Some(Some(parent)) => errors.push(GltrError::ConstraintViolation(format!(
+						"/nodes/{}: node has two parents, {parent} and {i}",
+						child.value()
+					))),
+					Some(parent) => *parent = Some(i),
+				}
+			}
+		}
+
+		for start in 0..self.nodes.len() {
+			let mut current = start;
+			for _ in 0..self.nodes.len() {
+				match parents[current] {
+					None => break,
+					Some(parent) if parent == start => {
+						errors.push(GltrError::ConstraintViolation(format!("/nodes/{start}: node is its own ancestor")));
+						break;
+					}
+					Some(parent) => current = parent,
+				}
+			}
+		}
+
+		for (i, mesh) in self.meshes.iter().enumerate() {
+			for (p, primitive) in mesh.primitives.iter().enumerate() {
+				let counts: Vec<(&String, usize)> = primitive
+					.attributes
+					.iter()
+					.filter_map(|(name, a)| self.get(*a).map(|a| (name, a.count)))
+					.collect();
+
+				if let Some((name, count)) = counts.iter().find(|(_, c)| *c != counts[0].1) {
+					errors.push(GltrError::ConstraintViolation(format!(
+						"/meshes/{i}/primitives/{p}/attributes/{name}: count {count} differs from {} of {}",
+						counts[0].1, counts[0].0
+					)));
+				}
+			}
+		}
+
+		for (i, animation) in self.animations.iter().enumerate() {
+			for (c, channel) in animation.channels.iter().enumerate() {
+				if channel.sampler.value() >= animation.samplers.len() {
+					errors.push(GltrError::invalid_index(format!("/animations/{i}/channels/{c}/sampler"), channel.sampler.value()));
+				}
+			}
+		}
+
+		for (i, node) in self.nodes.iter().enumerate() {
+			if let Some(l) = node.extension::<KhrLightsPunctualNode>() {
+				if l.light.value() >= self.lights().len() {
+					errors.push(GltrError::invalid_index(
+						format!("/nodes/{i}/extensions/KHR_lights_punctual/light"),
+						l.light.value(),
+					));
+				}
+			}
+		}
+
+		for name in &self.extensions_required {
+			if !self.extensions_used.contains(name) {
+				errors.push(GltrError::ConstraintViolation(format!(
+					"/extensionsRequired: {name} is not in extensionsUsed"
+				)));
+			}
+		}
+
+		errors
+	}
+}
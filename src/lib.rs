@@ -1,11 +1,17 @@
 #[cfg(test)]
 mod tests;
 pub mod buffers;
+mod diff;
+mod error;
 mod gltf_object;
+mod math;
 mod ops;
 
+pub use crate::diff::{diff, ChangeKind, EntityDiff, GltfDiff, PropertyChange};
+
 pub mod prelude {
 	pub use crate::buffers::*;
+	pub use crate::diff::*;
 	pub use crate::gltf_object::prelude::*;
 	pub use crate::gltf_object::*;
 }
@@ -44,6 +50,26 @@ pub(crate) mod defaults {
 	}
 
 
+	pub(crate) const fn default_f32_vec2_1() -> [f32; 2] {
+		[1f32, 1f32]
+	}
+
+	pub(crate) const fn default_f32_vec2_0() -> [f32; 2] {
+		[0f32, 0f32]
+	}
+
+	pub(crate) const fn material_default_ior() -> f32 {
+		1.5f32
+	}
+
+	pub(crate) const fn light_spot_default_outer_cone_angle() -> f32 {
+		std::f32::consts::FRAC_PI_4
+	}
+
+	pub(crate) const fn material_default_alpha_cutoff() -> f32 {
+		0.5f32
+	}
+
 	pub(crate) fn material_default_alpha_mode() -> String {
 		"OPAQUE".to_string()
 	}
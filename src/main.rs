@@ -0,0 +1,281 @@
+use gltr::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: gltr <command> [arguments]
+
+commands:
+	split <in> <outdir> [--center] [--recalculate-buffers]
+		writes every node with a mesh to its own .gltf file in outdir
+	info <in> [--json]
+		prints what the file contains, or its statistics as JSON
+	validate <in>...
+		checks every file, printing the problems found
+	diff <old> <new> [--json]
+		prints the nodes, meshes and materials that changed, exiting with 1 when there are any
+	merge <in>... <out>
+		combines the files into one
+	prune <in> <out>
+		removes everything the file does not use
+	pack <in> <out.glb>
+		writes a .glb file with every buffer and image embedded
+	unpack <in.glb> <out.gltf>
+		writes a .gltf file with its buffer and images as separate files
+
+exit status: 0 on success, 1 when a command fails, validation finds problems or files differ, 2 for invalid
+arguments";
+
+enum CliError {
+	Usage(String),
+	Gltr(GltrError),
+}
+
+impl From<GltrError> for CliError {
+	fn from(value: GltrError) -> Self {
+		CliError::Gltr(value)
+	}
+}
+
+impl From<std::io::Error> for CliError {
+	fn from(value: std::io::Error) -> Self {
+		CliError::Gltr(value.into())
+	}
+}
+
+type CliResult<T> = Result<T, CliError>;
+
+fn main() -> ExitCode {
+	let args: Vec<String> = std::env::args().skip(1).collect();
+
+	if args.is_empty() || args.iter().any(|a| a == "-h" || a == "--help") {
+		println!("{USAGE}");
+		return if args.is_empty() { ExitCode::from(2) } else { ExitCode::SUCCESS };
+	}
+
+	match run(&args[0], &args[1..]) {
+		Ok(true) => ExitCode::SUCCESS,
+		Ok(false) => ExitCode::from(1),
+		Err(CliError::Usage(message)) => {
+			eprintln!("{message}\n\n{USAGE}");
+			ExitCode::from(2)
+		}
+		Err(CliError::Gltr(e)) => {
+			eprintln!("error: {e}");
+			ExitCode::from(1)
+		}
+	}
+}
+
+/// runs a command, returning false when it completed but found problems
+fn run(command: &str, args: &[String]) -> CliResult<bool> {
+	let (flags, paths): (Vec<&String>, Vec<&String>) = args.iter().partition(|a| a.starts_with("--"));
+
+	let allowed: &[&str] = match command {
+		"split" => &["--center", "--recalculate-buffers"],
+		"info" | "diff" => &["--json"],
+		_ => &[],
+	};
+	if let Some(flag) = flags.iter().find(|f| !allowed.contains(&f.as_str())) {
+		return Err(CliError::Usage(format!("unknown option {flag} for {command}")));
+	}
+
+	match (command, paths.as_slice()) {
+		("split", [input, outdir]) => {
+			let mut extract_flags = GltrExtractFlags::empty();
+			if flags.iter().any(|f| *f == "--center") {
+				extract_flags = extract_flags.center_objects();
+			}
+			if flags.iter().any(|f| *f == "--recalculate-buffers") {
+				extract_flags = extract_flags.recalculate_buffers();
+			}
+
+			split(Path::new(input), Path::new(outdir), extract_flags)?;
+			Ok(true)
+		}
+		("info", [input]) => {
+			let object = load(Path::new(input))?;
+			let stats = object.stats()?;
+
+			if flags.iter().any(|f| *f == "--json") {
+				let json = serde_json::to_string_pretty(&stats).map_err(GltrError::from)?;
+				println!("{json}");
+			} else {
+				info(&object, &stats);
+			}
+			Ok(true)
+		}
+		("validate", inputs) if !inputs.is_empty() => validate(inputs),
+		("diff", [old, new]) => {
+			let changes = gltr::diff(&load(Path::new(old))?, &load(Path::new(new))?)?;
+
+			if flags.iter().any(|f| *f == "--json") {
+				let json = serde_json::to_string_pretty(&changes).map_err(GltrError::from)?;
+				println!("{json}");
+			} else {
+				print!("{changes}");
+			}
+			Ok(changes.is_empty())
+		}
+		("merge", [inputs @ .., output]) if !inputs.is_empty() => {
+			let mut merged = load(Path::new(inputs[0]))?;
+			for input in &inputs[1..] {
+				merged.merge(load(Path::new(input))?);
+			}
+
+			merged.save(output)?;
+			Ok(true)
+		}
+		("prune", [input, output]) => {
+			let mut object = load(Path::new(input))?;
+			let removed = object.prune()?;
+			object.save(output)?;
+
+			println!("removed {removed} unused objects");
+			Ok(true)
+		}
+		("pack", [input, output]) => {
+			let input = Path::new(input);
+			let mut object = load(input)?;
+			object.embed_images(&base_dir(input))?;
+
+			fs::write(output, object.to_glb()?)?;
+			Ok(true)
+		}
+		("unpack", [input, output]) => {
+			unpack(Path::new(input), Path::new(output))?;
+			Ok(true)
+		}
+		("split" | "info" | "validate" | "diff" | "merge" | "prune" | "pack" | "unpack", _) => {
+			Err(CliError::Usage(format!("wrong number of arguments for {command}")))
+		}
+		_ => Err(CliError::Usage(format!("unknown command {command}"))),
+	}
+}
+
+/// the directory relative uris in the file at `path` are resolved against
+fn base_dir(path: &Path) -> PathBuf {
+	path.parent().map(Path::to_path_buf).unwrap_or_default()
+}
+
+/// loads a .gltf or .glb file, reading buffers stored in external files into the document
+fn load(path: &Path) -> CliResult<GltfObject> {
+	let mut object = GltfObject::load(path)?;
+	object.embed_buffers(&base_dir(path))?;
+
+	Ok(object)
+}
+
+fn split(input: &Path, outdir: &Path, flags: GltrExtractFlags) -> CliResult<()> {
+	let object = load(input)?;
+	fs::create_dir_all(outdir)?;
+
+	let mut written = 0;
+	for (idx, node) in object.nodes.iter().enumerate() {
+		if node.mesh.is_none() {
+			continue;
+		}
+
+		let name = match &node.name {
+			Some(name) => {
+				let name: String = name.chars().map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' }).collect();
+				format!("{idx}_{name}.gltf")
+			}
+			None => format!("node_{idx}.gltf"),
+		};
+
+		let extracted = object.extract_node(Index::new(idx), flags)?;
+		extracted.save(outdir.join(name))?;
+		written += 1;
+	}
+
+	println!("wrote {written} files to {}", outdir.display());
+	Ok(())
+}
+
+fn info(object: &GltfObject, stats: &GltfStats) {
+	let counts = [
+		("scenes", object.scenes.len()),
+		("nodes", object.nodes.len()),
+		("meshes", object.meshes.len()),
+		("materials", object.materials.len()),
+		("textures", object.textures.len()),
+		("images", object.images.len()),
+		("samplers", object.samplers.len()),
+		("accessors", object.accessors.len()),
+		("bufferViews", object.buffer_views.len()),
+		("buffers", object.buffers.0.len()),
+		("cameras", object.cameras.len()),
+		("skins", object.skins.len()),
+		("animations", object.animations.len()),
+		("lights", object.lights().len()),
+	];
+
+	println!("glTF {}, generator {}", object.asset.version, object.asset.generator.as_deref().unwrap_or("unknown"));
+	for (name, count) in counts {
+		println!("{name:>12}: {count}");
+	}
+
+	let buffer_bytes: usize = stats.buffers.iter().map(|b| b.byte_length).sum();
+	let unused_bytes: usize = stats.buffers.iter().map(|b| b.unused_bytes).sum();
+	let gpu_bytes: usize = stats.images.iter().filter_map(|i| i.gpu_bytes).sum();
+
+	println!("{:>12}: {buffer_bytes} ({unused_bytes} unused)", "buffer bytes");
+	println!("{:>12}: {gpu_bytes}", "image memory");
+	println!("{:>12}: {}", "draw calls", stats.draw_calls);
+	println!("{:>12}: {}", "vertices", stats.scene_vertices);
+	println!("{:>12}: {}", "triangles", stats.scene_triangles);
+
+	if !object.extensions_used.is_empty() {
+		println!("extensions used: {}", object.extensions_used.join(", "));
+	}
+	if !object.extensions_required.is_empty() {
+		println!("extensions required: {}", object.extensions_required.join(", "));
+	}
+}
+
+fn validate(inputs: &[&String]) -> CliResult<bool> {
+	let mut valid = true;
+
+	for input in inputs {
+		let (errors, unchecked) = match load(Path::new(input)) {
+			Err(CliError::Gltr(e)) => (vec![e], vec![]),
+			Err(e) => return Err(e),
+			Ok(object) => (object.validate(), object.unchecked_extensions()),
+		};
+
+		if errors.is_empty() {
+			println!("{input}: valid");
+		} else {
+			valid = false;
+			println!("{input}: {} problems", errors.len());
+			for e in errors {
+				println!("\t{e}");
+			}
+		}
+
+		for pointer in unchecked {
+			println!("\t{pointer}: not checked, the references held by this extension are unknown");
+		}
+	}
+
+	Ok(valid)
+}
+
+fn unpack(input: &Path, output: &Path) -> CliResult<()> {
+	let mut object = load(input)?;
+
+	let stem = match output.file_stem() {
+		None => return Err(CliError::Usage(format!("{} is not a file name", output.display()))),
+		Some(stem) => stem.to_string_lossy().into_owned(),
+	};
+
+	let dir = base_dir(output);
+	fs::create_dir_all(&dir)?;
+	for (name, bytes) in object.externalize(&stem)? {
+		fs::write(dir.join(name), bytes)?;
+	}
+
+	fs::write(output, object.to_json_string_pretty()?)?;
+	Ok(())
+}
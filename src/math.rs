@@ -0,0 +1,245 @@
+//! small vector, quaternion and matrix helpers over plain arrays.
+//!
+//! matrices are column-major `[f32; 16]`, the same layout as node.matrix, quaternions are `[x, y, z, w]`
+//! as in node.rotation
+
+pub(crate) type Vec3 = [f32; 3];
+pub(crate) type Quat = [f32; 4];
+pub(crate) type Mat4 = [f32; 16];
+
+pub(crate) const IDENTITY: Mat4 = [
+	1f32, 0f32, 0f32, 0f32, //
+	0f32, 1f32, 0f32, 0f32, //
+	0f32, 0f32, 1f32, 0f32, //
+	0f32, 0f32, 0f32, 1f32,
+];
+
+pub(crate) const QUAT_IDENTITY: Quat = [0f32, 0f32, 0f32, 1f32];
+
+pub(crate) fn add(a: Vec3, b: Vec3) -> Vec3 {
+	[a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+pub(crate) fn sub(a: Vec3, b: Vec3) -> Vec3 {
+	[a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub(crate) fn scale(a: Vec3, s: f32) -> Vec3 {
+	[a[0] * s, a[1] * s, a[2] * s]
+}
+
+pub(crate) fn dot(a: Vec3, b: Vec3) -> f32 {
+	a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub(crate) fn cross(a: Vec3, b: Vec3) -> Vec3 {
+	[
+		a[1] * b[2] - a[2] * b[1],
+		a[2] * b[0] - a[0] * b[2],
+		a[0] * b[1] - a[1] * b[0],
+	]
+}
+
+pub(crate) fn length(a: Vec3) -> f32 {
+	dot(a, a).sqrt()
+}
+
+/// normalizes `a`, returning the zero vector unchanged
+pub(crate) fn normalize(a: Vec3) -> Vec3 {
+	let l = length(a);
+	if l > 0f32 {
+		scale(a, 1f32 / l)
+	} else {
+		a
+	}
+}
+
+pub(crate) fn min(a: Vec3, b: Vec3) -> Vec3 {
+	[a[0].min(b[0]), a[1].min(b[1]), a[2].min(b[2])]
+}
+
+pub(crate) fn max(a: Vec3, b: Vec3) -> Vec3 {
+	[a[0].max(b[0]), a[1].max(b[1]), a[2].max(b[2])]
+}
+
+pub(crate) fn quat_normalize(q: Quat) -> Quat {
+	let l = (q[0] * q[0] + q[1] * q[1] + q[2] * q[2] + q[3] * q[3]).sqrt();
+	if l > 0f32 {
+		[q[0] / l, q[1] / l, q[2] / l, q[3] / l]
+	} else {
+		QUAT_IDENTITY
+	}
+}
+
+pub(crate) fn quat_mul(a: Quat, b: Quat) -> Quat {
+	[
+		a[3] * b[0] + a[0] * b[3] + a[1] * b[2] - a[2] * b[1],
+		a[3] * b[1] - a[0] * b[2] + a[1] * b[3] + a[2] * b[0],
+		a[3] * b[2] + a[0] * b[1] - a[1] * b[0] + a[2] * b[3],
+		a[3] * b[3] - a[0] * b[0] - a[1] * b[1] - a[2] * b[2],
+	]
+}
+
+pub(crate) fn quat_rotate(q: Quat, v: Vec3) -> Vec3 {
+	let u = [q[0], q[1], q[2]];
+	let t = scale(cross(u, v), 2f32);
+	add(add(v, scale(t, q[3])), cross(u, t))
+}
+
+/// the upper 3x3 of an orthonormal rotation matrix as a quaternion
+fn quat_from_rotation(m: &Mat4) -> Quat {
+	let (m00, m01, m02) = (m[0], m[4], m[8]);
+	let (m10, m11, m12) = (m[1], m[5], m[9]);
+	let (m20, m21, m22) = (m[2], m[6], m[10]);
+
+	let trace = m00 + m11 + m22;
+	let q = if trace > 0f32 {
+		let s = (trace + 1f32).sqrt() * 2f32;
+		[(m21 - m12) / s, (m02 - m20) / s, (m10 - m01) / s, 0.25f32 * s]
+	} else if m00 > m11 && m00 > m22 {
+		let s = (1f32 + m00 - m11 - m22).sqrt() * 2f32;
+		[0.25f32 * s, (m01 + m10) / s, (m02 + m20) / s, (m21 - m12) / s]
+	} else if m11 > m22 {
+		let s = (1f32 + m11 - m00 - m22).sqrt() * 2f32;
+		[(m01 + m10) / s, 0.25f32 * s, (m12 + m21) / s, (m02 - m20) / s]
+	} else {
+		let s = (1f32 + m22 - m00 - m11).sqrt() * 2f32;
+		[(m02 + m20) / s, (m12 + m21) / s, 0.25f32 * s, (m10 - m01) / s]
+	};
+
+	quat_normalize(q)
+}
+
+pub(crate) fn mat4_mul(a: &Mat4, b: &Mat4) -> Mat4 {
+	let mut out = [0f32; 16];
+	for c in 0..4 {
+		for r in 0..4 {
+			out[c * 4 + r] = (0..4).map(|k| a[k * 4 + r] * b[c * 4 + k]).sum();
+		}
+	}
+	out
+}
+
+pub(crate) fn mat4_from_trs(t: Vec3, r: Quat, s: Vec3) -> Mat4 {
+	let [x, y, z, w] = r;
+	let (x2, y2, z2) = (x + x, y + y, z + z);
+	let (xx, xy, xz) = (x * x2, x * y2, x * z2);
+	let (yy, yz, zz) = (y * y2, y * z2, z * z2);
+	let (wx, wy, wz) = (w * x2, w * y2, w * z2);
+
+	[
+		(1f32 - (yy + zz)) * s[0],
+		(xy + wz) * s[0],
+		(xz - wy) * s[0],
+		0f32,
+		(xy - wz) * s[1],
+		(1f32 - (xx + zz)) * s[1],
+		(yz + wx) * s[1],
+		0f32,
+		(xz + wy) * s[2],
+		(yz - wx) * s[2],
+		(1f32 - (xx + yy)) * s[2],
+		0f32,
+		t[0],
+		t[1],
+		t[2],
+		1f32,
+	]
+}
+
+/// splits an affine matrix into translation, rotation and scale. shear is lost
+pub(crate) fn mat4_decompose(m: &Mat4) -> (Vec3, Quat, Vec3) {
+	let t = [m[12], m[13], m[14]];
+
+	let mut s = [
+		length([m[0], m[1], m[2]]),
+		length([m[4], m[5], m[6]]),
+		length([m[8], m[9], m[10]]),
+	];
+
+	if mat4_determinant3(m) < 0f32 {
+		s[0] = -s[0];
+	}
+
+	let mut rotation = *m;
+	for c in 0..3 {
+		let inv = if s[c] != 0f32 { 1f32 / s[c] } else { 0f32 };
+		for r in 0..3 {
+			rotation[c * 4 + r] *= inv;
+		}
+	}
+
+	(t, quat_from_rotation(&rotation), s)
+}
+
+/// determinant of the upper 3x3
+pub(crate) fn mat4_determinant3(m: &Mat4) -> f32 {
+	dot([m[0], m[1], m[2]], cross([m[4], m[5], m[6]], [m[8], m[9], m[10]]))
+}
+
+pub(crate) fn transform_point(m: &Mat4, p: Vec3) -> Vec3 {
+	[
+		m[0] * p[0] + m[4] * p[1] + m[8] * p[2] + m[12],
+		m[1] * p[0] + m[5] * p[1] + m[9] * p[2] + m[13],
+		m[2] * p[0] + m[6] * p[1] + m[10] * p[2] + m[14],
+	]
+}
+
+pub(crate) fn transform_vector(m: &Mat4, v: Vec3) -> Vec3 {
+	[
+		m[0] * v[0] + m[4] * v[1] + m[8] * v[2],
+		m[1] * v[0] + m[5] * v[1] + m[9] * v[2],
+		m[2] * v[0] + m[6] * v[1] + m[10] * v[2],
+	]
+}
+
+/// inverse of an affine matrix, None when it is singular
+pub(crate) fn mat4_inverse_affine(m: &Mat4) -> Option<Mat4> {
+	let det = mat4_determinant3(m);
+	if det.abs() < f32::EPSILON {
+		return None;
+	}
+
+	let c0 = [m[0], m[1], m[2]];
+	let c1 = [m[4], m[5], m[6]];
+	let c2 = [m[8], m[9], m[10]];
+
+	// rows of the inverse 3x3 are the cross products of the columns
+	let r0 = scale(cross(c1, c2), 1f32 / det);
+	let r1 = scale(cross(c2, c0), 1f32 / det);
+	let r2 = scale(cross(c0, c1), 1f32 / det);
+
+	let t = [m[12], m[13], m[14]];
+
+	Some([
+		r0[0],
+		r1[0],
+		r2[0],
+		0f32,
+		r0[1],
+		r1[1],
+		r2[1],
+		0f32,
+		r0[2],
+		r1[2],
+		r2[2],
+		0f32,
+		-dot(r0, t),
+		-dot(r1, t),
+		-dot(r2, t),
+		1f32,
+	])
+}
+
+/// the matrix used to transform normals, the inverse transpose of the upper 3x3
+pub(crate) fn normal_matrix(m: &Mat4) -> Mat4 {
+	match mat4_inverse_affine(m) {
+		None => IDENTITY,
+		Some(inv) => [
+			inv[0], inv[4], inv[8], 0f32, //
+			inv[1], inv[5], inv[9], 0f32, //
+			inv[2], inv[6], inv[10], 0f32, //
+			0f32, 0f32, 0f32, 1f32,
+		],
+	}
+}
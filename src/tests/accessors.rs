@@ -0,0 +1,171 @@
+use crate::buffers::BufferViewTarget;
+use crate::prelude::*;
+use crate::tests::cliffs;
+
+#[test]
+fn read_positions_within_bounds() {
+	let object = cliffs();
+
+	let positions = object.read_accessor_array::<3>(Index::new(0)).expect("accessor 0 should be readable");
+	let accessor = &object.accessors[0];
+
+	assert_eq!(positions.len(), accessor.count);
+	for p in &positions {
+		for (i, v) in p.iter().enumerate() {
+			assert!(*v >= accessor.min[i] && *v <= accessor.max[i]);
+		}
+	}
+
+	assert!(object.read_accessor_array::<2>(Index::new(0)).is_err());
+
+	let indices = object.read_accessor_u32(Index::new(4)).expect("accessor 4 should be readable");
+	assert!(indices.iter().all(|x| (*x as usize) < accessor.count));
+}
+
+#[test]
+fn write_and_read_back() {
+	let mut object = GltfObject::new();
+
+	let normals = [0.0, 1.0, -1.0, 0.5, -0.25, 0.125];
+	let idx = object
+		.push_accessor_f32(&normals, "VEC3", ComponentType::SHORT, true, Some(BufferViewTarget::ARRAY_BUFFER))
+		.unwrap();
+
+	let read = object.read_accessor_f32(idx).unwrap();
+	for (a, b) in normals.iter().zip(read.iter()) {
+		assert!((a - b).abs() < 1e-4);
+	}
+
+	assert_eq!(object.try_get(idx).unwrap().max, vec![16384.0, 32767.0, 4096.0]);
+	assert_eq!(object.read_accessor_raw(idx).unwrap()[1], 32767.0);
+
+	// MAT3 of bytes pads every column to 4 bytes
+	let matrix: Vec<f32> = (1..=9).map(|x| x as f32).collect();
+	let idx = object.push_accessor_f32(&matrix, "MAT3", ComponentType::UNSIGNED_BYTE, false, None).unwrap();
+	assert_eq!(object.buffer_views[object.accessors[idx.value()].buffer_view.unwrap().value()].byte_length, 12);
+	assert_eq!(object.read_accessor_f32(idx).unwrap(), matrix);
+
+	let offset = object.buffer_views[object.accessors[idx.value()].buffer_view.unwrap().value()].byte_offset;
+	assert_eq!(offset % 4, 0);
+}
+
+#[test]
+fn push_buffer_view_appends_to_the_data_uri() {
+	let mut object = GltfObject::new();
+	let mut expected: Vec<u8> = vec![];
+
+	// every length of trailing partial base64 group, and pieces that end on each of them
+	for (i, length) in [1, 2, 3, 4, 5, 7, 11, 0, 6].into_iter().enumerate() {
+		let piece: Vec<u8> = (0..length).map(|b| (i * 16 + b) as u8).collect();
+		let view = object.push_buffer_view(&piece, None).unwrap();
+
+		expected.resize(expected.len().next_multiple_of(4), 0);
+		assert_eq!(object.get(view).unwrap().byte_offset, expected.len());
+		expected.extend_from_slice(&piece);
+
+		assert_eq!(object.buffers.0[0].bytes().unwrap(), expected);
+		assert_eq!(object.buffers.0[0].byte_length, expected.len());
+		assert_eq!(object.buffer_view_bytes(view).unwrap(), piece);
+	}
+
+	// a byteLength that doesn't match the data is corrected rather than trusted
+	object.buffers.0[0].byte_length = 1;
+	let view = object.push_buffer_view(&[9, 9], None).unwrap();
+	assert_eq!(object.get(view).unwrap().byte_offset, expected.len().next_multiple_of(4));
+	assert_eq!(object.buffers.0[0].byte_length, expected.len().next_multiple_of(4) + 2);
+	assert_eq!(object.buffer_view_bytes(view).unwrap(), vec![9, 9]);
+}
+
+#[test]
+fn recompute_bounds_of_normalized_and_sparse_accessors() {
+	let mut object = GltfObject::new();
+
+	// bounds stay in the stored domain of normalized integers
+	let normals = [0.0, 1.0, -1.0, 0.5, -0.25, 0.125];
+	let normalized = object.push_accessor_f32(&normals, "VEC3", ComponentType::SHORT, true, None).unwrap();
+	object.accessors[normalized.value()].max = vec![0.5, 1.0, 0.125];
+
+	// zeros with the second element replaced by the sparse values
+	let indices = object.push_buffer_view(&[1u8], None).unwrap();
+	let values: Vec<u8> = [2f32, -3.0, 4.0].iter().flat_map(|v| v.to_le_bytes()).collect();
+	let values = object.push_buffer_view(&values, None).unwrap();
+	let sparse = object.push(GltfAccessor {
+		buffer_view: None,
+		byte_offset: None,
+		component_type: ComponentType::FLOAT,
+		normalized: false,
+		count: 3,
+		r#type: "VEC3".to_string(),
+		max: vec![],
+		min: vec![],
+		sparse: Some(
+			serde_json::from_value(serde_json::json!({
+				"count": 1,
+				"indices": { "bufferView": indices.value(), "componentType": 5121 },
+				"values": { "bufferView": values.value() },
+			}))
+			.unwrap(),
+		),
+		name: None,
+		extensions: None,
+		extras: None,
+		unknown_fields: UnknownFields::new(),
+	});
+	object.push(GltfMesh {
+		primitives: vec![GltfMeshPrimitive {
+			attributes: [("POSITION".to_string(), sparse)].into(),
+			accessor: None,
+			material: None,
+			mode: None,
+			targets: None,
+			extensions: None,
+			extras: None,
+			unknown_fields: UnknownFields::new(),
+			original_index: None,
+		}],
+		..GltfMesh::default()
+	});
+
+	let before = object.to_json_value().unwrap();
+	let mismatches = object.check_bounds().unwrap();
+	assert_eq!(object.to_json_value().unwrap(), before);
+
+	assert_eq!(mismatches.len(), 2);
+	assert_eq!(mismatches[0].accessor, normalized);
+	assert_eq!(mismatches[0].expected_max, vec![16384.0, 32767.0, 4096.0]);
+	assert_eq!(mismatches[1].accessor, sparse);
+	assert!(mismatches[1].min.is_empty());
+	assert_eq!(mismatches[1].expected_min, vec![0.0, -3.0, 0.0]);
+	assert_eq!(mismatches[1].expected_max, vec![2.0, 0.0, 4.0]);
+
+	assert_eq!(object.recompute_bounds().unwrap(), 2);
+	assert!(object.check_bounds().unwrap().is_empty());
+	assert_eq!(object.accessors[normalized.value()].max, vec![16384.0, 32767.0, 4096.0]);
+}
+
+#[test]
+fn recompute_bounds_of_exported_asset() {
+	let mut object = cliffs();
+	let mismatches = object.check_bounds().unwrap();
+	assert_eq!(object.recompute_bounds().unwrap(), mismatches.len());
+	assert!(object.check_bounds().unwrap().is_empty());
+}
+
+#[test]
+fn compact_indices_avoid_primitive_restart() {
+	let mut object = GltfObject::new();
+
+	for (max, component_type) in [
+		(254, ComponentType::UNSIGNED_BYTE),
+		(255, ComponentType::UNSIGNED_SHORT),
+		(65534, ComponentType::UNSIGNED_SHORT),
+		(65535, ComponentType::UNSIGNED_INT),
+	] {
+		let idx = object.push_accessor_indices_compact(&[0, max, 1]).unwrap();
+		assert_eq!(object.get(idx).unwrap().component_type, component_type, "max index {max}");
+		assert_eq!(object.read_accessor_u32(idx).unwrap(), vec![0, max, 1]);
+	}
+
+	let idx = object.push_accessor_indices(&[0, 65535, 1]).unwrap();
+	assert_eq!(object.get(idx).unwrap().component_type, ComponentType::UNSIGNED_INT);
+}
@@ -0,0 +1,170 @@
+use crate::prelude::*;
+use crate::tests::{close, triangle};
+
+/// a triangle facing +Z on a child 2 above the root, with the root translation animated
+fn animated_triangle() -> (GltfObject, NodeId, NodeId) {
+	let mut builder = GltfBuilder::new();
+	let mesh = builder
+		.add_mesh(vec![PrimitiveData { normals: vec![[0.0, 0.0, 1.0]; 3], ..triangle() }])
+		.unwrap();
+
+	let root = builder.add_node(None, NodeTransform::from_translation([1.0, 0.0, 0.0])).unwrap();
+	let child = builder.add_mesh_node(Some(root), NodeTransform::from_translation([0.0, 2.0, 0.0]), mesh).unwrap();
+	builder.add_scene(None, &[root]).unwrap();
+	let mut object = builder.build();
+
+	let input = object.push_accessor_f32(&[0.0, 1.0], "SCALAR", ComponentType::FLOAT, false, None).unwrap();
+	let output = object.push_accessor_array::<3>(&[[1.0, 0.0, 0.0], [1.0, 0.0, 3.0]], None).unwrap();
+	object.animations.push(
+		serde_json::from_value(serde_json::json!({
+			"channels": [{ "sampler": 0, "target": { "node": root.value(), "path": "translation" } }],
+			"samplers": [{ "input": input.value(), "output": output.value() }],
+		}))
+		.unwrap(),
+	);
+
+	(object, root, child)
+}
+
+/// the world position of the vertices of the mesh of `node`
+fn world_vertices(object: &GltfObject, node: NodeId) -> Vec<[f32; 3]> {
+	let world = object.node_world_matrix(node).unwrap();
+	let mesh = object.get(node).unwrap().mesh.unwrap();
+	let positions = object.read_accessor_array::<3>(object.primitive(mesh, 0).unwrap().attributes["POSITION"]).unwrap();
+	positions.iter().map(|p| Aabb { min: *p, max: *p }.transformed(&world).min).collect()
+}
+
+fn translation_keys(object: &GltfObject) -> Vec<f32> {
+	object.read_accessor_f32(object.animations[0].samplers[0].output).unwrap()
+}
+
+#[test]
+fn convert_axes_on_roots() {
+	let (mut object, root, child) = animated_triangle();
+	object.convert_axes(CoordinateSystem::GLTF, CoordinateSystem::Z_UP, false).unwrap();
+
+	// y up becomes z up, z forward becomes -y
+	let world = world_vertices(&object, child);
+	assert!(close(&world.concat(), &[1.0, 0.0, 2.0, 2.0, 0.0, 2.0, 1.0, 0.0, 3.0]));
+	assert!(close(&object.get(root).unwrap().translation.unwrap(), &[1.0, 0.0, 0.0]));
+	assert!(close(&translation_keys(&object), &[1.0, 0.0, 0.0, 1.0, -3.0, 0.0]));
+
+	// the geometry itself is left alone
+	assert!(object.primitive(object.get(child).unwrap().mesh.unwrap(), 0).unwrap().attributes["POSITION"].value() == 0);
+	assert!(object.validate().is_empty());
+}
+
+#[test]
+fn convert_axes_baked() {
+	let (mut object, root, child) = animated_triangle();
+	object.convert_axes(CoordinateSystem::GLTF, CoordinateSystem::Z_UP, true).unwrap();
+
+	let world = world_vertices(&object, child);
+	assert!(close(&world.concat(), &[1.0, 0.0, 2.0, 2.0, 0.0, 2.0, 1.0, 0.0, 3.0]));
+	assert!(close(&object.get(child).unwrap().translation.unwrap(), &[0.0, 0.0, 2.0]));
+	assert!(close(&translation_keys(&object), &[1.0, 0.0, 0.0, 1.0, -3.0, 0.0]));
+	assert_eq!(object.get(root).unwrap().rotation, None);
+
+	let primitive = object.primitive(object.get(child).unwrap().mesh.unwrap(), 0).unwrap().clone();
+	let normals = object.read_accessor_array::<3>(primitive.attributes["NORMAL"]).unwrap();
+	assert!(normals.iter().all(|n| close(n, &[0.0, -1.0, 0.0])));
+
+	object.prune().unwrap();
+	assert!(object.check_bounds().unwrap().is_empty());
+	assert!(object.validate().is_empty());
+}
+
+/// the world position, view direction (-Z) and up direction (+Y) of a node
+fn world_view(object: &GltfObject, node: NodeId) -> Vec<f32> {
+	let m = object.node_world_matrix(node).unwrap();
+	vec![m[12], m[13], m[14], -m[8], -m[9], -m[10], m[4], m[5], m[6]]
+}
+
+#[test]
+fn convert_axes_baked_keeps_cameras_and_lights_aimed() {
+	let (mut object, root, _) = animated_triangle();
+	object.cameras.push(
+		serde_json::from_value(serde_json::json!({ "type": "perspective", "perspective": { "yfov": 1.0, "znear": 0.1 } })).unwrap(),
+	);
+	let light = object.add_light(KhrLight::new(KhrLightType::Spot));
+
+	// a camera looking horizontally along -Z and a spot light pointing down
+	let camera = object.push(GltfNode { camera: Some(Index::new(0)), translation: Some([0.0, 0.0, 5.0]), ..GltfNode::default() });
+	let mut spot = GltfNode { rotation: Some([-std::f32::consts::FRAC_1_SQRT_2, 0.0, 0.0, std::f32::consts::FRAC_1_SQRT_2]), ..GltfNode::default() };
+	spot.set_extension(KhrLightsPunctualNode::new(light));
+	let spot = object.push(spot);
+	object.nodes[root.value()].children.get_or_insert_with(Vec::new).extend([camera, spot]);
+
+	assert!(close(&world_view(&object, camera), &[1.0, 0.0, 5.0, 0.0, 0.0, -1.0, 0.0, 1.0, 0.0]));
+	assert!(close(&world_view(&object, spot)[3..6], &[0.0, -1.0, 0.0]));
+
+	object.convert_axes(CoordinateSystem::GLTF, CoordinateSystem::Z_UP, true).unwrap();
+
+	// -z becomes +y and y becomes z, the camera still looks horizontally and the light still points down
+	let camera = object.nodes.iter().position(|n| n.camera.is_some()).map(Index::new).unwrap();
+	assert!(close(&world_view(&object, camera), &[1.0, -5.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0]));
+
+	let spot = object.nodes.iter().position(|n| n.extension::<KhrLightsPunctualNode>().is_some()).map(Index::new).unwrap();
+	assert!(close(&world_view(&object, spot)[3..6], &[0.0, 0.0, -1.0]));
+	assert_eq!(object.nodes.iter().filter(|n| n.camera.is_some() || n.extensions.is_some()).count(), 2);
+	assert!(object.validate().is_empty());
+}
+
+#[test]
+fn mirroring_conversion_keeps_winding() {
+	// left handed: z up, x forward, y right
+	let left_handed = CoordinateSystem { up: Axis::Z, forward: Axis::X, right: Axis::Y };
+
+	let (mut object, ..) = animated_triangle();
+	assert!(object.convert_axes(CoordinateSystem::GLTF, left_handed, false).is_err());
+
+	// an animated root after a still one, which is left as it was
+	let (mut object, root, _) = animated_triangle();
+	let later = object.push(GltfNode { translation: Some([0.0, 1.0, 0.0]), ..GltfNode::default() });
+	object.scenes[0].nodes.push(later);
+	object.animations[0].channels[0].target.node = Some(later);
+	assert!(object.convert_axes(CoordinateSystem::GLTF, left_handed, false).is_err());
+	assert_eq!(object.get(root).unwrap().matrix, None);
+	assert_eq!(object.get(root).unwrap().translation, Some([1.0, 0.0, 0.0]));
+
+	let (mut object, _, child) = animated_triangle();
+	object.convert_axes(CoordinateSystem::GLTF, left_handed, true).unwrap();
+
+	let mesh = object.get(child).unwrap().mesh.unwrap();
+	let primitive = object.primitive(mesh, 0).unwrap();
+	let positions = object.read_accessor_array::<3>(primitive.attributes["POSITION"]).unwrap();
+	let normal = object.read_accessor_array::<3>(primitive.attributes["NORMAL"]).unwrap()[0];
+	let [a, b, c] = object.primitive_triangles(mesh, 0).unwrap().next().unwrap().map(|i| positions[i as usize]);
+
+	// the face normal from the winding still agrees with NORMAL
+	let (u, v) = ([b[0] - a[0], b[1] - a[1], b[2] - a[2]], [c[0] - a[0], c[1] - a[1], c[2] - a[2]]);
+	let face = [u[1] * v[2] - u[2] * v[1], u[2] * v[0] - u[0] * v[2], u[0] * v[1] - u[1] * v[0]];
+	assert!(face.iter().zip(normal).map(|(f, n)| f * n).sum::<f32>() > 0.0);
+	assert!(close(&normal, &[1.0, 0.0, 0.0]));
+}
+
+#[test]
+fn scale_units_to_centimetres() {
+	let (mut object, root, child) = animated_triangle();
+	object.cameras.push(
+		serde_json::from_value(serde_json::json!({ "type": "perspective", "perspective": { "yfov": 1.0, "znear": 0.1, "zfar": 10.0 } }))
+			.unwrap(),
+	);
+	object.add_light(KhrLight { range: Some(5.0), ..KhrLight::new(KhrLightType::Point) });
+	let mut baked = object.clone();
+
+	object.scale_units(100.0, false).unwrap();
+	assert_eq!(object.get(root).unwrap().scale, Some([100.0; 3]));
+	assert!(close(&translation_keys(&object), &[100.0, 0.0, 0.0, 100.0, 0.0, 300.0]));
+	assert!(close(&world_vertices(&object, child)[1], &[200.0, 200.0, 0.0]));
+	assert!(close(&[object.cameras[0].perspective.as_ref().unwrap().z_near], &[10.0]));
+	assert_eq!(object.lights()[0].range, Some(500.0));
+
+	baked.scale_units(100.0, true).unwrap();
+	assert_eq!(baked.get(root).unwrap().scale, None);
+	assert!(close(&baked.get(child).unwrap().translation.unwrap(), &[0.0, 200.0, 0.0]));
+	assert!(close(&world_vertices(&baked, child).concat(), &world_vertices(&object, child).concat()));
+	assert_eq!(baked.lights()[0].range, Some(500.0));
+
+	assert!(object.scale_units(0.0, false).is_err());
+}
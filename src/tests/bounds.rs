@@ -0,0 +1,63 @@
+use crate::prelude::*;
+use crate::tests::{close, triangle};
+
+/// a triangle on a node turned 45 degrees around z, and again on a child 10 along the turned x axis
+fn turned_triangles() -> (GltfObject, NodeId, NodeId) {
+	let mut builder = GltfBuilder::new();
+	let mesh = builder
+		.add_mesh(vec![triangle()])
+		.unwrap();
+
+	let angle = std::f32::consts::FRAC_PI_8;
+	let turned = NodeTransform { rotation: [0.0, 0.0, angle.sin(), angle.cos()], ..NodeTransform::default() };
+	let root = builder.add_mesh_node(None, turned, mesh).unwrap();
+	let child = builder.add_mesh_node(Some(root), NodeTransform::from_translation([10.0, 0.0, 0.0]), mesh).unwrap();
+	builder.add_scene(None, &[root]).unwrap();
+
+	(builder.build(), root, child)
+}
+
+#[test]
+fn node_bounds_from_corners_and_vertices() {
+	let (object, root, child) = turned_triangles();
+	let h = std::f32::consts::FRAC_1_SQRT_2;
+
+	let local = object.mesh_bounds(object.get(root).unwrap().mesh.unwrap()).unwrap().unwrap();
+	assert_eq!(local, Aabb { min: [0.0; 3], max: [1.0, 1.0, 0.0] });
+
+	// the far corner of the box isn't a vertex, it reaches higher than the triangle
+	let corners = object.node_bounds(root, false, false).unwrap().unwrap();
+	assert!(close(&corners.min, &[-h, 0.0, 0.0]) && close(&corners.max, &[h, 2.0 * h, 0.0]));
+
+	let exact = object.node_bounds(root, false, true).unwrap().unwrap();
+	assert!(close(&exact.min, &[-h, 0.0, 0.0]) && close(&exact.max, &[h, h, 0.0]));
+
+	let offset = 10.0 * h;
+	let child_bounds = object.node_bounds(child, false, true).unwrap().unwrap();
+	assert!(close(&child_bounds.min, &[offset - h, offset, 0.0]));
+
+	let all = object.node_bounds(root, true, true).unwrap().unwrap();
+	assert_eq!(all, exact.union(&child_bounds));
+	assert_eq!(object.scene_bounds(Index::new(0), true).unwrap(), Some(all));
+	assert!(object.scene_bounds(Index::new(1), true).is_err());
+}
+
+#[test]
+fn bounding_spheres_contain_every_vertex() {
+	let (object, root, child) = turned_triangles();
+
+	let sphere = object.node_bounding_sphere(root, true, true).unwrap().unwrap();
+	for node in [root, child] {
+		let world = object.node_world_matrix(node).unwrap();
+		for vertex in [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]] {
+			let p = Aabb { min: vertex, max: vertex }.transformed(&world).min;
+			let distance = p.iter().zip(sphere.center).map(|(a, b)| (a - b) * (a - b)).sum::<f32>().sqrt();
+			assert!(distance <= sphere.radius + 1e-5);
+		}
+	}
+
+	// no larger than the sphere around the box
+	let aabb = object.scene_bounds(Index::new(0), true).unwrap().unwrap();
+	assert!(sphere.radius <= aabb.bounding_sphere().radius + 1e-5);
+	assert_eq!(object.scene_bounding_sphere(Index::new(0), true).unwrap(), Some(sphere));
+}
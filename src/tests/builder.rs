@@ -0,0 +1,92 @@
+use crate::prelude::*;
+
+const PNG: &[u8] = &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n', 0, 0, 0, 0];
+
+fn quad(material: Option<MaterialId>) -> PrimitiveData {
+	PrimitiveData {
+		positions: vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]],
+		normals: vec![[0.0, 0.0, 1.0]; 4],
+		tex_coords: vec![[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]],
+		indices: vec![0, 1, 2, 0, 2, 3],
+		material,
+		..PrimitiveData::default()
+	}
+}
+
+#[test]
+fn build_textured_scene() {
+	let mut builder = GltfBuilder::new();
+
+	let texture = builder.add_texture_from_png(PNG).expect("should be a PNG");
+	let material = builder.add_material(GltfMetallicRoughness {
+		base_color_texture: Some(GltfTextureInfo::new(texture)),
+		metallic_factor: 0.0,
+		..GltfMetallicRoughness::default()
+	});
+	let mesh = builder.add_mesh(vec![quad(Some(material))]).expect("quad should be valid");
+
+	let root = builder.add_node(None, NodeTransform::default()).unwrap();
+	let child = builder.add_mesh_node(Some(root), NodeTransform::from_translation([0.0, 2.0, 0.0]), mesh).unwrap();
+	builder.node_mut(child).name = Some("quad".to_string());
+	builder.add_scene(Some("main"), &[root]).unwrap();
+
+	let object = builder.build();
+
+	// the saved document is valid and reads back the same data
+	let object = GltfObject::parse_json_str(&object.to_json_string().unwrap()).expect("should parse");
+
+	assert_eq!(object.scene, Some(Index::new(0)));
+	assert_eq!(object.scenes[0].nodes, vec![root]);
+	assert_eq!(object.get(root).unwrap().children, Some(vec![child]));
+	assert_eq!(object.get(child).unwrap().translation, Some([0.0, 2.0, 0.0]));
+	assert_eq!(object.get(root).unwrap().translation, None);
+
+	assert_eq!(object.buffers.0.len(), 1);
+	assert_eq!(object.images[0].mime_type.as_deref(), Some("image/png"));
+	assert_eq!(object.buffer_view_bytes(object.images[0].buffer_view.unwrap()).unwrap(), PNG);
+
+	let primitive = &object.get(mesh).unwrap().primitives[0];
+	assert_eq!(primitive.material, Some(material));
+
+	let positions = object.get(primitive.attributes["POSITION"]).unwrap();
+	assert_eq!(positions.min, vec![0.0, 0.0, 0.0]);
+	assert_eq!(positions.max, vec![1.0, 1.0, 0.0]);
+	assert_eq!(object.read_accessor_array::<2>(primitive.attributes["TEXCOORD_0"]).unwrap()[1], [1.0, 1.0]);
+
+	let indices = primitive.accessor.unwrap();
+	assert_eq!(object.get(indices).unwrap().component_type, ComponentType::UNSIGNED_SHORT);
+	assert_eq!(object.read_accessor_u32(indices).unwrap(), vec![0, 1, 2, 0, 2, 3]);
+
+	for view in &object.buffer_views {
+		assert_eq!(view.byte_offset % 4, 0);
+	}
+}
+
+#[test]
+fn large_indices_use_unsigned_int() {
+	let mut builder = GltfBuilder::new();
+	let indices = builder.add_indices(&[0, 70000, 16_777_217]);
+	let object = builder.build();
+
+	assert_eq!(object.get(indices).unwrap().component_type, ComponentType::UNSIGNED_INT);
+	assert_eq!(object.read_accessor_u32(indices).unwrap(), vec![0, 70000, 16_777_217]);
+}
+
+#[test]
+fn invalid_content_is_rejected() {
+	let mut builder = GltfBuilder::new();
+
+	assert!(builder.add_texture_from_png(b"GIF89a").is_err());
+
+	let mut bad = quad(None);
+	bad.normals.pop();
+	assert!(matches!(builder.add_mesh(vec![bad]), Err(GltrError::ConstraintViolation(_))));
+
+	let mut bad = quad(None);
+	bad.indices.push(4);
+	assert!(builder.add_mesh(vec![bad]).is_err());
+
+	let object = builder.build();
+	assert!(object.meshes.is_empty());
+	assert!(object.images.is_empty());
+}
@@ -0,0 +1,84 @@
+use crate::prelude::*;
+use crate::tests::triangle;
+
+/// a named node with a named mesh and material, plus an unnamed node with an unnamed mesh
+fn scene(base_color: [f32; 4], translation: [f32; 3]) -> GltfBuilder {
+	let mut builder = GltfBuilder::new();
+
+	let material = builder.add_material(GltfMetallicRoughness { base_color_factor: base_color, ..GltfMetallicRoughness::default() });
+	builder.material_mut(material).name = Some("paint".to_string());
+
+	let mesh = builder.add_mesh(vec![PrimitiveData { material: Some(material), ..triangle() }]).unwrap();
+	builder.mesh_mut(mesh).name = Some("body".to_string());
+
+	let unnamed = builder.add_mesh(vec![triangle()]).unwrap();
+
+	let car = builder.add_mesh_node(None, NodeTransform::from_translation(translation), mesh).unwrap();
+	builder.node_mut(car).name = Some("car".to_string());
+	let other = builder.add_mesh_node(None, NodeTransform::default(), unnamed).unwrap();
+	builder.add_scene(None, &[car, other]).unwrap();
+
+	builder
+}
+
+#[test]
+fn identical_documents_have_no_diff() {
+	let a = scene([1.0; 4], [0.0; 3]).build();
+
+	// reordering the arrays is not a change
+	let mut b = a.clone();
+	b.meshes.swap(0, 1);
+	b.nodes[0].mesh = Some(Index::new(1));
+	b.nodes[1].mesh = Some(Index::new(0));
+
+	let changes = diff(&a, &b).unwrap();
+	assert!(changes.is_empty(), "{changes}");
+}
+
+#[test]
+fn reports_changed_entities() {
+	let a = scene([1.0; 4], [0.0; 3]).build();
+
+	let mut builder = scene([1.0, 0.0, 0.0, 1.0], [2.0, 0.0, 0.0]);
+	let bigger = builder
+		.add_mesh(vec![PrimitiveData {
+			positions: vec![[0.0, 0.0, 0.0], [4.0, 0.0, 0.0], [0.0, 4.0, 0.0], [4.0, 4.0, 0.0]],
+			indices: vec![0, 1, 2, 2, 1, 3],
+			..PrimitiveData::default()
+		}])
+		.unwrap();
+	let wheel = builder.add_node(None, NodeTransform::default()).unwrap();
+	builder.node_mut(wheel).name = Some("wheel".to_string());
+	let mut b = builder.build();
+
+	// the named mesh keeps its name but gets the geometry of the new one
+	b.meshes.swap(0, bigger.value());
+	b.meshes[0].name = Some("body".to_string());
+	b.meshes[bigger.value()].name = None;
+	b.meshes.truncate(2);
+	b.nodes[1].mesh = Some(Index::new(1));
+
+	let changes = diff(&a, &b).unwrap();
+
+	let paint = &changes.materials[0];
+	assert_eq!(paint.kind, ChangeKind::Modified);
+	assert_eq!(paint.changes[0].property, "pbrMetallicRoughness/baseColorFactor");
+	assert_eq!(paint.changes[0].new, Some(serde_json::json!([1.0, 0.0, 0.0, 1.0])));
+
+	let body = &changes.meshes[0];
+	assert_eq!((body.kind, body.name.as_deref()), (ChangeKind::Modified, Some("body")));
+	let properties: Vec<&str> = body.changes.iter().map(|c| c.property.as_str()).collect();
+	assert!(properties.contains(&"primitives/0/vertices"));
+	assert!(properties.contains(&"primitives/0/bounds/max"));
+	assert!(properties.contains(&"primitives/0/material"));
+
+	let car = changes.nodes.iter().find(|n| n.name.as_deref() == Some("car")).unwrap();
+	assert_eq!(car.changes.len(), 1);
+	assert_eq!(car.changes[0].property, "translation");
+
+	let wheel = changes.nodes.iter().find(|n| n.name.as_deref() == Some("wheel")).unwrap();
+	assert_eq!((wheel.kind, wheel.new_index), (ChangeKind::Added, Some(2)));
+
+	assert!(changes.to_string().contains("~ node \"car\"\n\ttranslation: [0.0,0.0,0.0] -> [2.0,0.0,0.0]"));
+	assert_eq!(serde_json::to_value(&changes).unwrap()["nodes"][1]["kind"], "added");
+}
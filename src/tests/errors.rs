@@ -0,0 +1,103 @@
+use crate::prelude::*;
+use std::error::Error;
+
+fn buffer(uri: &str, byte_length: usize) -> GltfBase64Buffer {
+	GltfBase64Buffer {
+		byte_length,
+		uri: Some(uri.to_string()),
+		name: None,
+		extensions: None,
+		extras: None,
+		unknown_fields: UnknownFields::new(),
+		original_index: None,
+	}
+}
+
+#[test]
+fn invalid_json_reports_position() {
+	let result = GltfObject::parse_json_str("{\n\t\"asset\": {\n\t\t\"version\": \"2.0\",\n\t}\n}");
+
+	match result {
+		Err(GltrError::InvalidJson { line, column, .. }) => assert_eq!((line, column), (4, 2)),
+		other => panic!("expected InvalidJson, got {other:?}"),
+	}
+
+	let error = GltfObject::parse_json_str("{ \"nodes\": 5 }").unwrap_err();
+	assert!(matches!(error, GltrError::JsonData(_)));
+	assert!(error.source().is_some());
+	assert!(error.to_string().contains("line 1"));
+
+	// a value has no position to report
+	let error = GltrError::from(serde_json::from_value::<GltfNode>(serde_json::json!({ "mesh": "one" })).unwrap_err());
+	assert!(matches!(error, GltrError::JsonData(_)));
+	assert!(!error.to_string().contains("line"));
+}
+
+#[test]
+fn buffer_decoding_errors() {
+	let external = buffer("model.bin", 4);
+	assert!(matches!(external.bytes(), Err(GltrError::InvalidDataUri(_))));
+
+	let corrupt = buffer("data:application/octet-stream;base64,AAA*AAAA", 6);
+	match corrupt.bytes() {
+		Err(e @ GltrError::Base64 { .. }) => {
+			assert!(matches!(e, GltrError::Base64 { position: Some(3), .. }));
+			assert!(e.source().is_some());
+		}
+		other => panic!("expected Base64, got {other:?}"),
+	}
+
+	let gltf_buffer = buffer("data:application/gltf-buffer;base64,AAECAw==", 4);
+	assert_eq!(gltf_buffer.bytes().unwrap(), vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn buffer_length_mismatch_is_an_error() {
+	let short = buffer("data:application/octet-stream;base64,AAECAw==", 8);
+
+	match short.to_binary() {
+		Err(GltrError::BufferLength { expected, actual }) => assert_eq!((expected, actual), (8, 4)),
+		other => panic!("expected BufferLength, got {other:?}"),
+	}
+}
+
+#[test]
+fn view_out_of_range() {
+	let buffers = GltfBuffers(vec![buffer("data:application/octet-stream;base64,AAECAw==", 4)]).to_binary().unwrap();
+
+	let view = GltfBufferView::new(Index::new(0), 2, 4, None);
+	assert!(matches!(
+		buffers.get_view(&view),
+		Err(GltrError::OutOfRange { offset: 2, length: 4, available: 4, .. })
+	));
+
+	let view = GltfBufferView::new(Index::new(3), 0, 4, None);
+	assert!(matches!(buffers.get_view(&view), Err(GltrError::InvalidIndex { index: 3, .. })));
+}
+
+#[test]
+fn invalid_index_has_json_path() {
+	let mut object = GltfObject::new();
+	object.nodes.push(GltfNode {
+		mesh: Some(Index::new(4)),
+		..GltfNode::default()
+	});
+
+	match object.extract_node(Index::new(0), GltrExtractFlags::empty()) {
+		Err(GltrError::InvalidIndex { path, index }) => {
+			assert_eq!(path, "/nodes/0/mesh");
+			assert_eq!(index, 4);
+		}
+		other => panic!("expected InvalidIndex, got {other:?}"),
+	}
+
+	let error = object.extract_node(Index::new(7), GltrExtractFlags::empty()).unwrap_err();
+	assert_eq!(error.to_string(), "/nodes: index 7 does not exist");
+}
+
+#[test]
+fn load_missing_file() {
+	let error = GltfObject::load("assets/test_assets/does_not_exist.gltf").unwrap_err();
+	assert!(matches!(error, GltrError::Io(_)));
+	assert!(error.source().is_some());
+}
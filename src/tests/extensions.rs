@@ -0,0 +1,291 @@
+use crate::prelude::*;
+use crate::tests::cliffs;
+
+const MATERIAL: &str = r#"{
+	"name": "Clearcoated",
+	"pbrMetallicRoughness": {
+		"baseColorTexture": {
+			"index": 0,
+			"extensions": {
+				"KHR_texture_transform": { "offset": [0.5, 0], "scale": [2, 2] }
+			}
+		}
+	},
+	"extensions": {
+		"KHR_materials_clearcoat": { "clearcoatFactor": 1.0, "clearcoatNormalTexture": { "index": 1, "scale": 0.5 } },
+		"KHR_materials_ior": { "ior": 1.4 },
+		"VENDOR_unknown_extension": { "anything": [1, 2, 3] }
+	}
+}"#;
+
+#[test]
+fn known_extensions_are_typed() {
+	let material = serde_json::from_str::<GltfMaterial>(MATERIAL).expect("should be valid JSON");
+
+	let clearcoat = material.extension::<KhrMaterialsClearcoat>().expect("clearcoat should be typed");
+	assert_eq!(clearcoat.clearcoat_factor, 1.0);
+	assert_eq!(clearcoat.clearcoat_roughness_factor, 0.0);
+	assert_eq!(clearcoat.clearcoat_normal_texture.as_ref().unwrap().scale, 0.5);
+
+	assert_eq!(material.extension::<KhrMaterialsIor>().unwrap().ior, 1.4);
+	assert!(material.extension::<KhrMaterialsSpecular>().is_none());
+
+	let unknown = material.extensions.as_ref().unwrap().get_raw("VENDOR_unknown_extension").unwrap();
+	assert!(!unknown.is_known());
+	assert_eq!(unknown.to_value(), serde_json::json!({ "anything": [1, 2, 3] }));
+
+	let base_color = material.pbr_metallic_roughness.as_ref().unwrap().base_color_texture.as_ref().unwrap();
+	let transform = base_color.extension::<KhrTextureTransform>().expect("transform should be typed");
+	assert_eq!(transform.offset, [0.5, 0.0]);
+	assert_eq!(transform.rotation, 0.0);
+	assert_eq!(transform.tex_coord, None);
+}
+
+#[test]
+fn extensions_round_trip() {
+	let material = serde_json::from_str::<GltfMaterial>(MATERIAL).expect("should be valid JSON");
+
+	let json = serde_json::to_value(&material).unwrap();
+	let extensions = &json["extensions"];
+
+	assert_eq!(extensions["KHR_materials_ior"]["ior"], serde_json::json!(1.4f32));
+	assert_eq!(extensions["VENDOR_unknown_extension"], serde_json::json!({ "anything": [1, 2, 3] }));
+	assert!(extensions["KHR_materials_clearcoat"].get("clearcoatTexture").is_none());
+
+	let reparsed = serde_json::from_value::<GltfMaterial>(json).unwrap();
+	assert_eq!(reparsed.extensions, material.extensions);
+}
+
+#[test]
+fn set_and_remove_extensions() {
+	let mut node = serde_json::from_str::<GltfNode>(r#"{ "name": "lamp" }"#).unwrap();
+
+	assert!(node.extension::<KhrLightsPunctualNode>().is_none());
+
+	node.set_extension(KhrLightsPunctualNode::new(Index::new(3)));
+	assert_eq!(node.extension::<KhrLightsPunctualNode>().unwrap().light.value(), 3);
+	assert!(node.extension::<KhrLightsPunctual>().is_none());
+
+	node.extension_mut::<KhrLightsPunctualNode>().unwrap().light = Index::new(4);
+	assert_eq!(serde_json::to_value(&node).unwrap()["extensions"]["KHR_lights_punctual"]["light"], 4);
+
+	assert!(node.remove_extension(KhrLightsPunctualNode::NAME).is_some());
+	assert!(node.extensions.is_none());
+
+	let mut object = GltfObject::new();
+	object.set_extension(KhrLightsPunctual {
+		lights: vec![KhrLight {
+			color: [1.0, 0.0, 0.0],
+			intensity: 10.0,
+			spot: Some(KhrLightSpot { inner_cone_angle: 0.1, outer_cone_angle: 0.5, ..KhrLightSpot::default() }),
+			..KhrLight::new(KhrLightType::Spot)
+		}],
+		..KhrLightsPunctual::default()
+	});
+
+	let json = serde_json::to_value(&object.extensions).unwrap();
+	assert_eq!(json["KHR_lights_punctual"]["lights"][0]["type"], "spot");
+}
+
+#[test]
+fn extensions_used_follows_content() {
+	let mut object = cliffs();
+
+	object.materials[0].set_extension(KhrMaterialsEmissiveStrength { emissive_strength: 4.0, extensions: None, extras: None, unknown_fields: UnknownFields::new() });
+	object.extensions_used = vec!["KHR_stale_extension".to_string()];
+	object.extensions_required = vec!["KHR_stale_extension".to_string()];
+
+	let json = object.to_json_value().unwrap();
+	assert_eq!(json["extensionsUsed"], serde_json::json!(["KHR_materials_emissive_strength"]));
+	assert!(json.get("extensionsRequired").is_none());
+
+	// serde writes the synced lists too, the stored ones are left as they are
+	let json = serde_json::to_value(&object).unwrap();
+	assert_eq!(json["extensionsUsed"], serde_json::json!(["KHR_materials_emissive_strength"]));
+	assert!(json.get("extensionsRequired").is_none());
+	assert_eq!(object.extensions_used, vec!["KHR_stale_extension".to_string()]);
+
+	// extensions nested in untyped extensions and in unmodelled properties are found too
+	let sheen = serde_json::json!({ "sheenColorTexture": { "index": 0, "extensions": { "KHR_texture_transform": {} } } });
+	object.materials[0].extensions.as_mut().unwrap().insert_raw("KHR_materials_sheen", sheen);
+	object.animations.push(serde_json::from_value(serde_json::json!({ "channels": [], "samplers": [], "extras": { "extensions": { "VENDOR_extra": {} } } })).unwrap());
+	object.asset.unknown_fields.insert("future".to_string(), serde_json::json!({ "extensions": { "VENDOR_future": {} } }));
+	let names: Vec<_> = object.collect_extension_names().into_iter().collect();
+	assert_eq!(names, ["KHR_materials_emissive_strength", "KHR_materials_sheen", "KHR_texture_transform", "VENDOR_future"]);
+	object.animations.clear();
+	object.asset.unknown_fields.clear();
+	object.materials[0].remove_extension("KHR_materials_sheen");
+
+	let extracted = object.extract_node(Index::new(1), GltrExtractFlags::empty()).expect("node should extract");
+	assert_eq!(extracted.extensions_used, vec!["KHR_materials_emissive_strength".to_string()]);
+	assert!(extracted.extensions_required.is_empty());
+
+	object.materials[0].remove_extension(KhrMaterialsEmissiveStrength::NAME);
+	object.sync_extensions();
+	assert!(object.extensions_used.is_empty());
+}
+
+fn light(light_type: KhrLightType, intensity: f32) -> KhrLight {
+	KhrLight {
+		intensity,
+		range: Some(10.0),
+		spot: None,
+		..KhrLight::new(light_type)
+	}
+}
+
+#[test]
+fn extract_node_with_light() {
+	let mut object = cliffs();
+
+	object.add_light(light(KhrLightType::Point, 1.0));
+	let spot = object.add_light(KhrLight {
+		spot: Some(KhrLightSpot { inner_cone_angle: 0.2, outer_cone_angle: 0.6, ..KhrLightSpot::default() }),
+		..light(KhrLightType::Spot, 50.0)
+	});
+	assert_eq!(spot.value(), 1);
+
+	object.nodes[1].set_extension(KhrLightsPunctualNode::new(spot));
+
+	let node_light = object.node_light(Index::new(1)).unwrap().expect("node should have a light");
+	assert_eq!(node_light.light_type, KhrLightType::Spot);
+	assert!(object.node_light(Index::new(0)).unwrap().is_none());
+
+	let extracted = object.extract_node(Index::new(1), GltrExtractFlags::empty()).expect("node should extract");
+	assert_eq!(extracted.lights().len(), 1);
+	assert_eq!(extracted.nodes[0].extension::<KhrLightsPunctualNode>().unwrap().light.value(), 0);
+
+	let extracted_light = extracted.node_light(Index::new(0)).unwrap().unwrap();
+	assert_eq!(extracted_light.intensity, 50.0);
+	assert_eq!(extracted_light.spot.as_ref().unwrap().outer_cone_angle, 0.6);
+	assert!(extracted.extensions_used.contains(&"KHR_lights_punctual".to_string()));
+
+	object.nodes[2].set_extension(KhrLightsPunctualNode::new(Index::new(7)));
+	assert!(object.node_light(Index::new(2)).is_err());
+	assert!(object.extract_node(Index::new(2), GltrExtractFlags::empty()).is_err());
+}
+
+#[test]
+fn texture_transform_matrix() {
+	let transform = KhrTextureTransform {
+		offset: [0.5, 0.0],
+		rotation: std::f32::consts::FRAC_PI_2,
+		scale: [2.0, 1.0],
+		..KhrTextureTransform::default()
+	};
+
+	let uv = transform.transform_uv([1.0, 0.0]);
+	assert!((uv[0] - 0.5).abs() < 1e-6);
+	assert!((uv[1] + 2.0).abs() < 1e-6);
+
+	assert!(KhrTextureTransform::default().is_identity());
+	assert_eq!(KhrTextureTransform::default().matrix(), [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]);
+}
+
+#[test]
+fn bake_texture_transform_into_texcoords() {
+	let mut object = cliffs();
+
+	let transform = KhrTextureTransform { offset: [0.5, 0.25], scale: [2.0, 2.0], tex_coord: Some(1), ..KhrTextureTransform::default() };
+	let base_color = object.materials[0].pbr_metallic_roughness.as_mut().unwrap().base_color_texture.as_mut().unwrap();
+	base_color.set_extension(transform.clone());
+	object.sync_extensions();
+	assert_eq!(object.extensions_used, vec!["KHR_texture_transform".to_string()]);
+
+	let original_accessor = object.meshes[0].primitives[0].attributes["TEXCOORD_1"];
+	let original = object.read_accessor_array::<2>(original_accessor).unwrap();
+
+	object.bake_texture_transforms().expect("transform should bake");
+
+	let baked_accessor = object.meshes[0].primitives[0].attributes["TEXCOORD_1"];
+	assert_ne!(baked_accessor, original_accessor);
+
+	let baked = object.read_accessor_array::<2>(baked_accessor).unwrap();
+	for (a, b) in original.iter().zip(baked.iter()) {
+		let expected = transform.transform_uv(*a);
+		assert!((expected[0] - b[0]).abs() < 1e-5 && (expected[1] - b[1]).abs() < 1e-5);
+	}
+
+	let base_color = object.materials[0].pbr_metallic_roughness.as_ref().unwrap().base_color_texture.as_ref().unwrap();
+	assert!(base_color.extensions.is_none());
+	assert_eq!(base_color.tex_coord, 1);
+	assert!(object.extensions_used.is_empty());
+}
+
+#[test]
+fn failed_bake_leaves_the_object_unchanged() {
+	let mut object = cliffs();
+
+	let transform = KhrTextureTransform { offset: [0.5, 0.25], ..KhrTextureTransform::default() };
+	let base_color = object.materials[0].pbr_metallic_roughness.as_mut().unwrap().base_color_texture.as_mut().unwrap();
+	base_color.set_extension(transform);
+	let set = base_color.tex_coord;
+	object.sync_extensions();
+
+	// a second primitive of the material, whose texture coordinates can't be read as UVs
+	let mut unreadable = object.meshes[0].primitives[0].clone();
+	let positions = unreadable.attributes["POSITION"];
+	unreadable.attributes.insert(format!("TEXCOORD_{set}"), positions);
+	object.meshes[0].primitives.push(unreadable);
+
+	let before = object.to_json_string().unwrap();
+	assert!(object.bake_texture_transform(Index::new(0)).is_err());
+	assert_eq!(object.to_json_string().unwrap(), before);
+}
+
+#[test]
+fn bake_refuses_sets_read_by_untyped_textures() {
+	let mut object = cliffs();
+
+	let base_color = object.materials[0].pbr_metallic_roughness.as_mut().unwrap().base_color_texture.as_mut().unwrap();
+	base_color.set_extension(KhrTextureTransform { offset: [0.5, 0.25], ..KhrTextureTransform::default() });
+	let set = base_color.tex_coord;
+	let sheen = serde_json::json!({ "sheenColorTexture": { "index": 0, "texCoord": set } });
+	object.materials[0].extensions.get_or_insert_with(GltfExtensions::new).insert_raw("KHR_materials_sheen", sheen);
+	object.sync_extensions();
+
+	let before = object.to_json_string().unwrap();
+	assert!(matches!(object.bake_texture_transform(Index::new(0)), Err(GltrError::ConstraintViolation(_))));
+	assert_eq!(object.to_json_string().unwrap(), before);
+}
+
+#[test]
+fn conflicting_texture_transforms_are_rejected() {
+	let mut material = serde_json::from_str::<GltfMaterial>(MATERIAL).expect("should be valid JSON");
+	material.normal_texture = Some(GltfNormalTexture {
+		texture_index: Index::new(1),
+		tex_coord: 0,
+		scale: 1.0,
+		extensions: None,
+		extras: None,
+		unknown_fields: UnknownFields::new(),
+	});
+
+	let mut object = GltfObject::new();
+	object.materials.push(material);
+
+	assert!(object.bake_texture_transform(Index::new(0)).is_err());
+	assert!(object.bake_texture_transform(Index::new(1)).is_err());
+}
+
+#[test]
+fn texture_slots_match_texture_slots_mut() {
+	let mut material = serde_json::from_value::<GltfMaterial>(serde_json::json!({
+		"pbrMetallicRoughness": { "baseColorTexture": { "index": 0 }, "metallicRoughnessTexture": { "index": 1, "texCoord": 1 } },
+		"normalTexture": { "index": 2 },
+		"occlusionTexture": { "index": 3 },
+		"emissiveTexture": { "index": 4 },
+		"extensions": {
+			"KHR_materials_clearcoat": { "clearcoatTexture": { "index": 5 }, "clearcoatRoughnessTexture": { "index": 6 }, "clearcoatNormalTexture": { "index": 7 } },
+			"KHR_materials_transmission": { "transmissionTexture": { "index": 8 } },
+			"KHR_materials_specular": { "specularTexture": { "index": 9 }, "specularColorTexture": { "index": 10, "texCoord": 2 } }
+		}
+	}))
+	.unwrap();
+
+	let slots: Vec<_> = material.texture_slots().iter().map(|s| (s.pointer, s.texture_index, s.tex_coord)).collect();
+	let slots_mut: Vec<_> = material.texture_slots_mut().iter().map(|s| (s.pointer, *s.texture_index, *s.tex_coord)).collect();
+
+	assert_eq!(slots.len(), 11);
+	assert_eq!(slots, slots_mut);
+}
@@ -0,0 +1,490 @@
+use crate::prelude::*;
+use crate::tests::{close, triangle};
+
+/// a mesh with a single non-indexed primitive with `count` vertices
+fn mesh(count: usize, mode: PrimitiveMode, indices: Vec<u32>) -> (GltfObject, MeshId) {
+	let mut builder = GltfBuilder::new();
+	let mesh = builder
+		.add_mesh(vec![PrimitiveData {
+			positions: (0..count).map(|i| [i as f32, (i % 2) as f32, 0.0]).collect(),
+			indices,
+			..PrimitiveData::default()
+		}])
+		.unwrap();
+	builder.mesh_mut(mesh).primitives[0].mode = Some(mode);
+
+	(builder.build(), mesh)
+}
+
+#[test]
+fn primitive_mode_serialization() {
+	let primitive: GltfMeshPrimitive = serde_json::from_value(serde_json::json!({ "attributes": {}, "mode": 5 })).unwrap();
+	assert_eq!(primitive.mode(), PrimitiveMode::TRIANGLE_STRIP);
+
+	let primitive: GltfMeshPrimitive = serde_json::from_value(serde_json::json!({ "attributes": {} })).unwrap();
+	assert_eq!(primitive.mode(), PrimitiveMode::TRIANGLES);
+	assert!(serde_json::to_value(&primitive).unwrap().get("mode").is_none());
+
+	assert!(!PrimitiveMode::LINE_LOOP.is_triangles());
+	assert_eq!(PrimitiveMode::TRIANGLE_FAN.triangle_count(5), 3);
+}
+
+#[test]
+fn strip_and_fan_triangles() {
+	let (object, strip) = mesh(5, PrimitiveMode::TRIANGLE_STRIP, vec![]);
+	let triangles: Vec<[u32; 3]> = object.primitive_triangles(strip, 0).unwrap().collect();
+	assert_eq!(triangles, vec![[0, 1, 2], [1, 3, 2], [2, 3, 4]]);
+
+	let (object, fan) = mesh(5, PrimitiveMode::TRIANGLE_FAN, vec![4, 3, 2, 1, 0]);
+	let triangles = object.primitive_triangles(fan, 0).unwrap();
+	assert_eq!(triangles.len(), 3);
+	assert_eq!(triangles.collect::<Vec<_>>(), vec![[3, 2, 4], [2, 1, 4], [1, 0, 4]]);
+
+	let (object, lines) = mesh(4, PrimitiveMode::LINES, vec![]);
+	assert!(object.primitive_triangles(lines, 0).is_err());
+	assert!(matches!(object.primitive_triangles(lines, 1), Err(GltrError::InvalidIndex { index: 1, .. })));
+}
+
+#[test]
+fn triangulate_strip_in_place() {
+	// two runs joined by the degenerate triangles of the repeated 3 and 4
+	let (mut object, strip) = mesh(8, PrimitiveMode::TRIANGLE_STRIP, vec![0, 1, 2, 3, 3, 4, 4, 5, 6, 7]);
+	object.triangulate_meshes().unwrap();
+
+	let primitive = object.primitive(strip, 0).unwrap();
+	assert_eq!(primitive.mode, None);
+	assert_eq!(
+		object.read_accessor_u32(primitive.accessor.unwrap()).unwrap(),
+		vec![0, 1, 2, 1, 3, 2, 4, 5, 6, 5, 7, 6]
+	);
+
+	assert!(!object.triangulate_primitive(strip, 0).unwrap());
+}
+
+/// two triangles folded at a right angle along the edge from vertex 0 to vertex 1, one lying in the xy plane and
+/// one in the xz plane
+fn fold() -> (GltfObject, MeshId) {
+	let mut builder = GltfBuilder::new();
+	let mesh = builder
+		.add_mesh(vec![PrimitiveData {
+			positions: vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+			tex_coords: vec![[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 1.0]],
+			indices: vec![0, 1, 2, 0, 3, 1],
+			..PrimitiveData::default()
+		}])
+		.unwrap();
+
+	(builder.build(), mesh)
+}
+
+fn read_normals(object: &GltfObject, mesh: MeshId) -> Vec<[f32; 3]> {
+	object.read_accessor_array::<3>(object.primitive(mesh, 0).unwrap().attributes["NORMAL"]).unwrap()
+}
+
+#[test]
+fn flat_normals_unweld_vertices() {
+	let (mut object, mesh) = fold();
+	object.generate_normals(mesh, 0, NormalMode::Flat).unwrap();
+
+	assert_eq!(object.primitive_vertex_count(mesh, 0).unwrap(), 6);
+	let normals = read_normals(&object, mesh);
+	assert_eq!(&normals[..3], &[[0.0, 0.0, 1.0]; 3]);
+	assert_eq!(&normals[3..], &[[0.0, 1.0, 0.0]; 3]);
+
+	// the other attributes follow the split vertices
+	let primitive = object.primitive(mesh, 0).unwrap();
+	let tex_coords = object.read_accessor_array::<2>(primitive.attributes["TEXCOORD_0"]).unwrap();
+	assert_eq!(tex_coords, vec![[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [0.0, 0.0], [1.0, 1.0], [1.0, 0.0]]);
+	assert_eq!(object.read_accessor_u32(primitive.accessor.unwrap()).unwrap(), vec![0, 1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn smooth_normals_respect_crease_angle() {
+	let (mut object, mesh) = fold();
+	object.generate_normals(mesh, 0, NormalMode::default()).unwrap();
+
+	// no vertex is split, the shared edge gets the angle weighted average of both faces
+	assert_eq!(object.primitive_vertex_count(mesh, 0).unwrap(), 4);
+	let normals = read_normals(&object, mesh);
+	let half = std::f32::consts::FRAC_1_SQRT_2;
+	for (actual, expected) in normals[0].iter().zip([0.0, half, half]) {
+		assert!((actual - expected).abs() < 1e-6, "{:?}", normals);
+	}
+	assert_eq!(normals[2], [0.0, 0.0, 1.0]);
+	assert_eq!(normals[3], [0.0, 1.0, 0.0]);
+
+	let (mut object, mesh) = fold();
+	object.generate_normals(mesh, 0, NormalMode::Smooth { crease_angle: 60f32.to_radians() }).unwrap();
+	assert_eq!(object.primitive_vertex_count(mesh, 0).unwrap(), 6);
+	assert_eq!(read_normals(&object, mesh)[3], [0.0, 1.0, 0.0]);
+}
+
+#[test]
+fn tangents_split_mirrored_uvs() {
+	let mut builder = GltfBuilder::new();
+	let texture = builder.add_texture_from_png(b"\x89PNG\r\n\x1a\n").unwrap();
+	let material = builder.add_material(GltfMetallicRoughness::default());
+	builder.material_mut(material).normal_texture = Some(GltfNormalTexture {
+		texture_index: texture,
+		tex_coord: 0,
+		scale: 1.0,
+		extensions: None,
+		extras: None,
+		unknown_fields: Default::default(),
+	});
+
+	// the triangle on the left mirrors the uvs of the one on the right across the shared edge from 0 to 2
+	let primitive = PrimitiveData {
+		positions: vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [-1.0, 0.0, 0.0]],
+		normals: vec![[0.0, 0.0, 1.0]; 4],
+		tex_coords: vec![[0.0, 1.0], [1.0, 1.0], [0.0, 0.0], [1.0, 1.0]],
+		indices: vec![0, 1, 2, 0, 2, 3],
+		material: Some(material),
+		..PrimitiveData::default()
+	};
+	let mesh = builder.add_mesh(vec![primitive.clone(), PrimitiveData { material: None, ..primitive }]).unwrap();
+	let mut object = builder.build();
+
+	assert_eq!(object.generate_tangents().unwrap(), 1);
+	assert!(!object.primitive(mesh, 1).unwrap().attributes.contains_key("TANGENT"));
+
+	// the vertices on the seam are split, one copy per handedness
+	assert_eq!(object.primitive_vertex_count(mesh, 0).unwrap(), 6);
+	let primitive = object.primitive(mesh, 0).unwrap();
+	let tangents = object.read_accessor_array::<4>(primitive.attributes["TANGENT"]).unwrap();
+	assert_eq!(&tangents[..3], &[[1.0, 0.0, 0.0, 1.0]; 3]);
+	assert_eq!(&tangents[3..], &[[-1.0, 0.0, 0.0, -1.0]; 3]);
+	assert_eq!(object.read_accessor_u32(primitive.accessor.unwrap()).unwrap(), vec![0, 1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn tangent_spaces_share_groups_and_fill_degenerate_triangles() {
+	let mut builder = GltfBuilder::new();
+
+	// a quad stretched to twice its uv size, then a triangle collapsed onto the edge from 0 to 1
+	let positions = vec![[0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [0.0, 2.0, 0.0], [2.0, 2.0, 0.0]];
+	let primitive = PrimitiveData {
+		tex_coords: positions.iter().map(|p| [p[0] / 2.0, 1.0 - p[1] / 2.0]).collect(),
+		normals: vec![[0.0, 0.0, 1.0]; 4],
+		positions,
+		indices: vec![0, 1, 2, 1, 3, 2, 0, 1, 1],
+		..PrimitiveData::default()
+	};
+	let mesh = builder.add_mesh(vec![primitive]).unwrap();
+	let object = builder.build();
+
+	let spaces = object.primitive_tangent_spaces(mesh, 0).unwrap();
+	assert_eq!(spaces.len(), 9);
+	for space in spaces {
+		let actual = [&space.tangent[..], &space.bitangent, &[space.magnitude_s, space.magnitude_t]].concat();
+		assert!(close(&actual, &[1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 2.0, 2.0]), "{space:?}");
+		assert!(space.orientation_preserving);
+	}
+}
+
+#[test]
+fn weld_builds_compact_indices() {
+	// a non-indexed quad, the second triangle repeats two vertices, one of them slightly off
+	let positions = vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 1.00001, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0]];
+	let quad = PrimitiveData {
+		tex_coords: positions.iter().map(|p| [p[0], p[1]]).collect(),
+		positions,
+		..PrimitiveData::default()
+	};
+
+	let mut builder = GltfBuilder::new();
+	let mesh = builder.add_mesh(vec![quad.clone()]).unwrap();
+	let mut object = builder.build();
+
+	assert_eq!(object.weld_primitive(mesh, 0, WeldOptions::default()).unwrap(), 1);
+	let indices = object.primitive(mesh, 0).unwrap().accessor.unwrap();
+	assert_eq!(object.get(indices).unwrap().component_type, ComponentType::UNSIGNED_BYTE);
+	assert_eq!(object.read_accessor_u32(indices).unwrap(), vec![0, 1, 2, 3, 1, 4]);
+
+	assert_eq!(object.weld_primitive(mesh, 0, WeldOptions { epsilon: 1e-4 }).unwrap(), 1);
+	let primitive = object.primitive(mesh, 0).unwrap();
+	assert_eq!(object.read_accessor_u32(primitive.accessor.unwrap()).unwrap(), vec![0, 1, 2, 2, 1, 3]);
+	let tex_coords = object.read_accessor_array::<2>(primitive.attributes["TEXCOORD_0"]).unwrap();
+	assert_eq!(tex_coords, vec![[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 1.0]]);
+
+	// nothing left to weld
+	assert_eq!(object.weld_meshes(WeldOptions { epsilon: 1e-4 }).unwrap(), 0);
+
+	// a vertex color that differs keeps the vertices apart, whatever the epsilon
+	let mut builder = GltfBuilder::new();
+	let colors = (0..6).map(|i| [i as f32 / 6.0, 0.0, 0.0, 1.0]).collect();
+	let mesh = builder.add_mesh(vec![PrimitiveData { colors, ..quad }]).unwrap();
+	let mut object = builder.build();
+	assert_eq!(object.weld_primitive(mesh, 0, WeldOptions { epsilon: 1.0 }).unwrap(), 0);
+	assert_eq!(object.primitive_vertex_count(mesh, 0).unwrap(), 6);
+}
+
+#[test]
+fn vertex_cache_order_lowers_acmr() {
+	// a 16 by 16 grid of quads with its triangles in a scrambled order
+	let n = 17u32;
+	let positions: Vec<[f32; 3]> = (0..n * n).map(|i| [(i % n) as f32, (i / n) as f32, 0.0]).collect();
+	let mut triangles = vec![];
+	for y in 0..n - 1 {
+		for x in 0..n - 1 {
+			let i = y * n + x;
+			triangles.push([i, i + 1, i + n]);
+			triangles.push([i + n, i + 1, i + n + 1]);
+		}
+	}
+	let count = triangles.len();
+	let scrambled: Vec<u32> = (0..count).flat_map(|i| triangles[i * 97 % count]).collect();
+
+	let mut builder = GltfBuilder::new();
+	let mesh = builder.add_mesh(vec![PrimitiveData { positions, indices: scrambled, ..PrimitiveData::default() }]).unwrap();
+	let mut object = builder.build();
+
+	let stats = object.optimize_vertex_cache().unwrap();
+	assert!(stats.acmr_before > 2.0, "{stats:?}");
+	assert!(stats.acmr_after < 1.0, "{stats:?}");
+
+	// the same triangles, with the vertices in first use order
+	let primitive = object.primitive(mesh, 0).unwrap();
+	let indices = object.read_accessor_u32(primitive.accessor.unwrap()).unwrap();
+	assert_eq!(&indices[..3], &[0, 1, 2]);
+	let positions = object.read_accessor_array::<3>(primitive.attributes["POSITION"]).unwrap();
+	let mut drawn: Vec<[u32; 3]> = indices.chunks(3).map(|t| [0, 1, 2].map(|c| (positions[t[c] as usize][1] as u32) * n + positions[t[c] as usize][0] as u32)).collect();
+	drawn.sort();
+	triangles.sort();
+	assert_eq!(drawn, triangles);
+}
+
+/// a flat `n` by `n` grid of quads in the xy plane
+fn grid(n: u32) -> PrimitiveData {
+	let v = n + 1;
+	let mut indices = vec![];
+	for y in 0..n {
+		for x in 0..n {
+			let i = y * v + x;
+			indices.extend([i, i + 1, i + v, i + v, i + 1, i + v + 1]);
+		}
+	}
+
+	PrimitiveData {
+		positions: (0..v * v).map(|i| [(i % v) as f32, (i / v) as f32, 0.0]).collect(),
+		indices,
+		..PrimitiveData::default()
+	}
+}
+
+/// the area of the triangles projected on the xy plane, counting triangles turned over as negative
+fn signed_area(positions: &[[f32; 3]], indices: &[u32]) -> f32 {
+	indices
+		.chunks(3)
+		.map(|t| {
+			let [a, b, c] = [0, 1, 2].map(|i| positions[t[i] as usize]);
+			((b[0] - a[0]) * (c[1] - a[1]) - (c[0] - a[0]) * (b[1] - a[1])) / 2.0
+		})
+		.sum()
+}
+
+#[test]
+fn simplify_keeps_outline_of_flat_grid() {
+	let mut builder = GltfBuilder::new();
+	let data = grid(16);
+	let mesh = builder.add_mesh(vec![data.clone()]).unwrap();
+	let object = builder.build();
+
+	let simplified = object.simplify_primitive(mesh, 0, SimplifyOptions { ratio: 0.25, ..SimplifyOptions::default() }).unwrap();
+	let triangles = simplified.indices.len() / 3;
+	assert!(triangles <= 128 && triangles > 0, "{triangles} triangles");
+	assert!(simplified.error < 1e-6);
+
+	// nothing folds over and the border stays in place, so the grid still covers the same area
+	assert!((signed_area(&data.positions, &simplified.indices) - 256.0).abs() < 1e-3);
+
+	// with locked borders every vertex on the border is still used
+	let locked = object.simplify_primitive(mesh, 0, SimplifyOptions { ratio: 0.0, lock_borders: true, ..SimplifyOptions::default() }).unwrap();
+	let border = (0..17 * 17).filter(|i| i % 17 == 0 || i % 17 == 16 || i / 17 == 0 || i / 17 == 16);
+	for vertex in border {
+		assert!(locked.indices.contains(&vertex), "border vertex {vertex} was removed");
+	}
+	assert!((signed_area(&data.positions, &locked.indices) - 256.0).abs() < 1e-3);
+}
+
+#[test]
+fn simplify_respects_max_error() {
+	// a ridge along the middle of the grid, collapsing across it moves the surface by a tenth of the extent
+	let mut data = grid(8);
+	for p in data.positions.iter_mut() {
+		p[2] = if p[0] == 4.0 { 1.6 } else { 0.0 };
+	}
+
+	let mut builder = GltfBuilder::new();
+	let mesh = builder.add_mesh(vec![data.clone()]).unwrap();
+	let object = builder.build();
+
+	let options = SimplifyOptions { ratio: 0.0, max_error: 0.01, ..SimplifyOptions::default() };
+	let simplified = object.simplify_primitive(mesh, 0, options).unwrap();
+	assert!(simplified.error <= 0.01);
+
+	// vertices may slide along the ridge, but no triangle cuts across it
+	for t in simplified.indices.chunks(3) {
+		let x = t.iter().map(|v| data.positions[*v as usize][0]);
+		assert!(!(x.clone().any(|x| x < 4.0) && x.clone().any(|x| x > 4.0)), "{t:?} crosses the ridge");
+	}
+	assert!(simplified.indices.len() < data.indices.len());
+
+	let coarse = object.simplify_primitive(mesh, 0, SimplifyOptions { max_error: 1.0, ..options }).unwrap();
+	assert!(coarse.indices.len() < simplified.indices.len());
+	assert!(coarse.error > 0.01);
+}
+
+#[test]
+fn lods_as_msft_lod_nodes() {
+	let mut builder = GltfBuilder::new();
+	let mesh = builder.add_mesh(vec![grid(8)]).unwrap();
+	builder.mesh_mut(mesh).name = Some("cliff".to_string());
+	let node = builder.add_mesh_node(None, NodeTransform::from_translation([1.0, 2.0, 3.0]), mesh).unwrap();
+	builder.add_scene(None, &[node]).unwrap();
+	let mut object = builder.build();
+
+	let levels = [
+		LodLevel { simplify: SimplifyOptions { ratio: 0.5, ..SimplifyOptions::default() }, screen_coverage: 0.25 },
+		LodLevel { simplify: SimplifyOptions { ratio: 0.1, ..SimplifyOptions::default() }, screen_coverage: 0.125 },
+	];
+	let meshes = object.generate_lods(node, &levels, LodOutput::MsftLod { screen_coverage: 0.5 }).unwrap();
+	assert_eq!(meshes.len(), 2);
+	assert_eq!(object.get(meshes[1]).unwrap().name.as_deref(), Some("cliff_LOD2"));
+
+	let stats = object.stats().unwrap();
+	assert!(stats.meshes[2].triangles < stats.meshes[1].triangles);
+	assert!(stats.meshes[1].triangles < stats.meshes[0].triangles);
+
+	// the level nodes are outside the hierarchy, prune keeps them
+	object.prune().unwrap();
+	let lod = object.get(node).unwrap().extension::<MsftLod>().unwrap().clone();
+	assert_eq!(lod.ids.len(), 2);
+	assert_eq!(object.get(lod.ids[0]).unwrap().translation, Some([1.0, 2.0, 3.0]));
+	assert!(object.extensions_used.contains(&"MSFT_lod".to_string()));
+
+	let json = object.to_json_value().unwrap();
+	assert_eq!(json["nodes"][0]["extras"][MSFT_SCREEN_COVERAGE], serde_json::json!([0.5, 0.25, 0.125]));
+	assert!(object.validate().is_empty());
+
+	let parsed: GltfObject = serde_json::from_value(json).unwrap();
+	assert_eq!(parsed.get(node).unwrap().extension::<MsftLod>(), Some(&lod));
+}
+
+#[test]
+fn simplify_keeps_uv_seams() {
+	// the column at x = 4 is split: the quads on its right use copies of its vertices with other texture coordinates
+	let mut data = grid(8);
+	data.tex_coords = data.positions.iter().map(|p| [p[0] / 8.0, p[1] / 8.0]).collect();
+	let seam: Vec<u32> = (0..9).map(|y| y * 9 + 4).collect();
+	for (i, v) in seam.iter().enumerate() {
+		data.positions.push(data.positions[*v as usize]);
+		data.tex_coords.push([1.0, i as f32 / 8.0]);
+	}
+	for quad in data.indices.chunks_mut(6) {
+		if quad.iter().any(|v| *v % 9 > 4) {
+			for v in quad.iter_mut().filter(|v| **v % 9 == 4) {
+				*v = 81 + *v / 9;
+			}
+		}
+	}
+
+	let mut builder = GltfBuilder::new();
+	let mesh = builder.add_mesh(vec![data]).unwrap();
+	let object = builder.build();
+
+	let simplified = object.simplify_primitive(mesh, 0, SimplifyOptions { ratio: 0.0, ..SimplifyOptions::default() }).unwrap();
+	assert!(simplified.indices.len() < 8 * 8 * 6);
+	for v in seam.iter().chain(&(81..90).collect::<Vec<u32>>()) {
+		assert!(simplified.indices.contains(v), "seam vertex {v} was removed");
+	}
+}
+
+#[test]
+fn simplify_keeps_normal_seams() {
+	// a hard edge along x = 4: the quads on its right use copies of its vertices with another normal
+	let mut data = grid(8);
+	data.normals = vec![[0.0, 0.0, 1.0]; data.positions.len()];
+	let seam: Vec<u32> = (0..9).map(|y| y * 9 + 4).collect();
+	for v in &seam {
+		data.positions.push(data.positions[*v as usize]);
+		data.normals.push([0.6, 0.0, 0.8]);
+	}
+	for quad in data.indices.chunks_mut(6) {
+		if quad.iter().any(|v| *v % 9 > 4) {
+			for v in quad.iter_mut().filter(|v| **v % 9 == 4) {
+				*v = 81 + *v / 9;
+			}
+		}
+	}
+
+	let mut builder = GltfBuilder::new();
+	let mesh = builder.add_mesh(vec![data]).unwrap();
+	let mut object = builder.build();
+
+	let (simplified, _) = object.simplify_mesh(mesh, SimplifyOptions { ratio: 0.0, ..SimplifyOptions::default() }).unwrap();
+	let primitive = object.primitive(simplified, 0).unwrap();
+	let indices = object.read_accessor_u32(primitive.accessor.unwrap()).unwrap();
+	assert!(indices.len() < 8 * 8 * 6);
+	for v in seam.iter().chain(&(81..90).collect::<Vec<u32>>()) {
+		assert!(indices.contains(v), "seam vertex {v} was removed");
+	}
+
+	// the normals are shared with the original, not regenerated
+	assert_eq!(primitive.attributes["NORMAL"], object.primitive(mesh, 0).unwrap().attributes["NORMAL"]);
+}
+
+#[test]
+fn merge_primitives_by_material() {
+	let mut builder = GltfBuilder::new();
+	let red = builder.add_material(GltfMetallicRoughness::default());
+	builder.material_mut(red).name = Some("red".to_string());
+	let blue = builder.add_material(GltfMetallicRoughness::default());
+	builder.material_mut(blue).name = Some("blue".to_string());
+
+	let painted = |material| PrimitiveData { normals: vec![[0.0, 0.0, 1.0]; 3], material: Some(material), ..triangle() };
+	let both = builder.add_mesh(vec![painted(red), painted(blue)]).unwrap();
+	let only_red = builder.add_mesh(vec![painted(red)]).unwrap();
+
+	let root = builder.add_node(None, NodeTransform::from_translation([10.0, 0.0, 0.0])).unwrap();
+	builder.add_mesh_node(Some(root), NodeTransform::from_translation([1.0, 0.0, 0.0]), both).unwrap();
+	let mirrored = NodeTransform { scale: [-1.0, 1.0, 1.0], ..NodeTransform::default() };
+	builder.add_mesh_node(Some(root), mirrored, only_red).unwrap();
+	let group = builder.add_node(Some(root), NodeTransform::from_translation([0.0, 0.0, 2.0])).unwrap();
+	builder.add_mesh_node(Some(group), NodeTransform::from_translation([0.0, 3.0, 0.0]), both).unwrap();
+	builder.add_scene(None, &[root]).unwrap();
+	let mut object = builder.build();
+
+	// a joint under the root can't be removed, which is found before anything changes
+	let mut skinned = object.clone();
+	skinned.skins.push(serde_json::from_value(serde_json::json!({ "joints": [group.value()] })).unwrap());
+	assert!(matches!(skinned.merge_by_material(root), Err(GltrError::ConstraintViolation(_))));
+	assert_eq!((skinned.meshes.len(), skinned.accessors.len()), (object.meshes.len(), object.accessors.len()));
+	assert_eq!(skinned.get(root).unwrap().child_indices(), object.get(root).unwrap().child_indices());
+
+	let children = object.merge_by_material(root).unwrap();
+	assert_eq!(object.nodes.len(), 3);
+	assert_eq!(object.get(root).unwrap().child_indices(), &children[..]);
+	assert_eq!(object.get(root).unwrap().translation, Some([10.0, 0.0, 0.0]));
+
+	let names: Vec<_> = children.iter().map(|c| object.get(*c).unwrap().name.clone().unwrap()).collect();
+	assert_eq!(names, ["red", "blue"]);
+
+	// the red triangles of both meshes, the mirrored one turned back to face +z
+	let mesh = object.get(children[0]).unwrap().mesh.unwrap();
+	let primitive = object.primitive(mesh, 0).unwrap();
+	let positions = object.read_accessor_array::<3>(primitive.attributes["POSITION"]).unwrap();
+	let indices = object.read_accessor_u32(primitive.accessor.unwrap()).unwrap();
+	assert_eq!(positions.len(), 9);
+	assert_eq!(&positions[3..6], &[[0.0, 0.0, 0.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]);
+	assert_eq!(positions[6], [0.0, 3.0, 2.0]);
+	assert_eq!(indices, vec![0, 1, 2, 3, 5, 4, 6, 7, 8]);
+	assert!(indices.chunks(3).all(|t| signed_area(&positions, t) > 0.0));
+
+	let normals = object.read_accessor_array::<3>(primitive.attributes["NORMAL"]).unwrap();
+	assert!(normals.iter().all(|n| *n == [0.0, 0.0, 1.0]));
+
+	object.prune().unwrap();
+	assert_eq!(object.meshes.len(), 2);
+	assert!(object.validate().is_empty());
+}
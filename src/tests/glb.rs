@@ -0,0 +1,112 @@
+use crate::prelude::*;
+use crate::tests::triangle;
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn glb_round_trip() {
+	let object = GltfObject::load("assets/test_assets/cliffs.gltf").expect("should load");
+
+	let glb = object.to_glb().unwrap();
+	assert_eq!(&glb[0..4], b"glTF");
+	assert_eq!(u32::from_le_bytes([glb[8], glb[9], glb[10], glb[11]]) as usize, glb.len());
+	assert_eq!(glb.len() % 4, 0);
+
+	let parsed = GltfObject::parse_glb(&glb).expect("should parse");
+	assert_eq!(parsed.buffers.0.len(), 1);
+	assert_eq!(parsed.meshes.len(), object.meshes.len());
+
+	for i in 0..object.accessors.len() {
+		assert_eq!(parsed.read_accessor_raw(Index::new(i)).unwrap(), object.read_accessor_raw(Index::new(i)).unwrap());
+	}
+
+	// the uri of the binary chunk buffer is not saved
+	let json = parsed.to_json_value().unwrap();
+	assert!(json["buffers"][0].get("uri").is_some());
+	let json: serde_json::Value = serde_json::from_slice(&glb[20..20 + u32::from_le_bytes([glb[12], glb[13], glb[14], glb[15]]) as usize]).unwrap();
+	assert!(json["buffers"][0].get("uri").is_none());
+}
+
+#[test]
+fn glb_errors() {
+	assert!(GltfObject::parse_glb(b"not a glb file").is_err());
+
+	let mut glb = GltfObject::new().to_glb().unwrap();
+	glb[4] = 1;
+	assert!(GltfObject::parse_glb(&glb).unwrap_err().to_string().contains("version 1"));
+}
+
+#[test]
+fn save_and_load_by_extension() {
+	let dir = std::env::temp_dir().join("gltr_save_and_load_by_extension");
+	fs::create_dir_all(&dir).unwrap();
+
+	let mut builder = GltfBuilder::new();
+	let mesh = builder
+		.add_mesh(vec![triangle()])
+		.unwrap();
+	builder.add_mesh_node(None, NodeTransform::default(), mesh).unwrap();
+	let object = builder.build();
+
+	object.save(dir.join("triangle.glb")).unwrap();
+	let loaded = GltfObject::load(dir.join("triangle.glb")).unwrap();
+	assert_eq!(loaded.read_accessor_array::<3>(Index::new(0)).unwrap()[1], [1.0, 0.0, 0.0]);
+
+	// unpacked to an external buffer, which is read back when embedding
+	let mut unpacked = loaded.clone();
+	for (name, bytes) in unpacked.externalize("triangle").unwrap() {
+		fs::write(dir.join(name), bytes).unwrap();
+	}
+	assert_eq!(unpacked.buffers.0[0].uri.as_deref(), Some("triangle.bin"));
+	unpacked.save(dir.join("triangle.gltf")).unwrap();
+
+	let mut loaded = GltfObject::load(dir.join("triangle.gltf")).unwrap();
+	loaded.embed_buffers(&dir).unwrap();
+	assert_eq!(loaded.read_accessor_array::<3>(Index::new(0)).unwrap()[2], [0.0, 1.0, 0.0]);
+}
+
+#[test]
+fn externalize_only_drops_the_image_views() {
+	let mut builder = GltfBuilder::new();
+	let mesh = builder
+		.add_mesh(vec![triangle()])
+		.unwrap();
+	builder.add_mesh_node(None, NodeTransform::default(), mesh).unwrap();
+	let mut object = builder.build();
+
+	// an accessor nothing uses, which externalizing has no business removing
+	let unused = object.push_accessor_array(&[[2.0, 3.0]], None).unwrap();
+	let png = object.push_buffer_view(b"\x89PNG", None).unwrap();
+	object.push(GltfImage { buffer_view: Some(png), mime_type: Some("image/png".to_string()), ..GltfImage::default() });
+
+	let (accessors, views) = (object.accessors.len(), object.buffer_views.len());
+	let files = object.externalize("scene").unwrap();
+
+	assert_eq!(files[0], ("scene_0.png".to_string(), b"\x89PNG".to_vec()));
+	assert_eq!(object.accessors.len(), accessors);
+	assert_eq!(object.buffer_views.len(), views - 1);
+
+	// the unused accessor still reads its values from the .bin file
+	let view = object.get(object.get(unused).unwrap().buffer_view.unwrap()).unwrap();
+	assert_eq!(files[1].0, "scene.bin");
+	assert_eq!(files[1].1[view.byte_offset..view.byte_offset + 8], [2f32.to_le_bytes(), 3f32.to_le_bytes()].concat());
+}
+
+#[test]
+fn embed_images_checks_every_image_first() {
+	let mut object = GltfObject::new();
+	let png = "data:image/png;base64,iVBORw==".to_string();
+	object.push(GltfImage { uri: Some(png.clone()), ..GltfImage::default() });
+	object.push(GltfImage { uri: Some("texture.bmp".to_string()), ..GltfImage::default() });
+
+	// the type of the second image is unknown, the first one is left as it was
+	assert!(matches!(object.embed_images(Path::new(".")), Err(GltrError::ConstraintViolation(_))));
+	assert_eq!(object.images[0].uri, Some(png));
+	assert!(object.images[0].mime_type.is_none() && object.buffer_views.is_empty());
+
+	object.images[1].uri = Some("data:image/jpeg;base64,/9j/".to_string());
+	object.embed_images(Path::new(".")).unwrap();
+	assert!(object.images.iter().all(|i| i.uri.is_none()));
+	assert_eq!(object.images[1].mime_type.as_deref(), Some("image/jpeg"));
+	assert_eq!(object.buffer_view_bytes(object.images[0].buffer_view.unwrap()).unwrap(), b"\x89PNG");
+}
@@ -0,0 +1,87 @@
+use crate::buffers::{BufferViewTarget, GltfBufferView};
+use crate::prelude::*;
+
+#[test]
+fn get_and_push() {
+	let mut object = GltfObject::new();
+
+	let mesh = object.push(GltfMesh {
+		name: Some("mesh".to_string()),
+		..GltfMesh::default()
+	});
+	let node = object.push(GltfNode {
+		mesh: Some(mesh),
+		..GltfNode::default()
+	});
+
+	assert_eq!(mesh.value(), 0);
+	assert_eq!(object.get(node).unwrap().mesh, Some(mesh));
+	assert_eq!(object.get(mesh).unwrap().name.as_deref(), Some("mesh"));
+
+	object.get_mut(mesh).unwrap().name = None;
+	assert!(object.meshes[0].name.is_none());
+
+	let missing: Index<GltfMesh> = Index::new(3);
+	assert!(object.get(missing).is_none());
+	match object.try_get(missing) {
+		Err(GltrError::InvalidIndex { path, index }) => assert_eq!((path.as_str(), index), ("/meshes", 3)),
+		other => panic!("expected InvalidIndex, got {other:?}"),
+	}
+
+	assert_eq!(object.indices::<GltfNode>().collect::<Vec<_>>(), vec![node]);
+	assert_eq!(format!("{missing:?} {missing}"), "Index<GltfMesh>(3) 3");
+}
+
+#[test]
+fn serialized_as_numbers() {
+	let node = GltfNode {
+		mesh: Some(Index::new(2)),
+		children: Some(vec![Index::new(1), Index::new(4)]),
+		..GltfNode::default()
+	};
+
+	let json = serde_json::to_value(&node).unwrap();
+	assert_eq!(json, serde_json::json!({ "mesh": 2, "children": [1, 4] }));
+
+	let parsed: GltfNode = serde_json::from_value(json).unwrap();
+	assert_eq!(parsed.children, node.children);
+	assert!(serde_json::from_value::<GltfNode>(serde_json::json!({ "mesh": -1 })).is_err());
+}
+
+#[test]
+fn extract_node_shares_referenced_objects() {
+	let mut object = GltfObject::new();
+
+	let indices = object.push_accessor_indices(&[0, 1, 2]).unwrap();
+	let view = object.get(indices).unwrap().buffer_view.unwrap();
+	let bytes = object.buffer_view_bytes(view).unwrap();
+	object.push(GltfBufferView::new(Index::new(0), 0, bytes.len(), Some(BufferViewTarget::ELEMENT_ARRAY_BUFFER)));
+
+	// the second accessor has the same position as the mesh, the two used to be confused
+	let other = object.push(GltfAccessor {
+		buffer_view: Some(Index::new(1)),
+		..object.get(indices).unwrap().clone()
+	});
+
+	let primitive = |accessor| GltfMeshPrimitive {
+		accessor: Some(accessor),
+		..serde_json::from_value(serde_json::json!({ "attributes": {} })).unwrap()
+	};
+
+	object.push(GltfMesh::default());
+	let mesh = object.push(GltfMesh {
+		primitives: vec![primitive(indices), primitive(indices), primitive(other)],
+		..GltfMesh::default()
+	});
+	assert_eq!(mesh.value(), other.value());
+
+	let node = object.push(GltfNode {
+		mesh: Some(mesh),
+		..GltfNode::default()
+	});
+
+	let extracted = object.extract_node(node, GltrExtractFlags::empty()).unwrap();
+	assert_eq!(extracted.accessors.len(), 2);
+	assert_eq!(extracted.buffer_views.len(), 2);
+	assert_eq!(extracted.buffers.0.len(), 1);
+}
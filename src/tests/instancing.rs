@@ -0,0 +1,95 @@
+use crate::prelude::*;
+use crate::tests::{cliffs, indices};
+
+fn cliffs_sharing_one_mesh() -> GltfObject {
+	let mut object = cliffs();
+
+	for (i, node) in object.nodes.iter_mut().enumerate() {
+		node.mesh = Some(Index::new(0));
+		node.rotation = Some([0.0, (i as f32 * 0.1).sin(), 0.0, (i as f32 * 0.1).cos()]);
+		node.scale = Some([1.0 + i as f32, 1.0, 1.0]);
+	}
+
+	object
+}
+
+fn assert_matrix_eq(a: &[f32; 16], b: &[f32; 16]) {
+	for (x, y) in a.iter().zip(b.iter()) {
+		assert!((x - y).abs() < 1e-4, "{a:?} != {b:?}");
+	}
+}
+
+#[test]
+fn collapse_repeated_meshes() {
+	let mut object = cliffs_sharing_one_mesh();
+	let original: Vec<[f32; 16]> = (0..object.nodes.len()).map(|i| object.node_world_matrix(Index::new(i)).unwrap()).collect();
+
+	// a node with morph weights is left alone
+	object.nodes[4].extras = Some(serde_json::json!({ "keep": true }));
+	object.nodes[4].weights = Some(vec![1.0]);
+
+	let instanced = object.instance_repeated_meshes().expect("nodes should be instanced");
+
+	assert_eq!(instanced, indices(&[0]));
+	assert_eq!(object.nodes.len(), 2);
+	assert_eq!(object.scenes[0].nodes, indices(&[0, 1]));
+	assert_eq!(object.extensions_used, vec!["EXT_mesh_gpu_instancing".to_string()]);
+	assert_eq!(object.node_instance_count(Index::new(0)).unwrap(), 4);
+	assert_eq!(object.node_instance_count(Index::new(1)).unwrap(), 1);
+	assert!(object.nodes[1].extras.is_some());
+
+	let instances = object.node_instance_matrices(Index::new(0)).unwrap();
+	for (matrix, expected) in instances.iter().zip(original.iter()) {
+		assert_matrix_eq(matrix, expected);
+	}
+
+	object.expand_instancing().expect("instancing should expand");
+
+	assert!(object.extensions_used.is_empty());
+	assert!(object.nodes[0].mesh.is_none());
+	assert_eq!(object.nodes[0].child_indices(), indices(&[2, 3, 4, 5]));
+
+	for (child, expected) in object.nodes[0].child_indices().iter().zip(original.iter()) {
+		assert_matrix_eq(&object.node_world_matrix(*child).unwrap(), expected);
+		assert_eq!(object.get(*child).unwrap().mesh, Some(Index::new(0)));
+	}
+}
+
+#[test]
+fn joints_and_skinned_nodes_are_not_instanced() {
+	let mut object = cliffs_sharing_one_mesh();
+	object.skins.push(serde_json::from_value(serde_json::json!({ "joints": [1, 2] })).unwrap());
+	object.nodes[3].skin = Some(Index::new(0));
+
+	let accessors = object.accessors.len();
+	let instanced = object.instance_repeated_meshes().expect("nodes should be instanced");
+
+	// only nodes 0 and 4 are free to fold together
+	assert_eq!(instanced, indices(&[0]));
+	assert_eq!(object.nodes.len(), 4);
+	assert_eq!(object.node_instance_count(Index::new(0)).unwrap(), 2);
+	assert_eq!(object.skins[0].joints, indices(&[1, 2]));
+	assert_eq!(object.accessors.len(), accessors + 3);
+}
+
+#[test]
+fn remove_nodes_remaps_references() {
+	let mut object = cliffs_sharing_one_mesh();
+	object.nodes[0].children = Some(indices(&[3, 4]));
+	object.scenes[0].nodes = indices(&[0, 1, 2]);
+
+	object.remove_nodes(&indices(&[1, 3])).unwrap();
+
+	assert_eq!(object.nodes.len(), 3);
+	assert_eq!(object.nodes[0].child_indices(), indices(&[2]));
+	assert_eq!(object.scenes[0].nodes, indices(&[0, 1]));
+	assert!(object.remove_nodes(&indices(&[3])).is_err());
+
+	// joints are referred to by their position in the skin, removing one would shift the others
+	object.skins.push(serde_json::from_value(serde_json::json!({ "joints": [0, 2] })).unwrap());
+	assert!(matches!(object.remove_nodes(&indices(&[0])), Err(GltrError::ConstraintViolation(_))));
+	assert_eq!(object.nodes.len(), 3);
+
+	object.remove_nodes(&indices(&[1])).unwrap();
+	assert_eq!(object.skins[0].joints, indices(&[0, 1]));
+}
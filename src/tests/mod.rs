@@ -1,8 +1,51 @@
+mod accessors;
+mod axes;
+mod bounds;
+mod builder;
+mod diff;
+mod errors;
+mod extensions;
+mod geometry;
+mod glb;
+mod index;
+mod instancing;
+mod model;
+mod quantization;
+mod raycast;
+mod references;
+mod round_trip;
+mod stats;
+
 use crate::buffers::{BufferViewTarget, GltfBufferView, GltfBuffers};
 use crate::prelude::*;
 use std::fs;
 use std::time::Instant;
 
+/// typed indices for a list of positions, to compare against index arrays in the model
+fn indices<T>(values: &[usize]) -> Vec<Index<T>> {
+	values.iter().copied().map(Index::new).collect()
+}
+
+/// whether two lists of floats have the same length and agree to within 1e-5
+pub(super) fn close(a: &[f32], b: &[f32]) -> bool {
+	a.len() == b.len() && a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-5)
+}
+
+/// an indexed triangle in the xy plane, with the right angle at the origin
+pub(super) fn triangle() -> PrimitiveData {
+	PrimitiveData {
+		positions: vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+		indices: vec![0, 1, 2],
+		..PrimitiveData::default()
+	}
+}
+
+/// the cliffs sample asset, which most tests start from
+pub(super) fn cliffs() -> GltfObject {
+	let content = fs::read_to_string("assets/test_assets/cliffs.gltf").expect("scene JSON file should exist");
+	GltfObject::parse_json_str(content.as_str()).expect("should be valid JSON")
+}
+
 #[test]
 fn test_decode() {
 	let content = fs::read_to_string("assets/test_assets/buffers/buffer_1.json").expect("buffer JSON file should exist");
@@ -75,13 +118,7 @@ fn test_views_2() {
 
 	let offset = buff_0_len - size;
 
-	let mut view = GltfBufferView {
-		buffer: 0,
-		byte_length: size,
-		byte_offset: offset,
-		target: Some(BufferViewTarget::ELEMENT_ARRAY_BUFFER),
-		original_index: None,
-	};
+	let mut view = GltfBufferView::new(Index::new(0), offset, size, Some(BufferViewTarget::ELEMENT_ARRAY_BUFFER));
 
 	let v = binary_buffers.get_view(&view);
 
@@ -98,7 +135,7 @@ fn test_views_2() {
 #[test]
 pub fn deserialize_full_file() {
 	let content = fs::read_to_string("assets/test_assets/cliffs.gltf").expect("scene JSON file should exist");
-	let object = GltfObject::try_parse_json_str(content.as_str());
+	let object = GltfObject::parse_json_str(content.as_str());
 	assert!(object.is_ok());
 }
 
@@ -108,7 +145,7 @@ pub fn extract_single_object() {
 	let content = fs::read_to_string("assets/test_assets/cliffs.gltf").expect("scene JSON file should exist");
 
 	let time_before_parse = Instant::now();
-	let object = GltfObject::try_parse_json_str(content.as_str());
+	let object = GltfObject::parse_json_str(content.as_str());
 
 	let time_to_parse = time_before_parse.elapsed().as_millis();
 
@@ -117,11 +154,11 @@ pub fn extract_single_object() {
 	assert!(object.is_ok());
 	let time_before_extract = Instant::now();
 
-	let new = object.unwrap().extract_node(1, GltrExtractFlags::empty().center_objects().recalculate_buffers());
+	let _new = object.unwrap().extract_node(Index::new(1), GltrExtractFlags::empty().center_objects().recalculate_buffers());
 	let time_to_extract = time_before_extract.elapsed().as_millis();
 
 	println!("{time_to_extract} to extract node");
 
-	// dbg!(&new);
+	// dbg!(&_new);
 
 }
\ No newline at end of file
@@ -0,0 +1,113 @@
+use crate::prelude::*;
+use crate::tests::indices;
+
+#[test]
+fn minimal_asset() {
+	let object = GltfObject::parse_json_str(r#"{ "asset": { "version": "2.0" } }"#).expect("only asset is required");
+
+	assert_eq!(object.asset.generator, None);
+	assert_eq!(object.scene, None);
+	assert!(object.scenes.is_empty());
+
+	assert_eq!(object.to_json_value().unwrap(), serde_json::json!({ "asset": { "version": "2.0" } }));
+}
+
+#[test]
+fn spec_defaults_are_applied() {
+	let material = serde_json::from_str::<GltfMaterial>(r#"{ "pbrMetallicRoughness": {} }"#).expect("should parse");
+	assert_eq!(material.name, None);
+	assert_eq!(material.emissive_factor, [0f32; 3]);
+	assert_eq!(material.alpha_mode, "OPAQUE");
+	assert_eq!(material.alpha_cutoff, 0.5);
+	assert!(!material.double_sided);
+
+	let pbr = material.pbr_metallic_roughness.unwrap();
+	assert_eq!(pbr.base_color_factor, [1f32; 4]);
+	assert_eq!(pbr.metallic_factor, 1.0);
+	assert_eq!(pbr.roughness_factor, 1.0);
+
+	let view = serde_json::from_str::<GltfBufferView>(r#"{ "buffer": 0, "byteLength": 4 }"#).expect("should parse");
+	assert_eq!(view.byte_offset, 0);
+
+	let buffer = serde_json::from_str::<GltfBase64Buffer>(r#"{ "byteLength": 4 }"#).expect("GLB buffers have no uri");
+	assert!(matches!(buffer.bytes(), Err(GltrError::ConstraintViolation(_))));
+}
+
+#[test]
+fn schema_coverage() {
+	let object = GltfObject::load("assets/test_assets/round_trip/schema_coverage.gltf").expect("should load");
+
+	assert_eq!(object.asset.min_version.as_deref(), Some("2.0"));
+	assert_eq!(object.asset.copyright.as_deref(), Some("CC0"));
+	assert_eq!(object.scene, Some(Index::new(1)));
+	assert_eq!(object.scenes[0].name, None);
+
+	let perspective = object.cameras[0].perspective.as_ref().unwrap();
+	assert_eq!(perspective.y_fov, 0.6605);
+	assert_eq!(perspective.aspect_ratio, Some(1.5));
+	assert_eq!(perspective.z_far, None);
+	assert_eq!(object.cameras[1].orthographic.as_ref().unwrap().z_far, 100.5);
+
+	assert_eq!(object.skins[0].joints, indices(&[2, 3]));
+	assert_eq!(object.nodes[1].skin, Some(Index::new(0)));
+	assert_eq!(object.nodes[6].weights, Some(vec![0.25, 0.75]));
+
+	let morph = &object.meshes[1];
+	assert_eq!(morph.weights, Some(vec![0.0, 0.5]));
+	let targets = morph.primitives[0].targets.as_ref().unwrap();
+	assert_eq!(targets.len(), 2);
+	assert_eq!(targets[1]["NORMAL"].value(), 5);
+
+	let animation = &object.animations[0];
+	assert_eq!(animation.samplers[0].interpolation, "STEP");
+	assert_eq!(animation.samplers[1].interpolation, "LINEAR");
+	assert!(animation.samplers[1].extras.is_some());
+
+	for (name, value) in [("asset", &object.asset.unknown_fields), ("nodes/1", &object.nodes[1].unknown_fields)] {
+		assert!(value.is_empty(), "{name} has unmodelled properties {value:?}");
+	}
+}
+
+#[test]
+fn extracted_node_is_in_a_scene() {
+	let object = GltfObject::load("assets/test_assets/round_trip/schema_coverage.gltf").expect("should load");
+
+	let extracted = object.extract_node(Index::new(6), GltrExtractFlags::empty()).expect("node should extract");
+	assert_eq!(extracted.scene, Some(Index::new(0)));
+	assert_eq!(extracted.scenes[0].nodes, indices(&[0]));
+	assert_eq!(extracted.nodes[0].camera, None);
+}
+
+#[test]
+fn sample_assets() {
+	let skin = GltfObject::load("assets/test_assets/samples/SimpleSkin.gltf").expect("should load");
+	assert_eq!(skin.skins[0].joints, indices(&[1, 2]));
+
+	// joints and weights are interleaved in one strided view
+	let attributes = &skin.meshes[0].primitives[0].attributes;
+	let joints = skin.read_accessor_array::<4>(attributes["JOINTS_0"]).unwrap();
+	assert!(joints.iter().all(|j| *j == [0.0, 1.0, 0.0, 0.0]));
+	let weights = skin.read_accessor_array::<4>(attributes["WEIGHTS_0"]).unwrap();
+	assert!(weights.iter().all(|w| (w.iter().sum::<f32>() - 1.0).abs() < 1e-6));
+
+	// each inverse bind matrix undoes the world transform of its joint at rest
+	let inverse_bind = skin.read_accessor_array::<16>(skin.skins[0].inverse_bind_matrices.unwrap()).unwrap();
+	for (joint, inverse) in skin.skins[0].joints.iter().zip(&inverse_bind) {
+		let world = skin.node_world_matrix(*joint).unwrap();
+		assert_eq!(world[13] + inverse[13], 0.0);
+	}
+
+	let sampler = &skin.animations[0].samplers[0];
+	assert_eq!(skin.read_accessor_f32(sampler.input).unwrap().len(), 12);
+	assert_eq!(skin.animations[0].channels[0].target.node, Some(Index::new(2)));
+
+	let triangle = GltfObject::load("assets/test_assets/samples/AnimatedTriangle.gltf").expect("should load");
+	let sampler = &triangle.animations[0].samplers[0];
+	assert_eq!(triangle.read_accessor_f32(sampler.input).unwrap(), vec![0.0, 0.25, 0.5, 0.75, 1.0]);
+	assert_eq!(triangle.read_accessor_array::<4>(sampler.output).unwrap()[2], [0.0, 0.0, 1.0, 0.0]);
+
+	let cameras = GltfObject::load("assets/test_assets/samples/Cameras.gltf").expect("should load");
+	assert_eq!(cameras.cameras[0].perspective.as_ref().unwrap().y_fov, 0.7);
+	assert_eq!(cameras.cameras[1].orthographic.as_ref().unwrap().z_far, 100.0);
+	assert_eq!(cameras.nodes[2].camera, Some(Index::new(1)));
+}
@@ -0,0 +1,197 @@
+use crate::prelude::*;
+use crate::tests::cliffs;
+use crate::math::{mat4_mul, transform_point};
+use crate::tests::{indices, triangle};
+
+fn attribute(object: &GltfObject, mesh: Index<GltfMesh>, name: &str) -> Index<GltfAccessor> {
+	object.get(mesh).unwrap().primitives[0].attributes[name]
+}
+
+fn world_positions(object: &GltfObject, node: usize) -> Vec<[f32; 3]> {
+	let mut node = node;
+	let mut matrix = object.node_world_matrix(Index::new(node)).unwrap();
+
+	// quantization may move the mesh to a child node
+	if object.nodes[node].mesh.is_none() {
+		node = object.nodes[node].child_indices().last().unwrap().value();
+		matrix = object.node_world_matrix(Index::new(node)).unwrap();
+	}
+
+	let mesh = object.nodes[node].mesh.unwrap();
+	object
+		.read_accessor_array::<3>(attribute(object, mesh, "POSITION"))
+		.unwrap()
+		.iter()
+		.map(|p| {
+			[
+				matrix[0] * p[0] + matrix[4] * p[1] + matrix[8] * p[2] + matrix[12],
+				matrix[1] * p[0] + matrix[5] * p[1] + matrix[9] * p[2] + matrix[13],
+				matrix[2] * p[0] + matrix[6] * p[1] + matrix[10] * p[2] + matrix[14],
+			]
+		})
+		.collect()
+}
+
+#[test]
+fn quantize_and_dequantize() {
+	let mut object = cliffs();
+	object.nodes[1].rotation = Some([0.0, 0.38268343, 0.0, 0.9238795]);
+	object.nodes[2].children = Some(vec![Index::new(3)]);
+	object.scenes[0].nodes = indices(&[0, 1, 2, 4]);
+
+	let before: Vec<_> = (0..5).map(|n| world_positions(&object, n)).collect();
+	let uv_before = object.read_accessor_array::<2>(attribute(&object, Index::new(0), "TEXCOORD_0")).unwrap();
+
+	object.quantize_meshes(QuantizeOptions::default()).expect("meshes should quantize");
+
+	assert!(object.extensions_required.contains(&"KHR_mesh_quantization".to_string()));
+	assert!(object.extensions_used.contains(&"KHR_mesh_quantization".to_string()));
+	assert_eq!(object.get(attribute(&object, Index::new(0), "POSITION")).unwrap().component_type, ComponentType::SHORT);
+	assert_eq!(object.get(attribute(&object, Index::new(0), "NORMAL")).unwrap().component_type, ComponentType::BYTE);
+	assert_eq!(object.get(attribute(&object, Index::new(0), "TEXCOORD_0")).unwrap().component_type, ComponentType::UNSIGNED_SHORT);
+
+	// node 2 has a child, its mesh is moved to a new node so the child is unaffected
+	assert!(object.nodes[2].mesh.is_none());
+	assert_eq!(object.nodes[2].child_indices().len(), 2);
+
+	let after: Vec<_> = (0..5).map(|n| world_positions(&object, n)).collect();
+	for (a, b) in before.iter().flatten().zip(after.iter().flatten()) {
+		for i in 0..3 {
+			assert!((a[i] - b[i]).abs() < 1e-2, "{a:?} != {b:?}");
+		}
+	}
+
+	let base_color = object.materials[0].pbr_metallic_roughness.as_ref().unwrap().base_color_texture.as_ref().unwrap();
+	let transform = base_color.extension::<KhrTextureTransform>().cloned().unwrap_or_default();
+	let uv_after = object.read_accessor_array::<2>(attribute(&object, Index::new(0), "TEXCOORD_0")).unwrap();
+	for (a, b) in uv_before.iter().zip(uv_after.iter()) {
+		let b = transform.transform_uv(*b);
+		assert!((a[0] - b[0]).abs() < 1e-3 && (a[1] - b[1]).abs() < 1e-3, "{a:?} != {b:?}");
+	}
+
+	object.dequantize_meshes().expect("meshes should dequantize");
+
+	assert_eq!(object.get(attribute(&object, Index::new(0), "POSITION")).unwrap().component_type, ComponentType::FLOAT);
+	assert!(!object.extensions_used.contains(&"KHR_mesh_quantization".to_string()));
+	assert!(!object.extensions_required.contains(&"KHR_mesh_quantization".to_string()));
+
+	let dequantized: Vec<_> = (0..5).map(|n| world_positions(&object, n)).collect();
+	assert_eq!(after, dequantized);
+}
+
+/// the world positions of every instance of the mesh of an instanced node
+fn instance_positions(object: &GltfObject, node: usize) -> Vec<[f32; 3]> {
+	let world = object.node_world_matrix(Index::new(node)).unwrap();
+	let mesh = object.nodes[node].mesh.unwrap();
+	let positions = object.read_accessor_array::<3>(attribute(object, mesh, "POSITION")).unwrap();
+
+	let mut out = vec![];
+	for instance in object.node_instance_matrices(Index::new(node)).unwrap() {
+		let matrix = mat4_mul(&world, &instance);
+		out.extend(positions.iter().map(|p| transform_point(&matrix, *p)));
+	}
+	out
+}
+
+#[test]
+fn quantize_animated_skinned_and_instanced_nodes() {
+	let mut object = cliffs();
+	let translation = object.nodes[0].translation;
+
+	let input = object.push_accessor_f32(&[0.0, 1.0], "SCALAR", ComponentType::FLOAT, false, None).unwrap();
+	let output = object.push_accessor_array::<3>(&[[0.0, 0.0, 0.0], [0.0, 5.0, 0.0]], None).unwrap();
+	object.animations.push(
+		serde_json::from_value(serde_json::json!({
+			"channels": [{ "sampler": 0, "target": { "node": 0, "path": "translation" } }],
+			"samplers": [{ "input": input.value(), "output": output.value() }],
+		}))
+		.unwrap(),
+	);
+
+	let offsets = object.push_accessor_array::<3>(&[[0.0, 0.0, 0.0], [5.0, 0.0, 0.0]], None).unwrap();
+	let rotations = object.push_accessor_array::<4>(&[[0.0, 0.0, 0.0, 1.0], [0.0, 0.38268343, 0.0, 0.9238795]], None).unwrap();
+	object.nodes[1].set_extension(ExtMeshGpuInstancing {
+		attributes: [("TRANSLATION".to_string(), offsets), ("ROTATION".to_string(), rotations)].into(),
+		..ExtMeshGpuInstancing::default()
+	});
+
+	object.skins.push(serde_json::from_value(serde_json::json!({ "joints": [3] })).unwrap());
+	object.nodes[2].skin = Some(Index::new(0));
+
+	let before = (world_positions(&object, 0), instance_positions(&object, 1));
+	object.quantize_meshes(QuantizeOptions::default()).expect("meshes should quantize");
+
+	// the animation would overwrite a folded translation, the mesh moves to a child instead
+	assert!(object.nodes[0].mesh.is_none());
+	assert_eq!(object.nodes[0].translation, translation);
+
+	let after = (world_positions(&object, 0), instance_positions(&object, 1));
+	for (a, b) in before.0.iter().chain(&before.1).zip(after.0.iter().chain(&after.1)) {
+		assert!((0..3).all(|i| (a[i] - b[i]).abs() < 1e-2), "{a:?} != {b:?}");
+	}
+	assert_eq!(object.get(attribute(&object, Index::new(1), "POSITION")).unwrap().component_type, ComponentType::SHORT);
+
+	// the transform of a skinned node is ignored, its mesh keeps float positions
+	assert_eq!(object.get(attribute(&object, Index::new(2), "POSITION")).unwrap().component_type, ComponentType::FLOAT);
+	assert!(object.validate().is_empty());
+}
+
+#[test]
+fn quantize_keeps_cameras_and_lights_in_place() {
+	let mut object = cliffs();
+	object.cameras.push(serde_json::from_value(serde_json::json!({ "type": "perspective", "perspective": { "yfov": 0.7, "znear": 0.1 } })).unwrap());
+	object.nodes[3].camera = Some(Index::new(0));
+	let light = object.add_light(KhrLight::new(KhrLightType::Point));
+	object.nodes[4].set_extension(KhrLightsPunctualNode::new(light));
+
+	let transforms = (object.node_world_matrix(Index::new(3)).unwrap(), object.node_world_matrix(Index::new(4)).unwrap());
+	let before = (world_positions(&object, 3), world_positions(&object, 4));
+	object.quantize_meshes(QuantizeOptions::default()).expect("meshes should quantize");
+
+	// the dequantization goes to a new child holding the mesh, the camera and light don't move
+	assert_eq!((object.node_world_matrix(Index::new(3)).unwrap(), object.node_world_matrix(Index::new(4)).unwrap()), transforms);
+	assert!(object.nodes[3].mesh.is_none() && object.nodes[4].mesh.is_none());
+
+	let after = (world_positions(&object, 3), world_positions(&object, 4));
+	for (a, b) in before.0.iter().chain(&before.1).zip(after.0.iter().chain(&after.1)) {
+		assert!((0..3).all(|i| (a[i] - b[i]).abs() < 1e-2), "{a:?} != {b:?}");
+	}
+}
+
+#[test]
+fn quantize_refuses_untyped_instancing() {
+	let mut object = cliffs();
+	object.nodes[1].extensions.get_or_insert_with(GltfExtensions::new).insert_raw(ExtMeshGpuInstancing::NAME, serde_json::json!({}));
+	assert!(object.nodes[1].extension::<ExtMeshGpuInstancing>().is_none());
+
+	let accessors = object.accessors.len();
+	assert!(matches!(object.quantize_meshes(QuantizeOptions::default()), Err(GltrError::ConstraintViolation(_))));
+	assert_eq!(object.accessors.len(), accessors);
+}
+
+#[test]
+fn quantize_keeps_sets_of_untyped_textures_float() {
+	let quantized_type = |sheen: bool| {
+		let mut builder = GltfBuilder::new();
+		let mesh = builder
+			.add_mesh(vec![PrimitiveData { tex_coords: vec![[0.0, 0.0], [4.0, 0.0], [0.0, 2.0]], ..triangle() }])
+			.unwrap();
+		let node = builder.add_mesh_node(None, NodeTransform::default(), mesh).unwrap();
+		builder.add_scene(None, &[node]).unwrap();
+		let mut object = builder.build();
+
+		let mut material = serde_json::json!({ "pbrMetallicRoughness": { "baseColorTexture": { "index": 0 } } });
+		if sheen {
+			material["extensions"] = serde_json::json!({ "KHR_materials_sheen": { "sheenColorTexture": { "index": 0 } } });
+		}
+		object.materials.push(serde_json::from_value(material).unwrap());
+		object.meshes[0].primitives[0].material = Some(Index::new(0));
+
+		object.quantize_meshes(QuantizeOptions::default()).unwrap();
+		object.get(attribute(&object, Index::new(0), "TEXCOORD_0")).unwrap().component_type
+	};
+
+	// the sheen texture has no transform to take the dequantization, so the coordinates can't be remapped
+	assert_eq!(quantized_type(false), ComponentType::UNSIGNED_SHORT);
+	assert_eq!(quantized_type(true), ComponentType::FLOAT);
+}
@@ -0,0 +1,148 @@
+use crate::prelude::*;
+use crate::tests::{cliffs, close};
+
+/// a unit quad facing +y, scaled to 10 on the ground and again as a shelf 2 above its corner
+fn ground_and_shelf() -> (GltfObject, NodeId, NodeId) {
+	let mut builder = GltfBuilder::new();
+	let quad = builder
+		.add_mesh(vec![PrimitiveData {
+			positions: vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [1.0, 0.0, 1.0]],
+			indices: vec![0, 2, 1, 1, 2, 3],
+			..PrimitiveData::default()
+		}])
+		.unwrap();
+
+	let ground = builder.add_mesh_node(None, NodeTransform { scale: [10.0; 3], ..NodeTransform::default() }, quad).unwrap();
+	let shelf = builder.add_mesh_node(None, NodeTransform::from_translation([0.0, 2.0, 0.0]), quad).unwrap();
+	builder.add_scene(None, &[ground, shelf]).unwrap();
+
+	(builder.build(), ground, shelf)
+}
+
+#[test]
+fn raycast_hits_nearest_triangle() {
+	let (object, ground, shelf) = ground_and_shelf();
+	let bvh = object.scene_bvh(Index::new(0)).unwrap();
+	assert_eq!(bvh.triangle_count(), 4);
+
+	let hit = bvh.raycast([0.5, 5.0, 0.25], [0.0, -3.0, 0.0]).unwrap();
+	assert_eq!(hit.node, shelf);
+	assert_eq!(hit.instance, None);
+	assert!(close(&[hit.distance], &[3.0]));
+	assert!(close(&hit.point, &[0.5, 2.0, 0.25]));
+	assert!(close(&[hit.barycentrics.iter().sum()], &[1.0]));
+
+	let hit = bvh.raycast([5.0, 5.0, 6.0], [0.0, -1.0, 0.0]).unwrap();
+	assert_eq!(hit.node, ground);
+	assert_eq!(hit.triangle, 1);
+	assert!(close(&hit.point, &[5.0, 0.0, 6.0]));
+
+	// triangles are hit from behind too
+	let hit = bvh.raycast([5.0, -1.0, 5.0], [0.0, 1.0, 0.0]).unwrap();
+	assert_eq!(hit.node, ground);
+	assert!(close(&[hit.distance], &[1.0]));
+
+	assert!(bvh.raycast([5.0, 5.0, 5.0], [0.0, 1.0, 0.0]).is_none());
+	assert!(bvh.raycast([5.0, 5.0, 5.0], [0.0, 0.0, 0.0]).is_none());
+}
+
+#[test]
+fn closest_point_on_geometry() {
+	let (object, ground, shelf) = ground_and_shelf();
+	let bvh = object.scene_bvh(Index::new(0)).unwrap();
+
+	let closest = bvh.closest_point([5.0, 1.0, 5.0]).unwrap();
+	assert_eq!(closest.node, ground);
+	assert!(close(&closest.point, &[5.0, 0.0, 5.0]));
+
+	let closest = bvh.closest_point([0.5, 2.5, 0.5]).unwrap();
+	assert_eq!(closest.node, shelf);
+	assert!(close(&[closest.distance], &[0.5]));
+
+	// past the corner of the ground
+	let closest = bvh.closest_point([-3.0, -4.0, 5.0]).unwrap();
+	assert!(close(&closest.point, &[0.0, 0.0, 5.0]));
+	assert!(close(&[closest.distance], &[5.0]));
+}
+
+#[test]
+fn raycast_reports_the_instance_hit() {
+	let (mut object, _, _) = ground_and_shelf();
+	let instanced = object.instance_repeated_meshes().unwrap();
+	assert_eq!(instanced.len(), 1);
+
+	let bvh = object.scene_bvh(Index::new(0)).unwrap();
+	assert_eq!(bvh.triangle_count(), 4);
+
+	let hit = bvh.raycast([0.5, 5.0, 0.25], [0.0, -1.0, 0.0]).unwrap();
+	assert_eq!(hit.node, instanced[0]);
+	assert_eq!(hit.instance, Some(1));
+	assert!(close(&hit.point, &[0.5, 2.0, 0.25]));
+
+	let hit = bvh.raycast([5.0, 5.0, 6.0], [0.0, -1.0, 0.0]).unwrap();
+	assert_eq!(hit.instance, Some(0));
+	assert!(close(&hit.point, &[5.0, 0.0, 6.0]));
+}
+
+#[test]
+fn raycast_matches_brute_force_on_cliffs() {
+	let object = cliffs();
+	let scene = object.scene.unwrap_or(Index::new(0));
+
+	let bvh = object.scene_bvh(scene).unwrap();
+	let bounds = bvh.bounds().unwrap();
+	assert_eq!(Some(bounds), object.scene_bounds(scene, true).unwrap());
+
+	// every triangle in world space, to test against one by one
+	let mut triangles = vec![];
+	for root in &object.get(scene).unwrap().nodes {
+		for node in object.node_subtree(*root).unwrap() {
+			let Some(mesh) = object.nodes[node.value()].mesh else { continue };
+			let world = object.node_world_matrix(node).unwrap();
+			for primitive in 0..object.meshes[mesh.value()].primitives.len() {
+				let positions = object.read_accessor_array::<3>(object.primitive(mesh, primitive).unwrap().attributes["POSITION"]).unwrap();
+				for t in object.primitive_triangles(mesh, primitive).unwrap() {
+					triangles.push(t.map(|i| Aabb { min: positions[i as usize], max: positions[i as usize] }.transformed(&world).min));
+				}
+			}
+		}
+	}
+	assert_eq!(triangles.len(), bvh.triangle_count());
+
+	let sub = |a: [f32; 3], b: [f32; 3]| [a[0] - b[0], a[1] - b[1], a[2] - b[2]];
+	let cross = |a: [f32; 3], b: [f32; 3]| [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]];
+	let dot = |a: [f32; 3], b: [f32; 3]| a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+
+	let mut hits = 0;
+	let [cx, cy, cz] = bounds.center();
+	let [sx, sy, sz] = bounds.size();
+	for i in 0..64 {
+		let (u, v) = ((i % 8) as f32 / 7.0 - 0.5, (i / 8) as f32 / 7.0 - 0.5);
+		let origin = [cx + u * sx * 0.9, cy + sy, cz + v * sz * 0.9];
+		let dir = [u * 0.2, -1.0, 0.1];
+		let length = dot(dir, dir).sqrt();
+
+		let expected = triangles
+			.iter()
+			.filter_map(|[a, b, c]| {
+				let (ab, ac) = (sub(*b, *a), sub(*c, *a));
+				let p = cross(dir, ac);
+				let det = dot(ab, p);
+				let o = sub(origin, *a);
+				let (u, q) = (dot(o, p) / det, cross(o, ab));
+				let v = dot(dir, q) / det;
+				let t = dot(ac, q) / det;
+				(det != 0.0 && u >= 0.0 && v >= 0.0 && u + v <= 1.0 && t >= 0.0).then_some(t * length)
+			})
+			.min_by(f32::total_cmp);
+
+		let hit = bvh.raycast(origin, dir);
+		assert_eq!(hit.is_some(), expected.is_some());
+		if let (Some(hit), Some(expected)) = (hit, expected) {
+			assert!((hit.distance - expected).abs() < 1e-3 * expected.max(1.0));
+			assert!(bvh.closest_point(hit.point).unwrap().distance < 1e-3);
+			hits += 1;
+		}
+	}
+	assert!(hits > 32, "{hits} of 64 rays hit the cliffs");
+}
@@ -0,0 +1,139 @@
+use crate::prelude::*;
+use crate::tests::triangle;
+
+/// two meshes with their own material, only the second one is part of the scene
+fn scene_with_unused_mesh() -> (GltfObject, MeshId) {
+	let mut builder = GltfBuilder::new();
+
+	let unused_material = builder.add_material(GltfMetallicRoughness::default());
+	builder.add_mesh(vec![PrimitiveData { material: Some(unused_material), ..triangle() }]).unwrap();
+
+	let material = builder.add_material(GltfMetallicRoughness { metallic_factor: 0.25, ..GltfMetallicRoughness::default() });
+	let mesh = builder.add_mesh(vec![PrimitiveData { material: Some(material), ..triangle() }]).unwrap();
+
+	builder.add_node(None, NodeTransform::default()).unwrap();
+	let node = builder.add_mesh_node(None, NodeTransform::default(), mesh).unwrap();
+	builder.add_scene(None, &[node]).unwrap();
+
+	(builder.build(), mesh)
+}
+
+#[test]
+fn prune_removes_unused_objects() {
+	let (mut object, mesh) = scene_with_unused_mesh();
+	let positions = object.read_accessor_array::<3>(object.meshes[mesh.value()].primitives[0].attributes["POSITION"]).unwrap();
+	let bytes_before = object.buffers.0[0].byte_length;
+
+	// the node outside the scene, the first mesh, its material, its two accessors and their buffer views
+	assert_eq!(object.prune().unwrap(), 7);
+
+	assert_eq!(object.nodes.len(), 1);
+	assert_eq!(object.scenes[0].nodes, vec![Index::new(0)]);
+	assert_eq!(object.meshes.len(), 1);
+	assert_eq!(object.materials.len(), 1);
+	assert_eq!(object.materials[0].pbr_metallic_roughness.as_ref().unwrap().metallic_factor, 0.25);
+	assert_eq!(object.accessors.len(), 2);
+	assert_eq!(object.buffer_views.len(), 2);
+	assert!(object.buffers.0[0].byte_length < bytes_before);
+
+	let primitive = &object.meshes[0].primitives[0];
+	assert_eq!(primitive.material, Some(Index::new(0)));
+	assert_eq!(object.read_accessor_array::<3>(primitive.attributes["POSITION"]).unwrap(), positions);
+	assert!(object.validate().is_empty());
+}
+
+#[test]
+fn merge_offsets_indices() {
+	let (mut object, _) = scene_with_unused_mesh();
+	object.prune().unwrap();
+	let other = object.clone();
+
+	object.merge(other);
+
+	assert_eq!(object.meshes.len(), 2);
+	assert_eq!(object.buffers.0.len(), 2);
+	assert_eq!(object.scenes.len(), 2);
+	assert_eq!(object.scenes[0].nodes, vec![Index::new(0), Index::new(1)]);
+	assert_eq!(object.nodes[1].mesh, Some(Index::new(1)));
+	assert_eq!(object.meshes[1].primitives[0].material, Some(Index::new(1)));
+	assert_eq!(object.buffer_views[2].buffer, Index::new(1));
+	assert!(object.validate().is_empty());
+}
+
+#[test]
+fn validate_reports_problems() {
+	let (mut object, mesh) = scene_with_unused_mesh();
+	assert!(object.validate().is_empty());
+
+	object.meshes[mesh.value()].primitives[0].material = Some(Index::new(9));
+	assert!(matches!(object.clone().prune(), Err(GltrError::InvalidIndex { index: 9, .. })));
+
+	object.buffer_views[0].byte_length += 1000;
+	object.buffer_views[1].byte_stride = Some(6);
+	object.nodes[1].children = Some(vec![Index::new(1)]);
+	object.extensions_required.push("KHR_unknown".to_string());
+
+	let errors: Vec<String> = object.validate().iter().map(|e| e.to_string()).collect();
+	// the stride also makes the accessor reading the view run past its end
+	assert_eq!(errors.len(), 6, "{errors:?}");
+	assert!(errors[0].starts_with("/meshes/1/primitives/0/material"));
+	assert!(errors.iter().any(|e| e.starts_with("/bufferViews/0 reads")));
+	assert!(errors.iter().any(|e| e.starts_with("/accessors/1 reads")));
+	assert!(errors.iter().any(|e| e.contains("own ancestor")));
+}
+
+#[test]
+fn extracted_node_is_valid() {
+	let (object, _) = scene_with_unused_mesh();
+
+	let extracted = object.extract_node(Index::new(1), GltrExtractFlags::empty().recalculate_buffers()).unwrap();
+	assert_eq!(extracted.meshes.len(), 1);
+	assert_eq!(extracted.materials.len(), 1);
+	assert!(extracted.buffers.0[0].byte_length < object.buffers.0[0].byte_length);
+	assert!(extracted.validate().is_empty());
+
+	// without recalculating, the buffer is shared with the source document
+	let extracted = object.extract_node(Index::new(1), GltrExtractFlags::empty()).unwrap();
+	assert_eq!(extracted.buffers.0[0].uri, object.buffers.0[0].uri);
+}
+
+#[test]
+fn prune_follows_untyped_khronos_extensions() {
+	let (mut object, mesh) = scene_with_unused_mesh();
+	for uri in ["a.png", "b.png"] {
+		let image = object.push(GltfImage { uri: Some(uri.to_string()), ..GltfImage::default() });
+		object.push(GltfTexture { source_image_index: Some(image), ..GltfTexture::default() });
+	}
+
+	let material = object.meshes[mesh.value()].primitives[0].material.unwrap();
+	let sheen = serde_json::json!({ "sheenColorFactor": [1, 1, 1], "sheenColorTexture": { "index": 1 } });
+	object.materials[material.value()].extensions.get_or_insert_with(GltfExtensions::new).insert_raw("KHR_materials_sheen", sheen);
+	assert!(object.unchecked_extensions().is_empty());
+
+	object.prune().unwrap();
+
+	assert_eq!(object.textures.len(), 1);
+	assert_eq!(object.images[0].uri.as_deref(), Some("b.png"));
+	let sheen = object.materials[0].extensions.as_ref().unwrap().get_raw("KHR_materials_sheen").unwrap().to_value();
+	assert_eq!(sheen["sheenColorTexture"]["index"], 0);
+	assert!(object.validate().is_empty());
+
+	object.materials[0].extensions.as_mut().unwrap().insert_raw("KHR_materials_sheen", serde_json::json!({ "sheenColorTexture": { "index": 4 } }));
+	assert!(matches!(&object.validate()[..], [GltrError::InvalidIndex { index: 4, .. }]));
+}
+
+#[test]
+fn prune_refuses_unknown_extensions() {
+	let (mut object, mesh) = scene_with_unused_mesh();
+	let material = object.meshes[mesh.value()].primitives[0].material.unwrap();
+	object.materials[material.value()]
+		.extensions
+		.get_or_insert_with(GltfExtensions::new)
+		.insert_raw("VENDOR_material", serde_json::json!({ "texture": 0 }));
+
+	assert_eq!(object.unchecked_extensions(), vec!["/materials/1/extensions/VENDOR_material".to_string()]);
+	assert!(matches!(object.prune(), Err(GltrError::ConstraintViolation(_))));
+	assert_eq!(object.meshes.len(), 2);
+	assert!(object.extract_node(Index::new(1), GltrExtractFlags::empty()).is_err());
+	assert!(object.externalize("scene").is_err());
+}
@@ -0,0 +1,135 @@
+use crate::prelude::*;
+use serde_json::{json, Value};
+use std::fs;
+
+const CORPUS: &[&str] = &[
+	"assets/test_assets/cliffs.gltf",
+	"assets/test_assets/round_trip/unknown_properties.gltf",
+	"assets/test_assets/round_trip/extensions.gltf",
+	"assets/test_assets/round_trip/schema_coverage.gltf",
+	"assets/test_assets/samples/AnimatedTriangle.gltf",
+	"assets/test_assets/samples/Cameras.gltf",
+	"assets/test_assets/samples/SimpleSkin.gltf",
+];
+
+/// properties that may be written out when the source left them undefined, with the value the spec defines for them
+fn spec_defaults() -> Vec<(&'static str, Value)> {
+	vec![
+		("alphaMode", json!("OPAQUE")),
+		("alphaCutoff", json!(0.5)),
+		("baseColorFactor", json!([1.0, 1.0, 1.0, 1.0])),
+		("metallicFactor", json!(1.0)),
+		("roughnessFactor", json!(1.0)),
+		("emissiveFactor", json!([0.0, 0.0, 0.0])),
+		("doubleSided", json!(false)),
+		("byteOffset", json!(0)),
+		("texCoord", json!(0)),
+		("scale", json!(1.0)),
+		("strength", json!(1.0)),
+		("normalized", json!(false)),
+		("interpolation", json!("LINEAR")),
+		("clearcoatFactor", json!(0.0)),
+		("clearcoatRoughnessFactor", json!(0.0)),
+		("color", json!([1.0, 1.0, 1.0])),
+		("intensity", json!(1.0)),
+		("innerConeAngle", json!(0.0)),
+		("outerConeAngle", json!(std::f64::consts::FRAC_PI_4)),
+		("offset", json!([0.0, 0.0])),
+		("rotation", json!(0.0)),
+		("scale", json!([1.0, 1.0])),
+	]
+}
+
+/// numbers are compared at f32 precision, which is what the model stores them as
+fn same_number(a: f64, b: f64) -> bool {
+	(a - b).abs() <= 1e-6 * a.abs().max(b.abs()).max(1.0)
+}
+
+fn assert_equivalent(original: &Value, saved: &Value, path: &str) {
+	match (original, saved) {
+		(Value::Object(original), Value::Object(saved)) => {
+			for (key, value) in original {
+				match saved.get(key) {
+					None => panic!("{path}/{key} was dropped"),
+					Some(s) => assert_equivalent(value, s, &format!("{path}/{key}")),
+				}
+			}
+
+			for (key, value) in saved {
+				if original.contains_key(key) {
+					continue;
+				}
+
+				let is_default = spec_defaults()
+					.iter()
+					.any(|(k, d)| k == key && values_equal(d, value));
+				assert!(is_default, "{path}/{key} was added with {value}");
+			}
+		}
+		(Value::Array(original), Value::Array(saved)) => {
+			assert_eq!(original.len(), saved.len(), "{path} changed length");
+			for (i, (o, s)) in original.iter().zip(saved).enumerate() {
+				assert_equivalent(o, s, &format!("{path}/{i}"));
+			}
+		}
+		(Value::Number(o), Value::Number(s)) => {
+			assert!(same_number(o.as_f64().unwrap(), s.as_f64().unwrap()), "{path} changed from {o} to {s}")
+		}
+		_ => assert_eq!(original, saved, "{path} changed"),
+	}
+}
+
+fn values_equal(a: &Value, b: &Value) -> bool {
+	match (a, b) {
+		(Value::Number(a), Value::Number(b)) => same_number(a.as_f64().unwrap(), b.as_f64().unwrap()),
+		(Value::Array(a), Value::Array(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| values_equal(a, b)),
+		_ => a == b,
+	}
+}
+
+#[test]
+fn round_trip_corpus() {
+	for path in CORPUS {
+		let content = fs::read_to_string(path).expect("sample asset should exist");
+		let original: Value = serde_json::from_str(&content).expect("should be valid JSON");
+
+		let object = GltfObject::parse_json_str(&content).unwrap_or_else(|e| panic!("{path}: {e}"));
+		let saved_string = object.to_json_string().expect("should serialize");
+		let saved: Value = serde_json::from_str(&saved_string).expect("should be valid JSON");
+
+		assert_equivalent(&original, &saved, path);
+
+		// saving a saved file changes nothing at all
+		let again = GltfObject::parse_json_str(&saved_string).expect("saved asset should parse");
+		assert_eq!(again.to_json_string().unwrap(), saved_string, "{path} is not stable across saves");
+	}
+}
+
+#[test]
+fn unknown_properties_are_kept() {
+	let object = GltfObject::load("assets/test_assets/round_trip/unknown_properties.gltf").expect("should load");
+
+	assert_eq!(object.unknown_fields["VENDOR_root_property"], json!({ "nested": [1, 2, { "deep": null }] }));
+	assert_eq!(object.nodes[0].unknown_fields["VENDOR_flag"], json!(true));
+
+	let primitive = &object.meshes[0].primitives[0];
+	let variants = primitive.extensions.as_ref().unwrap().get_raw("KHR_materials_variants").unwrap();
+	assert_eq!(variants.to_value()["mappings"][0]["material"], json!(1));
+}
+
+#[test]
+fn known_extension_keeps_unmodelled_properties() {
+	let object = GltfObject::load("assets/test_assets/round_trip/extensions.gltf").expect("should load");
+
+	let base_color = object.materials[0].pbr_metallic_roughness.as_ref().unwrap().base_color_texture.as_ref().unwrap();
+	let transform = base_color.extension::<KhrTextureTransform>().expect("transform should be typed");
+	assert_eq!(transform.rotation, 1.5);
+	assert_eq!(transform.extras, Some(json!({ "source": "uv tool" })));
+
+	// a vendor property on one light doesn't hide the lights
+	assert_eq!(object.lights().len(), 2);
+	assert_eq!(object.lights()[0].unknown_fields["VENDOR_light"], json!(1));
+	assert_eq!(object.lights()[1].name.as_deref(), Some("sun"));
+	assert_eq!(object.node_light(Index::new(0)).unwrap().unwrap().intensity, 20.0);
+	assert!(object.materials[0].extension::<KhrMaterialsClearcoat>().is_some());
+}
@@ -0,0 +1,70 @@
+use crate::buffers::GltfBufferView;
+use crate::prelude::*;
+
+const PNG_HEADER: [u8; 24] = [
+	0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n', 0, 0, 0, 13, b'I', b'H', b'D', b'R', 0, 0, 1, 0, 0, 0, 0, 64,
+];
+
+fn strip(count: usize) -> PrimitiveData {
+	PrimitiveData {
+		positions: (0..count).map(|i| [i as f32, (i % 2) as f32, 0.0]).collect(),
+		..PrimitiveData::default()
+	}
+}
+
+#[test]
+fn counts_respect_primitive_mode() {
+	let mut builder = GltfBuilder::new();
+
+	let texture = builder.add_texture_from_png(&PNG_HEADER).unwrap();
+	let material = builder.add_material(GltfMetallicRoughness {
+		base_color_texture: Some(GltfTextureInfo::new(texture)),
+		..GltfMetallicRoughness::default()
+	});
+
+	let mesh = builder
+		.add_mesh(vec![strip(6), PrimitiveData { indices: vec![0, 1, 2, 2, 1, 3], material: Some(material), ..strip(4) }])
+		.unwrap();
+	builder.mesh_mut(mesh).primitives[0].mode = Some(PrimitiveMode::TRIANGLE_STRIP);
+
+	let root = builder.add_node(None, NodeTransform::default()).unwrap();
+	builder.add_mesh_node(Some(root), NodeTransform::default(), mesh).unwrap();
+	builder.add_mesh_node(Some(root), NodeTransform::default(), mesh).unwrap();
+	builder.add_scene(None, &[root]).unwrap();
+
+	let mut object = builder.build();
+
+	// a view nothing reads, at the end of the buffer
+	let mut bytes = object.buffers.0[0].bytes().unwrap();
+	let length = bytes.len();
+	bytes.resize(length + 8, 0);
+	object.buffers.0[0].set_bytes(&bytes);
+	object.push(GltfBufferView::new(Index::new(0), length + 4, 4, None));
+
+	let stats = object.stats().unwrap();
+
+	let primitives = &stats.meshes[0].primitives;
+	assert_eq!((primitives[0].vertices, primitives[0].triangles), (6, 4));
+	assert_eq!((primitives[1].indices, primitives[1].triangles), (Some(6), 2));
+	assert_eq!(stats.meshes[0].triangles, 6);
+
+	assert_eq!(stats.draw_calls, 4);
+	assert_eq!(stats.scene_triangles, 12);
+	assert_eq!(stats.scene_vertices, 20);
+
+	assert_eq!(stats.buffers[0].unused_bytes, 4);
+	assert_eq!(stats.buffer_views.last().unwrap().users, 0);
+
+	assert_eq!((stats.images[0].width, stats.images[0].height), (Some(256), Some(64)));
+	assert_eq!(stats.images[0].byte_length, Some(PNG_HEADER.len()));
+
+	let root = &stats.root_nodes[0];
+	assert_eq!((root.nodes, root.draw_calls), (3, 4));
+	// both nodes share the accessors of the mesh, they are only counted once
+	let geometry: usize = stats.accessors.iter().map(|a| a.byte_length).sum();
+	assert_eq!(root.geometry_bytes, geometry);
+	assert_eq!(root.image_bytes, PNG_HEADER.len());
+
+	let json = serde_json::to_value(&stats).unwrap();
+	assert_eq!(json["rootNodes"][0]["drawCalls"], 4);
+}